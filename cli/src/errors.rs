@@ -17,6 +17,7 @@ error_chain! {
     foreign_links {
         Io(::std::io::Error);
         NumParseInt(::std::num::ParseIntError);
+        NumParseFloat(::std::num::ParseFloatError);
         SerdeJson(serde_json::Error);
         NdarrayShape(ndarray::ShapeError);
     }
@@ -85,7 +85,7 @@ fn main() {
             "Hint the model format ('onnx' or 'tf') instead of guess from extension.")
 
         (@arg input: -i --input +takes_value
-            "Set input value (@file or 3x4xi32)")
+            "Set input value (@file, @file.npy, @file.npz:name or 3x4xi32)")
 
         (@arg input_node: --("input-node") +takes_value
             "Override input nodes names (auto-detects otherwise).")
@@ -137,7 +137,37 @@ fn main() {
             Arg::with_name("assert-output")
                 .takes_value(true)
                 .long("assert-output")
-                .help("Fact to check the ouput tensor against (@filename, or 3x4xf32)"),
+                .help("Fact to check the ouput tensor against (@filename, @file.npy, @file.npz:name, or 3x4xf32)"),
+        )
+        .arg(
+            Arg::with_name("check-values")
+                .long("check-values")
+                .help("Stop at the first node producing NaN or infinite values"),
+        )
+        .arg(
+            Arg::with_name("max-abs")
+                .takes_value(true)
+                .long("max-abs")
+                .help("With --check-values, also stop on values exceeding this magnitude"),
+        )
+        .arg(
+            Arg::with_name("dump")
+                .takes_value(true)
+                .long("dump")
+                .help("Dump intermediate tensors to a directory, one file per outlet"),
+        )
+        .arg(
+            Arg::with_name("dump-nodes")
+                .takes_value(true)
+                .long("dump-nodes")
+                .use_delimiter(true)
+                .help("Restrict --dump to these comma-separated nodes"),
+        )
+        .arg(
+            Arg::with_name("compare-dump")
+                .takes_value(true)
+                .long("compare-dump")
+                .help("Compare intermediate tensors against a previous --dump directory"),
         );
     app = app.subcommand(output_options(run));
 
@@ -417,7 +447,21 @@ fn handle(matches: clap::ArgMatches) -> CliResult<()> {
             let assert_outputs: Option<Vec<TensorFact>> = m
                 .values_of("assert-output")
                 .map(|vs| vs.map(|v| tensor::for_string(v).unwrap()).collect());
-            run::handle(params, assert_outputs, OutputParameters::from_clap(m)?)
+            let checks = if m.is_present("check-values") {
+                Some(tfdeploy::ValueChecks {
+                    max_abs: m.value_of("max-abs").map(f64::from_str).inside_out()?,
+                })
+            } else {
+                None
+            };
+            let dump = run::DumpParameters {
+                dump: m.value_of("dump").map(String::from),
+                dump_nodes: m
+                    .values_of("dump-nodes")
+                    .map(|vs| vs.map(String::from).collect()),
+                compare_dump: m.value_of("compare-dump").map(String::from),
+            };
+            run::handle(params, assert_outputs, checks, dump, OutputParameters::from_clap(m)?)
         }
 
         ("optimize-check", Some(m)) => {
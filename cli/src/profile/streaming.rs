@@ -179,6 +179,11 @@ pub fn handle_buffering(params: Parameters, output_params: OutputParameters) ->
         Tensor::I64(m) => split_inner!(Tensor::I64, m),
         Tensor::U8(m) => split_inner!(Tensor::U8, m),
         Tensor::U16(m) => split_inner!(Tensor::U16, m),
+        Tensor::U32(m) => split_inner!(Tensor::U32, m),
+        Tensor::U64(m) => split_inner!(Tensor::U64, m),
+        Tensor::F16(m) => split_inner!(Tensor::F16, m),
+        Tensor::ComplexF32(m) => split_inner!(Tensor::ComplexF32, m),
+        Tensor::ComplexF64(m) => split_inner!(Tensor::ComplexF64, m),
         Tensor::TDim(m) => split_inner!(Tensor::TDim, m),
         Tensor::String(m) => split_inner!(Tensor::String, m),
     };
@@ -1,11 +1,21 @@
-use tfdeploy::SimplePlan;
+use std::path::Path;
+
+use tfdeploy::{SimplePlan, ValueChecks};
 use tfdeploy::analyser::Fact;
 use tfdeploy::analyser::TensorFact;
+use tfdeploy::plan::{dump_filename, SimpleState};
 use display_graph::DisplayGraph;
 use errors::*;
 use {OutputParameters, Parameters};
 
-pub fn handle(params: Parameters, assert_outputs:Option<Vec<TensorFact>>, output_params: OutputParameters) -> CliResult<()> {
+/// Where to dump intermediate tensors, and what to compare them against.
+pub struct DumpParameters {
+    pub dump: Option<String>,
+    pub dump_nodes: Option<Vec<String>>,
+    pub compare_dump: Option<String>,
+}
+
+pub fn handle(params: Parameters, assert_outputs:Option<Vec<TensorFact>>, checks: Option<ValueChecks>, dump: DumpParameters, output_params: OutputParameters) -> CliResult<()> {
     let tfd = params.tfd_model;
     let output_id = tfd.node_by_name(&params.output_node)?.id;
 
@@ -16,7 +26,35 @@ pub fn handle(params: Parameters, assert_outputs:Option<Vec<TensorFact>>, output
     display_graph.render(&output_params)?;
 
     let plan = SimplePlan::new(&tfd, &params.input_nodes, &[params.output_node])?;
-    let outputs = plan.run(params.inputs.iter().map(|tf| tf.concretize().unwrap()).collect())?;
+    let inputs = params.inputs.iter().map(|tf| tf.concretize().unwrap()).collect();
+    let outputs = if dump.dump.is_some() || dump.compare_dump.is_some() {
+        let mut state = plan.state()?;
+        state.set_inputs(inputs)?;
+        for &n in &plan.order {
+            if state.values[n].is_none() {
+                match checks {
+                    Some(ref checks) => state.compute_one_checked(n, checks)?,
+                    None => state.compute_one(n)?,
+                }
+            }
+        }
+        if let Some(ref dir) = dump.dump {
+            let names: Option<Vec<&str>> = dump
+                .dump_nodes
+                .as_ref()
+                .map(|ns| ns.iter().map(|s| &**s).collect());
+            state.dump_values(dir, names.as_ref().map(|ns| &**ns))?;
+        }
+        if let Some(ref dir) = dump.compare_dump {
+            compare_dump(&state, &plan.order, Path::new(dir))?;
+        }
+        state.take_outputs()?
+    } else {
+        match checks {
+            Some(checks) => plan.run_checked(inputs, &checks)?,
+            None => plan.run(inputs)?,
+        }
+    };
 
     if let Some(asserts) = assert_outputs {
         ::utils::check_outputs(&outputs[0], &asserts)?;
@@ -24,3 +62,38 @@ pub fn handle(params: Parameters, assert_outputs:Option<Vec<TensorFact>>, output
 
     Ok(())
 }
+
+/// Compares computed values with the matching files of a previous dump.
+///
+/// Values without a matching file are ignored. Fails at the first node, in
+/// evaluation order, that diverges from the dump.
+fn compare_dump(state: &SimpleState, order: &[usize], dir: &Path) -> CliResult<()> {
+    let mut compared = 0;
+    for &n in order {
+        let node = &state.model().nodes()[n];
+        let values = match state.values[node.id] {
+            Some(ref values) => values,
+            None => continue,
+        };
+        for (slot, value) in values.iter().enumerate() {
+            let path = dir.join(dump_filename(&node.name, slot));
+            if !path.exists() {
+                continue;
+            }
+            let expected = ::tensor::tensor_for_text_data(&path.to_string_lossy())?;
+            if expected.shape() != value.shape() || !expected.close_enough(value, true) {
+                bail!(
+                    "Node {} ({}) output #{} differs from dump. expected: {:?} got: {:?}",
+                    node.name,
+                    node.op_name,
+                    slot,
+                    expected,
+                    value.as_tensor()
+                );
+            }
+            compared += 1;
+        }
+    }
+    info!("{} values match the dump in {:?}", compared, dir);
+    Ok(())
+}
@@ -2,6 +2,7 @@ use std::fs;
 use std::io::Read;
 
 use tfdeploy::analyser::TensorFact;
+use tfdeploy::tensor::f16;
 use tfdeploy::*;
 use CliResult;
 
@@ -31,33 +32,50 @@ pub fn for_size(size: &str) -> CliResult<TensorFact> {
     let datum_type = match datum_type.to_lowercase().as_str() {
         "f64" => DatumType::F64,
         "f32" => DatumType::F32,
+        "f16" => DatumType::F16,
+        "i64" => DatumType::I64,
         "i32" => DatumType::I32,
+        "i16" => DatumType::I16,
         "i8" => DatumType::I8,
+        "u64" => DatumType::U64,
+        "u32" => DatumType::U32,
+        "u16" => DatumType::U16,
         "u8" => DatumType::U8,
-        _ => bail!("Type of the input should be f64, f32, i32, i8 or u8."),
+        "bool" => DatumType::Bool,
+        _ => bail!(
+            "Type of the input should be f64, f32, f16, i64, i32, i16, i8, u64, u32, u16, u8 or bool."
+        ),
     };
 
     Ok(TensorFact::dt_shape(datum_type, shape))
 }
 
-fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
+pub fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
     let mut file = fs::File::open(filename)?;
     let mut data = String::new();
     file.read_to_string(&mut data)?;
 
     let mut lines = data.lines();
-    let proto = for_size(lines.next().ok_or("Empty data file")?)?;
+    let size = lines.next().ok_or("Empty data file")?;
+    let proto = for_size(size)?;
     let shape = proto.shape.concretize().unwrap();
 
     let values = lines.flat_map(|l| l.split_whitespace()).collect::<Vec<_>>();
 
     // We know there is at most one streaming dimension, so we can deduce the
-    // missing value with a simple division.
+    // missing value with a simple division, unless another dimension is zero.
     let product: usize = shape
         .iter()
         .map(|o| o.to_integer().unwrap_or(1) as usize)
         .product();
-    let missing = values.len() / product;
+    let missing = if product == 0 {
+        if !values.is_empty() {
+            bail!("Expected no value for a tensor of size {}, got {}", size, values.len())
+        }
+        0
+    } else {
+        values.len() / product
+    };
 
     macro_rules! for_type {
         ($t:ty) => {{
@@ -75,19 +93,61 @@ fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
     let tensor = match proto.datum_type.concretize().unwrap() {
         DatumType::F64 => for_type!(f64).into(),
         DatumType::F32 => for_type!(f32).into(),
+        DatumType::F16 => for_type!(f32).mapv(f16::from_f32).into(),
+        DatumType::I64 => for_type!(i64).into(),
         DatumType::I32 => for_type!(i32).into(),
+        DatumType::I16 => for_type!(i16).into(),
         DatumType::I8 => for_type!(i8).into(),
+        DatumType::U64 => for_type!(u64).into(),
+        DatumType::U32 => for_type!(u32).into(),
+        DatumType::U16 => for_type!(u16).into(),
         DatumType::U8 => for_type!(u8).into(),
+        DatumType::Bool => for_type!(bool).into(),
         _ => unimplemented!(),
     };
     Ok(tensor)
 }
 
+/// Loads an array from a npz archive, as `archive.npz` or `archive.npz:name`.
+///
+/// The name can only be omitted if the archive holds a single array.
+fn tensor_for_npz(spec: &str) -> CliResult<Tensor> {
+    let (filename, name) = match spec.rfind(".npz:") {
+        Some(ix) => (&spec[..ix + 4], Some(&spec[ix + 5..])),
+        None => (spec, None),
+    };
+    let file = fs::File::open(filename)?;
+    let mut tensors = ::tfdeploy::npy::read_npz(file)?;
+    match name {
+        Some(name) => match tensors.iter().position(|t| t.0 == name) {
+            Some(ix) => Ok(tensors.remove(ix).1),
+            None => bail!(
+                "No array named {} in {} (found: {})",
+                name,
+                filename,
+                tensors.iter().map(|t| &*t.0).collect::<Vec<_>>().join(", ")
+            ),
+        },
+        None if tensors.len() == 1 => Ok(tensors.remove(0).1),
+        None => bail!(
+            "{} holds several arrays, pick one with {}:<name> (found: {})",
+            filename,
+            filename,
+            tensors.iter().map(|t| &*t.0).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
 /// Parses the `data` command-line argument.
 fn for_data(filename: &str) -> CliResult<TensorFact> {
     let tensor = if filename.ends_with(".pb") {
         let mut file = fs::File::open(filename)?;
         ::tfdeploy_onnx::tensor::from_reader(file)?
+    } else if filename.ends_with(".npy") {
+        let mut file = fs::File::open(filename)?;
+        Tensor::read_npy(&mut file)?
+    } else if filename.contains(".npz") {
+        tensor_for_npz(filename)?
     } else {
         tensor_for_text_data(filename)?
     };
@@ -152,9 +212,34 @@ pub fn random(sizes: Vec<usize>, datum_type: DatumType) -> Tensor {
     match datum_type {
         DatumType::F64 => Tensor::f64s(&*sizes, &*r!(f64)),
         DatumType::F32 => Tensor::f32s(&*sizes, &*r!(f32)),
+        DatumType::F16 => Tensor::f16s(
+            &*sizes,
+            &*r!(f32).into_iter().map(f16::from_f32).collect::<Vec<_>>(),
+        ),
+        DatumType::I64 => Tensor::i64s(&*sizes, &*r!(i64)),
         DatumType::I32 => Tensor::i32s(&*sizes, &*r!(i32)),
+        DatumType::I16 => Tensor::i16s(&*sizes, &*r!(i16)),
         DatumType::I8 => Tensor::i8s(&*sizes, &*r!(i8)),
+        DatumType::U64 => Tensor::u64s(&*sizes, &*r!(u64)),
+        DatumType::U32 => Tensor::u32s(&*sizes, &*r!(u32)),
+        DatumType::U16 => Tensor::u16s(&*sizes, &*r!(u16)),
         DatumType::U8 => Tensor::u8s(&*sizes, &*r!(u8)),
+        DatumType::Bool => Tensor::bools(&*sizes, &*r!(bool)),
         _ => unimplemented!("missing type"),
     }.unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_size_text_roundtrip() {
+        let tensor = Tensor::i64s(&[0], &[]).unwrap();
+        let path = ::std::env::temp_dir().join(format!("tfd-cli-empty-{}.txt", ::std::process::id()));
+        tensor.write_text(&mut fs::File::create(&path).unwrap()).unwrap();
+        let loaded = tensor_for_text_data(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, tensor);
+    }
+}
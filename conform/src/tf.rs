@@ -47,7 +47,9 @@ enum TensorHolder {
     I16(Tensor<i16>),
     I32(Tensor<i32>),
     I64(Tensor<i64>),
-    String(Tensor<i8>),
+    U32(Tensor<u32>),
+    U64(Tensor<u64>),
+    String(Tensor<String>),
 }
 
 impl TensorHolder {
@@ -57,11 +59,18 @@ impl TensorHolder {
         tensor.copy_from_slice(m.as_slice().unwrap());
         tensor
     }
-}
 
-impl From<TfdTensor> for TensorHolder {
-    fn from(m: TfdTensor) -> TensorHolder {
-        match m {
+    fn to_string_tensor(m: ArrayD<Vec<u8>>) -> Tensor<String> {
+        let dims: Vec<u64> = m.shape().iter().map(|d| *d as _).collect();
+        let values: Vec<String> = m
+            .iter()
+            .map(|s| String::from_utf8_lossy(s).into_owned())
+            .collect();
+        Tensor::<String>::new(&*dims).with_values(&values).unwrap()
+    }
+
+    fn from_tfd(m: TfdTensor) -> Result<TensorHolder> {
+        let holder = match m {
             TfdTensor::Bool(a) => TensorHolder::Bool(Self::to_tensor(a)),
             TfdTensor::F32(a) => TensorHolder::F32(Self::to_tensor(a)),
             TfdTensor::F64(a) => TensorHolder::F64(Self::to_tensor(a)),
@@ -71,16 +80,22 @@ impl From<TfdTensor> for TensorHolder {
             TfdTensor::I64(a) => TensorHolder::I64(Self::to_tensor(a)),
             TfdTensor::U8(a) => TensorHolder::U8(Self::to_tensor(a)),
             TfdTensor::U16(a) => TensorHolder::U16(Self::to_tensor(a)),
+            TfdTensor::U32(a) => TensorHolder::U32(Self::to_tensor(a)),
+            TfdTensor::U64(a) => TensorHolder::U64(Self::to_tensor(a)),
+            TfdTensor::F16(_) | TfdTensor::ComplexF32(_) | TfdTensor::ComplexF64(_) => {
+                bail!("f16 and complex tensors are not supported by conformance tests")
+            }
             TfdTensor::TDim(dims) => {
                 if dims.iter().all(|d| d.to_integer().is_ok()) {
                     let dims: ArrayD<i32> = dims.map(|d| d.to_integer().unwrap() as i32);
                     TensorHolder::I32(Self::to_tensor(dims))
                 } else {
-                    panic!("Streaming used in tensorflow settings")
+                    bail!("Streaming used in tensorflow settings")
                 }
             }
-            TfdTensor::String(a) => TensorHolder::String(Self::to_tensor(a)),
-        }
+            TfdTensor::String(a) => TensorHolder::String(Self::to_string_tensor(a)),
+        };
+        Ok(holder)
     }
 }
 
@@ -98,8 +113,8 @@ impl Tensorflow {
     ) -> Result<Vec<TfdTensor>> {
         let tensors: Vec<(&str, TensorHolder)> = inputs
             .into_iter()
-            .map(|(name, mat)| (name, mat.into()))
-            .collect();
+            .map(|(name, mat)| Ok((name, TensorHolder::from_tfd(mat)?)))
+            .collect::<Result<_>>()?;
 
         let mut step = SessionRunArgs::new();
         for t in &tensors {
@@ -112,6 +127,8 @@ impl Tensorflow {
                 TensorHolder::I16(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::I32(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::I64(ref it) => step.add_feed(&op, 0, &it),
+                TensorHolder::U32(ref it) => step.add_feed(&op, 0, &it),
+                TensorHolder::U64(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::F32(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::F64(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::String(ref it) => step.add_feed(&op, 0, &it),
@@ -137,7 +154,7 @@ impl Tensorflow {
         let mut excluded = HashSet::new();
 
         for (name, mat) in inputs {
-            tensors.push((name, mat.into()));
+            tensors.push((name, TensorHolder::from_tfd(mat)?));
             excluded.insert(name.to_string());
         }
 
@@ -152,6 +169,8 @@ impl Tensorflow {
                 TensorHolder::I16(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::I32(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::I64(ref it) => step.add_feed(&op, 0, &it),
+                TensorHolder::U32(ref it) => step.add_feed(&op, 0, &it),
+                TensorHolder::U64(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::F32(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::F64(ref it) => step.add_feed(&op, 0, &it),
                 TensorHolder::String(ref it) => step.add_feed(&op, 0, &it),
@@ -200,7 +219,10 @@ fn convert_output(
         DataType::Float => convert!(F32),
         DataType::UInt8 => convert!(U8),
         DataType::Int8 => convert!(I8),
-        DataType::String => convert!(String),
+        DataType::String => {
+            let strings: Tensor<String> = step.fetch(output)?;
+            TfdTensor::String(tensor_to_array(&strings)?.map(|s| s.clone().into_bytes()))
+        }
         DataType::Int32 => convert!(I32),
         t => bail!("Missing Tensor to TfdTensor for type {:?}", t),
     };
@@ -0,0 +1,89 @@
+#![allow(non_snake_case)]
+extern crate conform;
+extern crate ndarray;
+#[macro_use]
+extern crate proptest;
+extern crate tensorflow;
+extern crate tfdeploy;
+extern crate tfdeploy_tf;
+
+use conform::*;
+use ndarray::prelude::*;
+use proptest::prelude::*;
+use tfdeploy_tf::tfpb;
+use tfdeploy_tf::tfpb::types::DataType::DT_FLOAT;
+use tfdeploy::Tensor as TfdTensor;
+
+fn depthwise_pb(stride: usize, valid: bool) -> ::Result<Vec<u8>> {
+    let conv = tfpb::node()
+        .name("conv")
+        .op("DepthwiseConv2dNative")
+        .input("data")
+        .input("kernel")
+        .attr("strides", vec![1, stride as i64, stride as i64, 1])
+        .attr("padding", if valid { "VALID" } else { "SAME" })
+        .attr("T", DT_FLOAT);
+
+    let graph = tfpb::graph()
+        .node(placeholder_f32("data"))
+        .node(placeholder_f32("kernel"))
+        .node(conv);
+
+    Ok(graph.write_to_bytes()?)
+}
+
+fn img_and_ker() -> BoxedStrategy<(TfdTensor, TfdTensor, usize)> {
+    (1usize..8, 1usize..8, 1usize..8, 1usize..4)
+        .prop_flat_map(|(ic, kh, kw, q)| (1usize..4, kh..24, kw..24, Just((ic, kh, kw, q))))
+        .prop_flat_map(|(ib, ih, iw, (ic, kh, kw, q))| {
+            let i_size = ib * iw * ih * ic;
+            let k_size = kw * kh * ic * q;
+            (
+                Just((ib, ih, iw, ic)),
+                Just((kh, kw, ic, q)),
+                ::proptest::collection::vec(-9i32..9, i_size..i_size + 1),
+                ::proptest::collection::vec(-9i32..9, k_size..k_size + 1),
+                1..(kh.min(kw) + 1),
+            )
+        })
+        .prop_map(|(img_shape, ker_shape, img, ker, stride)| {
+            (
+                Array::from_vec(img.into_iter().map(|i| i as f32).collect())
+                    .into_shape(img_shape)
+                    .unwrap()
+                    .into(),
+                Array::from_vec(ker.into_iter().map(|i| i as f32).collect())
+                    .into_shape(ker_shape)
+                    .unwrap()
+                    .into(),
+                stride,
+            )
+        })
+        .boxed()
+}
+
+proptest! {
+    #[test]
+    fn depthwise_compare((ref i, ref k, stride) in img_and_ker(),
+                       valid in ::proptest::bool::ANY) {
+        if valid {
+            prop_assume!(i.shape()[1] >= k.shape()[0]);
+            prop_assume!(i.shape()[2] >= k.shape()[1]);
+        }
+        let model = depthwise_pb(stride, valid).unwrap();
+        compare(&model, vec!(("data", i.clone()), ("kernel", k.clone())), "conv")?;
+    }
+}
+
+proptest! {
+    #[test]
+    fn depthwise_infer((ref i, ref k, stride) in img_and_ker(),
+                       valid in ::proptest::bool::ANY) {
+        if valid {
+            prop_assume!(i.shape()[1] >= k.shape()[0]);
+            prop_assume!(i.shape()[2] >= k.shape()[1]);
+        }
+        let model = depthwise_pb(stride, valid).unwrap();
+        infer(&model, vec!(("data", i.clone()), ("kernel", k.clone())), "conv")?;
+    }
+}
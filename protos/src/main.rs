@@ -10,8 +10,9 @@ fn main() {
 }
 
 fn tensorflow() {
-    let inputs: Vec<path::PathBuf> = fs::read_dir("tensorflow/core/framework")
-        .unwrap()
+    let inputs: Vec<path::PathBuf> = ["tensorflow/core/framework", "tensorflow/core/protobuf"]
+        .iter()
+        .flat_map(|dir| fs::read_dir(dir).unwrap())
         .map(|entry| entry.unwrap().path())
         .collect();
 
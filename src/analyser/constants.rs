@@ -107,12 +107,13 @@ pub fn connected_components(analyser: &Analyser) -> TfdResult<Vec<Component>> {
 }
 
 /// Creates a new Const node with the given Tensor value.
-fn build_const_node(id: usize, name: String, tensor: Tensor) -> Node {
+pub fn build_const_node(id: usize, name: String, tensor: Tensor) -> Node {
     Node {
         id,
         name,
         op_name: "Const".to_string(),
         inputs: vec![],
+        control_inputs: vec![],
         op: Box::new(::ops::konst::Const::for_tensor(tensor)),
     }
 }
@@ -0,0 +1,144 @@
+use super::constants::build_const_node;
+use super::helpers::tensor_to_fact;
+use super::prelude::*;
+use model::OutletId;
+use ops::math::Add;
+use ops::nn::{per_channel, BatchNorm};
+use {DatumType, Tensor, TfdResult};
+
+/// A batch normalization that can be folded into the convolution it follows.
+struct Fold {
+    conv: usize,
+    kernel: Tensor,
+    bias: Tensor,
+}
+
+fn const_input(analyser: &Analyser, edge: usize) -> Option<Tensor> {
+    analyser.edges[edge]
+        .fact
+        .value
+        .concretize()
+        .filter(|t| t.datum_type() == DatumType::F32)
+}
+
+fn fold_for(analyser: &Analyser, node: usize) -> TfdResult<Option<Fold>> {
+    let bn = match analyser.nodes[node].op.downcast_ref::<BatchNorm>() {
+        Some(bn) => bn,
+        None => return Ok(None),
+    };
+    let prev = &analyser.prev_edges[node];
+    if prev.len() != 5 {
+        return Ok(None);
+    }
+    // The folded node has no statistics to forward.
+    if analyser.next_edges[node]
+        .iter()
+        .any(|&e| analyser.edges[e].from.map(|o| o.slot) != Some(0))
+    {
+        return Ok(None);
+    }
+    let params = match prev[1..]
+        .iter()
+        .map(|&e| const_input(analyser, e))
+        .collect::<Option<Vec<Tensor>>>()
+    {
+        Some(params) => params,
+        None => return Ok(None),
+    };
+
+    let source = match analyser.edges[prev[0]].from {
+        Some(source) if source.slot == 0 => source.node,
+        _ => return Ok(None),
+    };
+    if analyser.next_edges[source].len() != 1 || analyser.prev_edges[source].len() != 2 {
+        return Ok(None);
+    }
+    let shape = &analyser.edges[prev[0]].fact.shape;
+    if shape.open {
+        return Ok(None);
+    }
+    let rank = shape.dims.len();
+    let (output_axis, kernel_axis) = match analyser.nodes[source].op.kernel_channel_axes(rank) {
+        Some(axes) if axes.0 == bn.channel_axis() => axes,
+        _ => return Ok(None),
+    };
+    let kernel = match const_input(analyser, analyser.prev_edges[source][1]) {
+        Some(kernel) => kernel.into_array::<f32>()?,
+        None => return Ok(None),
+    };
+
+    let (multiplier, bias) =
+        bn.multiplier_and_bias::<f32>(&params[0], &params[1], &params[2], &params[3])?;
+    if kernel.shape().get(kernel_axis) != Some(&multiplier.len()) {
+        return Ok(None);
+    }
+    let multiplier = per_channel(multiplier, kernel.ndim(), kernel_axis)?;
+    let kernel = kernel * &multiplier;
+    let bias = per_channel(bias, rank, output_axis)?;
+    Ok(Some(Fold {
+        conv: source,
+        kernel: kernel.into(),
+        bias: bias.into(),
+    }))
+}
+
+/// Makes an edge come from a new constant node holding the given tensor.
+fn attach_const(analyser: &mut Analyser, edge: usize, tensor: Tensor) {
+    let const_node_id = analyser.nodes.len();
+    let name = format!("generated_{}", const_node_id);
+    analyser.edges[edge].fact = tensor_to_fact(tensor.clone());
+    analyser
+        .nodes
+        .push(build_const_node(const_node_id, name, tensor));
+    analyser.prev_edges.push(vec![]);
+    analyser.next_edges.push(vec![edge]);
+    detach(analyser, edge);
+    let outlet = OutletId::new(const_node_id, 0);
+    let edge = &mut analyser.edges[edge];
+    edge.from = Some(outlet);
+    analyser.nodes[edge.to_node.unwrap()].inputs[edge.to_input] = outlet;
+}
+
+/// Removes an edge from the successors of its source.
+fn detach(analyser: &mut Analyser, edge: usize) {
+    if let Some(source) = analyser.edges[edge].from {
+        analyser.next_edges[source.node].retain(|&e| e != edge);
+    }
+}
+
+/// Folds inference-time batch normalizations into the kernel of the
+/// convolution they follow.
+///
+/// `conv(x, kernel)` followed by a normalization with constant parameters
+/// becomes `conv(x, kernel * multiplier) + bias`, with the per-channel
+/// multiplier and bias of the normalization. The convolution must have a
+/// constant kernel and feed nothing but the normalization, and the
+/// statistics the normalization forwards must be unused.
+pub fn fold_batch_norms(analyser: &mut Analyser) -> TfdResult<()> {
+    let mut folded = 0;
+    for node in 0..analyser.nodes.len() {
+        let fold = match fold_for(analyser, node)? {
+            Some(fold) => fold,
+            None => continue,
+        };
+        let kernel_edge = analyser.prev_edges[fold.conv][1];
+        attach_const(analyser, kernel_edge, fold.kernel);
+
+        let prev = analyser.prev_edges[node].clone();
+        attach_const(analyser, prev[1], fold.bias);
+        for &edge in &prev[2..] {
+            detach(analyser, edge);
+        }
+        analyser.prev_edges[node].truncate(2);
+        let bn = &mut analyser.nodes[node];
+        bn.inputs.truncate(2);
+        bn.op = Box::new(Add::default());
+        bn.op_name = "Add".to_string();
+        folded += 1;
+    }
+    info!("Folded {} batch normalizations.", folded);
+    if folded > 0 {
+        analyser.reset_plan()?;
+    }
+    Ok(())
+}
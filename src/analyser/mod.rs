@@ -4,10 +4,13 @@ use std::sync::Arc;
 use errors::*;
 use model::eval_order_for_nodes;
 use model::{Model, OutletId, RawModel, TVec};
+use ops::sink::Sink;
+use ops::source::Source;
 use ops::Op;
 use Node;
 
 mod constants;
+mod fold;
 pub mod types;
 
 #[allow(unused_imports)]
@@ -176,6 +179,11 @@ impl Analyser {
                     .iter()
                     .map(|outlet| OutletId::new(nodes_mapped[&outlet.node], outlet.slot))
                     .collect(),
+                control_inputs: old_node
+                    .control_inputs
+                    .iter()
+                    .filter_map(|n| nodes_mapped.get(n).cloned())
+                    .collect(),
                 op: new_op.unwrap_or_else(|| old_node.op.clone()),
             });
         }
@@ -187,6 +195,7 @@ impl Analyser {
     pub fn to_optimized_model(&mut self) -> TfdResult<Model> {
         self.analyse()?;
         constants::propagate_constants(self)?;
+        fold::fold_batch_norms(self)?;
         self.to_model()
     }
 
@@ -194,6 +203,7 @@ impl Analyser {
     pub fn optimize_and_finalize_model(&mut self) -> TfdResult<Model> {
         self.analyse()?;
         constants::propagate_constants(self)?;
+        fold::fold_batch_norms(self)?;
         self.finalize_model()
     }
 
@@ -208,6 +218,11 @@ impl Analyser {
         constants::propagate_constants(self)
     }
 
+    /// Folds batch normalizations into the convolutions they follow.
+    pub fn fold_batch_norms(&mut self) -> TfdResult<()> {
+        fold::fold_batch_norms(self)
+    }
+
     /// Runs the entire analysis at once.
     pub fn analyse(&mut self) -> TfdResult<()> {
         let mut nodes_to_visit: BTreeSet<usize> = (0..self.nodes.len()).collect();
@@ -256,7 +271,11 @@ impl Analyser {
             .collect();
 
         // FIXME(liautaud): We should handle multiple output ports in the future.
-        let mut outputs = tvec![TensorFact::new()];
+        let mut outputs = if node.op.downcast_ref::<Sink>().is_some() {
+            tvec![]
+        } else {
+            tvec![TensorFact::new()]
+        };
         for &i in &self.next_edges[node.id] {
             outputs[0] = self.edges[i].fact.unify(&outputs[0])?;
         }
@@ -275,7 +294,16 @@ impl Analyser {
 
         let (inputs, outputs) = self.facts(node.id)?;
 
-        let inferred = node.op.infer_and_propagate(inputs, outputs).map_err(|e| {
+        // Sources have nothing to evaluate, even without any input, and sinks
+        // nothing to produce, even with constant inputs.
+        let inferred = if node.op.downcast_ref::<Source>().is_some()
+            || node.op.downcast_ref::<Sink>().is_some()
+        {
+            node.op.infer(inputs, outputs)
+        } else {
+            node.op.infer_and_propagate(inputs, outputs)
+        };
+        let inferred = inferred.map_err(|e| {
             format!(
                 "While inferring forward for {} {}: {}",
                 node.id, node.name, e
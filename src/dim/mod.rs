@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::ops;
 
@@ -36,6 +37,16 @@ impl TDim {
         TDim(Stack::sym('S'))
     }
 
+    /// A dimension only known at runtime, other than the streaming one.
+    pub fn sym(s: char) -> TDim {
+        TDim(Stack::sym(s))
+    }
+
+    /// The symbols the dimension depends on.
+    pub fn symbols(&self) -> Vec<char> {
+        self.0.symbols()
+    }
+
     pub fn stream() -> TDim {
         Self::s()
     }
@@ -53,7 +64,7 @@ impl TDim {
     }
 
     pub fn is_stream(&self) -> bool {
-        self.as_const().is_none()
+        self.symbols().contains(&'S')
     }
 
     pub fn to_integer(&self) -> TfdResult<i64> {
@@ -65,6 +76,37 @@ impl TDim {
     }
 }
 
+/// Gives symbols to the dimensions a model leaves to runtime, like ONNX
+/// `dim_param`s or Tensorflow unknown dimensions.
+///
+/// A name always gets the same symbol. `S` is kept for the streaming
+/// dimension, which leaves 51 letters for the other names.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable(HashMap<String, char>);
+
+impl SymbolTable {
+    /// The dimension for a name, preferably with the name's initial as symbol.
+    ///
+    /// Returns `None` once all the letters are taken by other names.
+    pub fn sym(&mut self, name: &str) -> Option<TDim> {
+        if let Some(&s) = self.0.get(name) {
+            return Some(TDim::sym(s));
+        }
+        let initial = name.chars().next().filter(|c| c.is_ascii_alphabetic());
+        let s = initial
+            .into_iter()
+            .chain("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRTUVWXYZ".chars())
+            .find(|&s| s != 'S' && !self.0.values().any(|&v| v == s))?;
+        self.0.insert(name.to_string(), s);
+        Some(TDim::sym(s))
+    }
+
+    /// The name of a symbol.
+    pub fn name(&self, s: char) -> Option<&str> {
+        self.0.iter().find(|&(_, &v)| v == s).map(|(k, _)| &**k)
+    }
+}
+
 impl Zero for TDim {
     fn zero() -> Self {
         Self::from(0)
@@ -207,3 +249,32 @@ impl<I: AsPrimitive<i64>> From<I> for TDim {
         TDim(it.as_().into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbol_table() {
+        let mut symbols = SymbolTable::default();
+        let batch = symbols.sym("batch").unwrap();
+        assert_eq!(batch, TDim::sym('b'));
+        assert_eq!(symbols.sym("batch").unwrap(), batch);
+        assert_eq!(symbols.sym("bands").unwrap(), TDim::sym('a'));
+        assert_eq!(symbols.sym("seq").unwrap(), TDim::sym('s'));
+        assert_eq!(symbols.sym("Samples").unwrap(), TDim::sym('c'));
+        assert_eq!(symbols.name('a'), Some("bands"));
+        assert!(!batch.is_stream());
+        assert!((TDim::s() * batch).is_stream());
+    }
+
+    #[test]
+    fn symbol_table_exhausted() {
+        let mut symbols = SymbolTable::default();
+        for i in 0..51 {
+            assert!(symbols.sym(&format!("d{}", i)).is_some());
+        }
+        assert_eq!(symbols.sym("other"), None);
+        assert_eq!(symbols.sym("d0"), Some(TDim::sym('d')));
+    }
+}
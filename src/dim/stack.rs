@@ -126,6 +126,20 @@ impl Stack {
         &self.array[0..self.len]
     }
 
+    pub fn symbols(&self) -> Vec<char> {
+        let mut symbols: Vec<char> = self
+            .as_ops()
+            .iter()
+            .filter_map(|op| match op {
+                StackOp::Sym(s) => Some(*s),
+                _ => None,
+            })
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
     pub fn overflow(&self) -> bool {
         self.len == self.array.len()
     }
@@ -5,11 +5,12 @@ error_chain!{
         TfdError, TfdErrorKind, TfdResultExt, TfdResult;
     }
     foreign_links {
-        Image(::image::ImageError) #[cfg(features="image_ops")];
+        Image(::image::ImageError) #[cfg(feature="image_ops")];
         Io(::std::io::Error);
         NdarrayShape(::ndarray::ShapeError);
         StrUtf8(::std::str::Utf8Error);
         NumParseInt(::std::num::ParseIntError);
+        Zip(::zip::result::ZipError);
     }
     errors {
         TFString {}
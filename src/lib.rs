@@ -42,6 +42,7 @@ extern crate blis_src;
 extern crate derive_new;
 #[macro_use]
 extern crate error_chain;
+extern crate half;
 #[cfg(feature = "image_ops")]
 extern crate image;
 extern crate itertools;
@@ -62,6 +63,7 @@ extern crate serde;
 #[cfg(test)]
 extern crate simplelog;
 extern crate smallvec;
+extern crate zip;
 #[cfg(feature = "serialize")]
 #[macro_use]
 extern crate serde_derive;
@@ -77,6 +79,7 @@ mod broadcast;
 pub mod dim;
 pub mod errors;
 pub mod model;
+pub mod npy;
 #[macro_use]
 pub mod ops;
 pub mod plan;
@@ -86,9 +89,9 @@ pub mod tensor;
 pub use errors::*;
 
 pub use analyser::TensorFact;
-pub use dim::TDim;
+pub use dim::{SymbolTable, TDim};
 pub use model::{Model, Node, TVec};
-pub use plan::SimplePlan;
+pub use plan::{SimplePlan, ValueChecks};
 pub use tensor::{DatumType, Tensor};
 
 
@@ -15,6 +15,8 @@ pub struct Node {
     pub name: String,
     pub op_name: String,
     pub inputs: Vec<OutletId>,
+    /// Nodes to run before this one, without feeding it any value.
+    pub control_inputs: Vec<usize>,
     #[cfg_attr(feature = "serialize", serde(skip))]
     pub op: Box<ops::Op>,
 }
@@ -73,6 +75,7 @@ impl RawModel {
                 name: format!("Sink-{}", id),
                 op_name: "Sink".to_string(),
                 inputs: vec![missing],
+                control_inputs: vec![],
                 op: Box::new(ops::sink::Sink::new(::analyser::TensorFact::default())),
             });
         }
@@ -98,6 +101,8 @@ impl RawModel {
         &*self.nodes
     }
 
+    /// The nodes to feed at runtime: constants, like ONNX initializers, are
+    /// not inputs even if the original model lists them as such.
     pub fn guess_inputs(&self) -> Vec<&Node> {
         self.nodes
             .iter()
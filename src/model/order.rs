@@ -16,11 +16,12 @@ pub fn eval_order_for_nodes(nodes: &[Node], targets: &[usize]) -> TfdResult<Vec<
         for node_id in missing.iter() {
             let mut computable = true;
             let node = &nodes[node_id];
-            for i in node.inputs.iter() {
-                if !done.contains(i.node) {
+            let inputs = node.inputs.iter().map(|i| i.node);
+            for i in inputs.chain(node.control_inputs.iter().cloned()) {
+                if !done.contains(i) {
                     computable = false;
                     done_something = true;
-                    needed.insert(i.node);
+                    needed.insert(i);
                 }
             }
             if computable {
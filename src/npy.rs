@@ -0,0 +1,427 @@
+//! Reading and writing tensors in NumPy `.npy` and `.npz` formats.
+//!
+//! `.npy` files are read in both byte orders and in C or Fortran order. They
+//! are always written little-endian, in C order, using format version 1.0.
+//! `.npz` archives are zip files holding one `.npy` file per named array.
+use std::io::{Read, Seek, Write};
+
+use ndarray::prelude::*;
+use zip;
+
+use dim::TDim;
+use tensor::{f16, Complex, DatumType, Tensor};
+use TfdResult;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Element types that have a NumPy binary representation.
+trait NpyDatum: Sized + Clone {
+    /// NumPy type code, without the byte order character.
+    const CODE: &'static str;
+    const SIZE: usize;
+    fn from_bytes(bytes: &[u8], little: bool) -> Self;
+    fn write_le(&self, buf: &mut Vec<u8>);
+}
+
+macro_rules! npy_int {
+    ($t:ty, $code:expr) => {
+        impl NpyDatum for $t {
+            const CODE: &'static str = $code;
+            const SIZE: usize = ::std::mem::size_of::<$t>();
+            fn from_bytes(bytes: &[u8], little: bool) -> $t {
+                let mut b = [0u8; ::std::mem::size_of::<$t>()];
+                b.copy_from_slice(bytes);
+                if little {
+                    <$t>::from_le_bytes(b)
+                } else {
+                    <$t>::from_be_bytes(b)
+                }
+            }
+            fn write_le(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.to_le_bytes())
+            }
+        }
+    };
+}
+
+macro_rules! npy_float {
+    ($t:ty, $bits:ty, $code:expr) => {
+        impl NpyDatum for $t {
+            const CODE: &'static str = $code;
+            const SIZE: usize = ::std::mem::size_of::<$t>();
+            fn from_bytes(bytes: &[u8], little: bool) -> $t {
+                <$t>::from_bits(<$bits>::from_bytes(bytes, little))
+            }
+            fn write_le(&self, buf: &mut Vec<u8>) {
+                self.to_bits().write_le(buf)
+            }
+        }
+    };
+}
+
+npy_int!(u8, "u1");
+npy_int!(u16, "u2");
+npy_int!(i8, "i1");
+npy_int!(i16, "i2");
+npy_int!(i32, "i4");
+npy_int!(i64, "i8");
+npy_int!(u32, "u4");
+npy_int!(u64, "u8");
+npy_float!(f16, u16, "f2");
+npy_float!(f32, u32, "f4");
+npy_float!(f64, u64, "f8");
+
+macro_rules! npy_complex {
+    ($t:ty, $code:expr) => {
+        impl NpyDatum for Complex<$t> {
+            const CODE: &'static str = $code;
+            const SIZE: usize = 2 * ::std::mem::size_of::<$t>();
+            fn from_bytes(bytes: &[u8], little: bool) -> Complex<$t> {
+                let (re, im) = bytes.split_at(Self::SIZE / 2);
+                Complex::new(<$t>::from_bytes(re, little), <$t>::from_bytes(im, little))
+            }
+            fn write_le(&self, buf: &mut Vec<u8>) {
+                self.re.write_le(buf);
+                self.im.write_le(buf);
+            }
+        }
+    };
+}
+
+npy_complex!(f32, "c8");
+npy_complex!(f64, "c16");
+
+impl NpyDatum for bool {
+    const CODE: &'static str = "b1";
+    const SIZE: usize = 1;
+    fn from_bytes(bytes: &[u8], _little: bool) -> bool {
+        bytes[0] != 0
+    }
+    fn write_le(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8)
+    }
+}
+
+/// The parsed `.npy` header dictionary.
+#[derive(Debug, Clone, PartialEq)]
+struct Header {
+    descr: String,
+    fortran_order: bool,
+    shape: Vec<usize>,
+}
+
+impl Header {
+    fn parse(header: &str) -> TfdResult<Header> {
+        fn value_after<'a>(header: &'a str, key: &str) -> TfdResult<&'a str> {
+            let pos = header
+                .find(&*format!("'{}'", key))
+                .ok_or_else(|| format!("npy header lacks {}: {}", key, header))?;
+            let rest = &header[pos + key.len() + 2..];
+            let colon = rest.find(':').ok_or_else(|| format!("Malformed npy header: {}", header))?;
+            Ok(rest[colon + 1..].trim_start())
+        }
+        let descr = value_after(header, "descr")?;
+        let quote = descr.chars().next().ok_or("Malformed npy descr")?;
+        if quote != '\'' && quote != '"' {
+            bail!("Unsupported npy descr (structured arrays are not supported): {}", header)
+        }
+        let descr = descr[1..]
+            .split(quote)
+            .next()
+            .ok_or("Malformed npy descr")?
+            .to_string();
+        let fortran_order = value_after(header, "fortran_order")?.starts_with("True");
+        let shape = value_after(header, "shape")?;
+        if !shape.starts_with('(') {
+            bail!("Malformed npy shape: {}", header)
+        }
+        let end = shape.find(')').ok_or("Malformed npy shape")?;
+        let shape = shape[1..end]
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| s.len() > 0)
+            .map(|s| Ok(s.parse::<usize>()?))
+            .collect::<TfdResult<Vec<usize>>>()?;
+        Ok(Header {
+            descr,
+            fortran_order,
+            shape,
+        })
+    }
+
+    fn to_string(&self) -> String {
+        let shape = match self.shape.len() {
+            0 => "()".to_string(),
+            1 => format!("({},)", self.shape[0]),
+            _ => format!(
+                "({})",
+                self.shape
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        format!(
+            "{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}",
+            self.descr,
+            if self.fortran_order { "True" } else { "False" },
+            shape
+        )
+    }
+}
+
+fn array_from_bytes<T: NpyDatum>(header: &Header, little: bool, data: &[u8]) -> TfdResult<ArrayD<T>> {
+    let len = header.shape.iter().product::<usize>();
+    if data.len() != len * T::SIZE {
+        bail!(
+            "npy data size mismatch: shape {:?} of {} needs {} bytes, found {}",
+            header.shape,
+            header.descr,
+            len * T::SIZE,
+            data.len()
+        )
+    }
+    let values: Vec<T> = data
+        .chunks(T::SIZE)
+        .map(|c| T::from_bytes(c, little))
+        .collect();
+    if header.fortran_order {
+        let shape: Vec<usize> = header.shape.iter().rev().cloned().collect();
+        Ok(ArrayD::from_shape_vec(shape, values)?.reversed_axes())
+    } else {
+        Ok(ArrayD::from_shape_vec(&*header.shape, values)?)
+    }
+}
+
+fn write_array<T: NpyDatum, W: Write>(array: &ArrayD<T>, w: &mut W) -> TfdResult<()> {
+    let order = if T::SIZE == 1 { "|" } else { "<" };
+    let header = Header {
+        descr: format!("{}{}", order, T::CODE),
+        fortran_order: false,
+        shape: array.shape().to_vec(),
+    };
+    let mut header = header.to_string();
+    // magic, version and header length take 10 bytes, the whole preamble
+    // is padded to a multiple of 64 and ends with a newline
+    let padding = 63 - (10 + header.len()) % 64;
+    header.extend(::std::iter::repeat(' ').take(padding));
+    header.push('\n');
+    if header.len() > ::std::u16::MAX as usize {
+        bail!("npy header too long")
+    }
+    w.write_all(MAGIC)?;
+    w.write_all(&[1, 0])?;
+    w.write_all(&(header.len() as u16).to_le_bytes())?;
+    w.write_all(header.as_bytes())?;
+    let mut buf = Vec::with_capacity(array.len() * T::SIZE);
+    for v in array.iter() {
+        v.write_le(&mut buf);
+    }
+    w.write_all(&buf)?;
+    Ok(())
+}
+
+impl Tensor {
+    /// Reads a tensor from a NumPy `.npy` stream.
+    pub fn read_npy<R: Read>(r: &mut R) -> TfdResult<Tensor> {
+        let mut preamble = [0u8; 8];
+        r.read_exact(&mut preamble)?;
+        if &preamble[0..6] != MAGIC {
+            bail!("Not a npy file (wrong magic number)")
+        }
+        let header_len = match preamble[6] {
+            1 => {
+                let mut len = [0u8; 2];
+                r.read_exact(&mut len)?;
+                u16::from_le_bytes(len) as usize
+            }
+            2 | 3 => {
+                let mut len = [0u8; 4];
+                r.read_exact(&mut len)?;
+                u32::from_le_bytes(len) as usize
+            }
+            v => bail!("Unsupported npy format version {}", v),
+        };
+        let mut header = vec![0u8; header_len];
+        r.read_exact(&mut header)?;
+        let header = Header::parse(::std::str::from_utf8(&header)?)?;
+        let mut data = vec![];
+        r.read_to_end(&mut data)?;
+
+        let (order, code) = header.descr.split_at(1);
+        let little = match order {
+            "<" => true,
+            ">" => false,
+            "|" => true,
+            "=" => cfg!(target_endian = "little"),
+            _ => bail!("Unsupported npy descr {}", header.descr),
+        };
+        let tensor = match code {
+            "b1" => array_from_bytes::<bool>(&header, little, &data)?.into(),
+            "u1" => array_from_bytes::<u8>(&header, little, &data)?.into(),
+            "u2" => array_from_bytes::<u16>(&header, little, &data)?.into(),
+            "u4" => array_from_bytes::<u32>(&header, little, &data)?.into(),
+            "u8" => array_from_bytes::<u64>(&header, little, &data)?.into(),
+            "i1" => array_from_bytes::<i8>(&header, little, &data)?.into(),
+            "i2" => array_from_bytes::<i16>(&header, little, &data)?.into(),
+            "i4" => array_from_bytes::<i32>(&header, little, &data)?.into(),
+            "i8" => array_from_bytes::<i64>(&header, little, &data)?.into(),
+            "f2" => array_from_bytes::<f16>(&header, little, &data)?.into(),
+            "f4" => array_from_bytes::<f32>(&header, little, &data)?.into(),
+            "f8" => array_from_bytes::<f64>(&header, little, &data)?.into(),
+            "c8" => array_from_bytes::<Complex<f32>>(&header, little, &data)?.into(),
+            "c16" => array_from_bytes::<Complex<f64>>(&header, little, &data)?.into(),
+            _ => bail!("Unsupported npy type {}", header.descr),
+        };
+        Ok(tensor)
+    }
+
+    /// Writes the tensor as a NumPy `.npy` stream.
+    ///
+    /// TDim tensors must be concrete and are written as int64.
+    pub fn write_npy<W: Write>(&self, w: &mut W) -> TfdResult<()> {
+        match self {
+            &Tensor::Bool(ref a) => write_array(a, w),
+            &Tensor::U8(ref a) => write_array(a, w),
+            &Tensor::U16(ref a) => write_array(a, w),
+            &Tensor::U32(ref a) => write_array(a, w),
+            &Tensor::U64(ref a) => write_array(a, w),
+            &Tensor::I8(ref a) => write_array(a, w),
+            &Tensor::I16(ref a) => write_array(a, w),
+            &Tensor::I32(ref a) => write_array(a, w),
+            &Tensor::I64(ref a) => write_array(a, w),
+            &Tensor::F16(ref a) => write_array(a, w),
+            &Tensor::F32(ref a) => write_array(a, w),
+            &Tensor::F64(ref a) => write_array(a, w),
+            &Tensor::ComplexF32(ref a) => write_array(a, w),
+            &Tensor::ComplexF64(ref a) => write_array(a, w),
+            &Tensor::TDim(ref a) => {
+                let values = a
+                    .iter()
+                    .map(|d: &TDim| d.to_integer())
+                    .collect::<TfdResult<Vec<i64>>>()?;
+                write_array(&ArrayD::from_shape_vec(a.shape(), values)?, w)
+            }
+            &Tensor::String(_) => bail!("{:?} tensors can not be written as npy", DatumType::String),
+        }
+    }
+}
+
+/// Reads all the arrays of a NumPy `.npz` archive, in archive order.
+///
+/// Array names are the archive entry names without the `.npy` suffix.
+pub fn read_npz<R: Read + Seek>(r: R) -> TfdResult<Vec<(String, Tensor)>> {
+    let mut archive = zip::ZipArchive::new(r)?;
+    let mut tensors = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file.name().trim_end_matches(".npy").to_string();
+        let tensor = Tensor::read_npy(&mut file)
+            .map_err(|e| format!("Reading {} from npz, {}", name, e))?;
+        tensors.push((name, tensor));
+    }
+    Ok(tensors)
+}
+
+/// Writes named tensors to a NumPy `.npz` archive.
+pub fn write_npz<W: Write + Seek>(w: W, tensors: &[(&str, &Tensor)]) -> TfdResult<()> {
+    let mut archive = zip::ZipWriter::new(w);
+    for (name, tensor) in tensors {
+        archive.start_file(format!("{}.npy", name), zip::write::FileOptions::default())?;
+        tensor.write_npy(&mut archive)?;
+    }
+    archive.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn roundtrip(t: Tensor) {
+        let mut buf = vec![];
+        t.write_npy(&mut buf).unwrap();
+        assert_eq!((buf[8] as usize + 10) % 64, 0);
+        let back = Tensor::read_npy(&mut &*buf).unwrap();
+        assert_eq!(t, back);
+    }
+
+    #[test]
+    fn roundtrip_all_types() {
+        roundtrip(Tensor::bools(&[3], &[true, false, true]).unwrap());
+        roundtrip(Tensor::u8s(&[2, 2], &[1, 2, 3, 255]).unwrap());
+        roundtrip(Tensor::u16s(&[2], &[1, 65535]).unwrap());
+        roundtrip(Tensor::i8s(&[2], &[-1, 2]).unwrap());
+        roundtrip(Tensor::i16s(&[2], &[-1, 2]).unwrap());
+        roundtrip(Tensor::i32s(&[2, 1], &[-1, 2]).unwrap());
+        roundtrip(Tensor::i64s(&[2], &[-1, 1 << 40]).unwrap());
+        roundtrip(Tensor::f32s(&[1, 2, 3], &[1.0, 2.5, -3.0, 0.0, 1e-7, 4.0]).unwrap());
+        roundtrip(Tensor::f64s(&[], &[3.14]).unwrap());
+        roundtrip(Tensor::u32s(&[2], &[1, 1 << 31]).unwrap());
+        roundtrip(Tensor::u64s(&[2], &[1, 1 << 63]).unwrap());
+        roundtrip(Tensor::f16s(&[2], &[f16::from_f32(0.5), f16::from_f32(-2.0)]).unwrap());
+        roundtrip(Tensor::complex_f32s(&[1], &[Complex::new(1.0, -1.0)]).unwrap());
+        roundtrip(Tensor::complex_f64s(&[1], &[Complex::new(1.0, -1.0)]).unwrap());
+    }
+
+    #[test]
+    fn header() {
+        let header = Header {
+            descr: "<f4".to_string(),
+            fortran_order: false,
+            shape: vec![3],
+        };
+        assert_eq!(
+            header.to_string(),
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (3,), }"
+        );
+        assert_eq!(Header::parse(&header.to_string()).unwrap(), header);
+    }
+
+    fn npy(descr: &str, fortran: bool, shape: &str, data: &[u8]) -> Vec<u8> {
+        let header = format!(
+            "{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}\n",
+            descr,
+            if fortran { "True" } else { "False" },
+            shape
+        );
+        let mut buf = MAGIC.to_vec();
+        buf.extend(&[1, 0, header.len() as u8, 0]);
+        buf.extend(header.as_bytes());
+        buf.extend(data);
+        buf
+    }
+
+    #[test]
+    fn read_big_endian() {
+        let buf = npy(">i4", false, "(2,)", &[0, 0, 0, 1, 0, 0, 1, 0]);
+        let t = Tensor::read_npy(&mut &*buf).unwrap();
+        assert_eq!(t, Tensor::i32s(&[2], &[1, 256]).unwrap());
+    }
+
+    #[test]
+    fn read_fortran_order() {
+        let buf = npy("|u1", true, "(2, 3)", &[1, 4, 2, 5, 3, 6]);
+        let t = Tensor::read_npy(&mut &*buf).unwrap();
+        assert_eq!(t, Tensor::u8s(&[2, 3], &[1, 2, 3, 4, 5, 6]).unwrap());
+    }
+
+    #[test]
+    fn reject_wrong_size() {
+        let buf = npy("<i4", false, "(3,)", &[0, 0, 0, 1, 0, 0, 1, 0]);
+        assert!(Tensor::read_npy(&mut &*buf).is_err());
+    }
+
+    #[test]
+    fn npz_roundtrip() {
+        let a = Tensor::f32s(&[2], &[1.0, 2.0]).unwrap();
+        let b = Tensor::i64s(&[1, 1], &[42]).unwrap();
+        let mut buf = Cursor::new(vec![]);
+        write_npz(&mut buf, &[("a", &a), ("b", &b)]).unwrap();
+        buf.set_position(0);
+        let found = read_npz(buf).unwrap();
+        assert_eq!(found, vec![("a".to_string(), a), ("b".to_string(), b)]);
+    }
+}
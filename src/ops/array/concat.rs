@@ -0,0 +1,98 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ops::prelude::*;
+
+use super::{resolve_axis, stack};
+
+/// Concatenates its inputs along an axis.
+#[derive(Debug, Clone, new)]
+pub struct Concat {
+    axis: i64,
+}
+
+impl Concat {
+    fn eval_t<T: Datum>(inputs: &[Value], axis: usize) -> TfdResult<Value> {
+        let arrays = inputs
+            .iter()
+            .map(|v| v.to_array_view::<T>())
+            .collect::<TfdResult<Vec<_>>>()?;
+        Ok(stack(Axis(axis), &arrays)?.into())
+    }
+
+    /// Inference rules for the first `n` inputs and the output, for
+    /// frontends passing more inputs than the concatenated ones.
+    pub fn shape_rules<'r, 'p: 'r>(
+        &self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        n: usize,
+        output: &'p TensorProxy,
+    ) {
+        let axis = self.axis;
+        solver
+            .equals(&output.datum_type, &inputs[0].datum_type)
+            .equals(&output.rank, &inputs[0].rank)
+            .equals_all((0..n).map(|i| (&inputs[i].datum_type).bex()).collect())
+            .equals_all((0..n).map(|i| (&inputs[i].rank).bex()).collect())
+            .given(&inputs[0].rank, move |solver, rank| {
+                let axis = match resolve_axis(axis, rank as usize) {
+                    Ok(axis) => axis,
+                    Err(_) => return,
+                };
+                for d in 0..rank as usize {
+                    if d == axis {
+                        continue;
+                    }
+                    solver.equals(&inputs[0].shape[d], &output.shape[d]);
+                    solver.equals_all((0..n).map(|i| (&inputs[i].shape[d]).bex()).collect());
+                }
+                let mut concat_dim = -1 * output.shape[axis].bex();
+                for i in 0..n {
+                    concat_dim = concat_dim + inputs[i].shape[axis].bex();
+                }
+                solver.equals_zero(concat_dim);
+            });
+    }
+}
+
+impl Op for Concat {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        if inputs.is_empty() {
+            bail!("Concat needs at least one input")
+        }
+        let axis = resolve_axis(self.axis, inputs[0].shape().len())?;
+        Ok(tvec![dispatch_datum!(Self::eval_t(inputs[0].datum_type())(&inputs, axis))?])
+    }
+}
+
+impl InferenceRulesOp for Concat {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&outputs.len, 1)
+            .given(&inputs.len, move |solver, n| {
+                self.shape_rules(solver, inputs, n as usize, &outputs[0]);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concat_negative_axis() {
+        let a = Tensor::i32s(&[2, 1], &[1, 2]).unwrap();
+        let b = Tensor::i32s(&[2, 2], &[3, 4, 5, 6]).unwrap();
+        let output = Concat::new(-1).eval(tvec![a.into(), b.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::i32s(&[2, 3], &[1, 3, 4, 2, 5, 6]).unwrap()
+        );
+    }
+}
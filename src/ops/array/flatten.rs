@@ -0,0 +1,89 @@
+use analyser::rules::prelude::*;
+use ops::prelude::*;
+
+/// Flattens its input into a matrix, folding the axes before `axis` into
+/// the rows and the others into the columns.
+#[derive(Debug, Clone, new)]
+pub struct Flatten {
+    axis: usize,
+}
+
+impl Flatten {
+    fn output_shape(&self, shape: &[TDim]) -> TfdResult<[TDim; 2]> {
+        if self.axis > shape.len() {
+            bail!("Can not flatten rank {} at axis {}", shape.len(), self.axis)
+        }
+        let product = |dims: &[TDim]| dims.iter().fold(TDim::from(1), |a, &b| a * b);
+        Ok([product(&shape[..self.axis]), product(&shape[self.axis..])])
+    }
+
+    fn eval_t<T: Datum>(input: Value, shape: &[usize]) -> TfdResult<Value> {
+        Ok(input.into_array::<T>()?.into_shape(shape)?.into())
+    }
+}
+
+impl Op for Flatten {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let shape = input.shape();
+        if self.axis > shape.len() {
+            bail!("Can not flatten rank {} at axis {}", shape.len(), self.axis)
+        }
+        let shape = [
+            shape[..self.axis].iter().product::<usize>(),
+            shape[self.axis..].iter().product::<usize>(),
+        ];
+        Ok(tvec![dispatch_datum!(Self::eval_t(input.datum_type())(input, &shape))?])
+    }
+
+    /// Evaluates one step of the operation on the given input tensors.
+    fn step(
+        &self,
+        mut inputs: TVec<StepValue>,
+        _buffer: &mut Box<OpBuffer>,
+    ) -> TfdResult<Option<TVec<Value>>> {
+        let input = args_1!(inputs);
+        match input.into_stream() {
+            Some(Stream { info, chunk: Some(chunk), .. }) if info.axis == 0 && self.axis > 0 => {
+                Ok(Some(self.eval(tvec![chunk])?))
+            }
+            Some(Stream { chunk: None, .. }) => Ok(None),
+            _ => bail!("Flatten can only stream along the batch axis"),
+        }
+    }
+}
+
+impl InferenceRulesOp for Flatten {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&outputs[0].rank, 2)
+            .given(&inputs[0].shape, move |solver, shape: Vec<TDim>| {
+                if let Ok(shape) = self.output_shape(&shape) {
+                    solver.equals(&outputs[0].shape, shape.to_vec());
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flatten() {
+        let input = Tensor::f32s(&[2, 3, 4], &[0.; 24]).unwrap();
+        let output = Flatten::new(1).eval(tvec![input.clone().into()]).unwrap();
+        assert_eq!(output[0].shape(), &[2, 12]);
+        let output = Flatten::new(0).eval(tvec![input.into()]).unwrap();
+        assert_eq!(output[0].shape(), &[1, 24]);
+    }
+}
@@ -0,0 +1,103 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ops::prelude::*;
+
+use super::{resolve_axis, to_i64s};
+
+/// Picks entries of its first input along an axis, at the indices given by
+/// its second input. Negative indices count from the end of the axis.
+#[derive(Debug, Clone, new)]
+pub struct Gather {
+    axis: i64,
+}
+
+impl Gather {
+    fn output_shape<D: Clone>(data: &[D], indices: &[D], axis: usize) -> Vec<D> {
+        let mut shape = data[..axis].to_vec();
+        shape.extend(indices.iter().cloned());
+        shape.extend(data[axis + 1..].iter().cloned());
+        shape
+    }
+
+    fn eval_t<T: Datum>(data: Value, indices: &ArrayD<i64>, axis: usize) -> TfdResult<Value> {
+        let data = data.to_array_view::<T>()?;
+        let dim = data.shape()[axis] as i64;
+        let parts = indices
+            .iter()
+            .map(|&ix| {
+                let fixed = if ix < 0 { ix + dim } else { ix };
+                if fixed < 0 || fixed >= dim {
+                    bail!("Index {} out of bounds for axis of {}", ix, dim)
+                }
+                Ok(data.slice_axis(Axis(axis), (fixed as usize..fixed as usize + 1).into()))
+            })
+            .collect::<TfdResult<Vec<_>>>()?;
+        let shape = Self::output_shape(data.shape(), indices.shape(), axis);
+        if parts.is_empty() {
+            return Ok(ArrayD::<T>::default(shape).into());
+        }
+        Ok(super::stack(Axis(axis), &parts)?.into_shape(shape)?.into())
+    }
+}
+
+impl Op for Gather {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let (data, indices) = args_2!(inputs);
+        let axis = resolve_axis(self.axis, data.shape().len())?;
+        let indices = to_i64s(&indices)?;
+        Ok(tvec![dispatch_datum!(Self::eval_t(data.datum_type())(data, &indices, axis))?])
+    }
+}
+
+impl InferenceRulesOp for Gather {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 2)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(
+                &outputs[0].rank,
+                inputs[0].rank.bex() + inputs[1].rank.bex() - 1,
+            )
+            .given(&inputs[0].shape, move |solver, data: Vec<TDim>| {
+                let axis = match resolve_axis(self.axis, data.len()) {
+                    Ok(axis) => axis,
+                    Err(_) => return,
+                };
+                solver.given(&inputs[1].shape, move |solver, indices: Vec<TDim>| {
+                    let shape = Self::output_shape(&data, &indices, axis);
+                    solver.equals(&outputs[0].shape, shape);
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gather() {
+        let data = Tensor::i32s(&[2, 3], &[1, 2, 3, 4, 5, 6]).unwrap();
+        let indices = Tensor::i64s(&[2, 2], &[0, -1, 1, 1]).unwrap();
+        let output = Gather::new(1).eval(tvec![data.into(), indices.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::i32s(&[2, 2, 2], &[1, 3, 2, 2, 4, 6, 5, 5]).unwrap()
+        );
+    }
+
+    #[test]
+    fn gather_scalar_index() {
+        let data = Tensor::i32s(&[3], &[1, 2, 3]).unwrap();
+        let indices = Tensor::i64s(&[], &[1]).unwrap();
+        let output = Gather::new(0).eval(tvec![data.into(), indices.into()]).unwrap();
+        assert_eq!(output[0].as_tensor(), &Tensor::i32s(&[], &[2]).unwrap());
+    }
+}
@@ -0,0 +1,113 @@
+//! Ops that move data around without computing on it.
+use ndarray::prelude::*;
+
+use tensor::Datum;
+use {DatumType, Tensor, TfdResult};
+
+mod concat;
+mod flatten;
+mod gather;
+mod pad;
+mod reshape;
+mod shape;
+mod slice;
+mod split;
+mod squeeze;
+mod strided_slice;
+mod transpose;
+mod unsqueeze;
+
+pub use self::concat::Concat;
+pub use self::flatten::Flatten;
+pub use self::gather::Gather;
+pub use self::pad::{Pad, PadMode};
+pub use self::reshape::Reshape;
+pub use self::shape::Shape;
+pub use self::slice::Slice;
+pub use self::split::Split;
+pub use self::squeeze::Squeeze;
+pub use self::strided_slice::StridedSlice;
+pub use self::transpose::Transpose;
+pub use self::unsqueeze::Unsqueeze;
+
+/// Resolves a possibly negative axis against a rank.
+pub fn resolve_axis(axis: i64, rank: usize) -> TfdResult<usize> {
+    let fixed = if axis < 0 { axis + rank as i64 } else { axis };
+    if fixed < 0 || fixed >= rank as i64 {
+        bail!("Invalid axis {} for rank {}", axis, rank)
+    }
+    Ok(fixed as usize)
+}
+
+/// Reads an integer tensor, like a shape or indices, as `i64`s.
+pub fn to_i64s(tensor: &Tensor) -> TfdResult<ArrayD<i64>> {
+    match tensor.datum_type() {
+        DatumType::I32 => Ok(tensor.to_array_view::<i32>()?.mapv(|x| x as i64)),
+        _ => Ok(tensor.cast_to_array::<i64>()?.into_owned()),
+    }
+}
+
+/// Concatenates arrays along an existing axis.
+///
+/// Unlike `ndarray::stack`, this only requires the elements to be `Clone`,
+/// so it works on every datum type, strings included.
+pub fn stack<T: Datum>(axis: Axis, arrays: &[ArrayViewD<T>]) -> TfdResult<ArrayD<T>> {
+    if arrays.len() == 0 {
+        bail!("Can not stack an empty list of arrays")
+    }
+    let mut shape = arrays[0].shape().to_vec();
+    if axis.index() >= shape.len() {
+        bail!("Can not stack on axis {} arrays of rank {}", axis.index(), shape.len())
+    }
+    for a in &arrays[1..] {
+        let compatible = a.ndim() == shape.len()
+            && a.shape()
+                .iter()
+                .zip(shape.iter())
+                .enumerate()
+                .all(|(ix, (x, y))| ix == axis.index() || x == y);
+        if !compatible {
+            bail!(
+                "Can not stack on axis {} arrays of shapes {:?} and {:?}",
+                axis.index(),
+                arrays[0].shape(),
+                a.shape()
+            )
+        }
+    }
+    shape[axis.index()] = arrays.iter().map(|a| a.shape()[axis.index()]).sum();
+    let mut result = ArrayD::<T>::default(shape);
+    let mut offset = 0;
+    for a in arrays {
+        let len = a.shape()[axis.index()];
+        result
+            .slice_axis_mut(axis, (offset..offset + len).into())
+            .assign(a);
+        offset += len;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_strings() {
+        let a = arr1(&[b"a".to_vec(), b"b".to_vec()]).into_dyn();
+        let b = arr1(&[b"c".to_vec()]).into_dyn();
+        let c = stack(Axis(0), &[a.view(), b.view()]).unwrap();
+        assert_eq!(
+            c,
+            arr1(&[b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]).into_dyn()
+        );
+    }
+
+    #[test]
+    fn stack_mismatch() {
+        let a = Array::from_elem(vec![2, 3], 0i32);
+        let b = Array::from_elem(vec![2, 4], 0i32);
+        assert!(stack(Axis(0), &[a.view(), b.view()]).is_err());
+        assert_eq!(stack(Axis(1), &[a.view(), b.view()]).unwrap().shape(), &[2, 7]);
+    }
+}
@@ -0,0 +1,155 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use num::NumCast;
+use ops::prelude::*;
+
+/// How the values in the padding are picked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PadMode {
+    /// A constant value.
+    Constant(f32),
+    /// The input mirrored around its border, the border itself excluded.
+    Reflect,
+    /// The value on the border.
+    Edge,
+}
+
+/// Pads its input with the given number of values before and after each
+/// axis.
+#[derive(Debug, Clone, new)]
+pub struct Pad {
+    pads: Vec<(usize, usize)>,
+    mode: PadMode,
+}
+
+impl Pad {
+    /// Maps a padded coordinate to the input one, if any.
+    fn source(&self, x: usize, before: usize, dim: usize) -> Option<usize> {
+        let x = x as isize - before as isize;
+        let dim = dim as isize;
+        if x >= 0 && x < dim {
+            return Some(x as usize);
+        }
+        match self.mode {
+            PadMode::Constant(_) => None,
+            PadMode::Edge => Some(x.max(0).min(dim - 1) as usize),
+            PadMode::Reflect => {
+                let x = if x < 0 { -x } else { 2 * (dim - 1) - x };
+                Some(x.max(0).min(dim - 1) as usize)
+            }
+        }
+    }
+
+    fn eval_t<T: Datum + Copy + NumCast>(&self, input: Value) -> TfdResult<Value> {
+        let input = input.to_array_view::<T>()?;
+        if self.pads.len() != input.ndim() {
+            bail!("Pad expects {} pads, got {:?}", input.ndim(), self.pads)
+        }
+        let value = match self.mode {
+            PadMode::Constant(v) => T::from(v).ok_or("Can not cast pad value")?,
+            _ => T::from(0).unwrap(),
+        };
+        if self.mode == PadMode::Reflect
+            && self.pads
+                .iter()
+                .zip(input.shape())
+                .any(|(&(b, a), &d)| b.max(a) >= d)
+        {
+            bail!("Reflect padding {:?} does not fit in {:?}", self.pads, input.shape())
+        }
+        let shape: Vec<usize> = input
+            .shape()
+            .iter()
+            .zip(&self.pads)
+            .map(|(&d, &(b, a))| d + b + a)
+            .collect();
+        let mut coords = vec![0; input.ndim()];
+        let output = ArrayD::from_shape_fn(shape, |ix| {
+            for axis in 0..coords.len() {
+                match self.source(ix[axis], self.pads[axis].0, input.shape()[axis]) {
+                    Some(x) => coords[axis] = x,
+                    None => return value,
+                }
+            }
+            input[&*coords]
+        });
+        Ok(output.into())
+    }
+
+    /// Inference rules for an input and output tensor, for frontends
+    /// getting the pads from an input.
+    pub fn shape_rules<'r, 'p: 'r>(
+        &self,
+        solver: &mut Solver<'r>,
+        input: &'p TensorProxy,
+        output: &'p TensorProxy,
+    ) {
+        solver
+            .equals(&input.datum_type, &output.datum_type)
+            .equals(&input.rank, self.pads.len() as i64)
+            .equals(&output.rank, self.pads.len() as i64);
+        for (axis, &(before, after)) in self.pads.iter().enumerate() {
+            solver.equals(&output.shape[axis], input.shape[axis].bex() + (before + after).to_dim());
+        }
+    }
+}
+
+impl Op for Pad {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let output = match input.datum_type() {
+            DatumType::U8 => self.eval_t::<u8>(input)?,
+            DatumType::U16 => self.eval_t::<u16>(input)?,
+            DatumType::U32 => self.eval_t::<u32>(input)?,
+            DatumType::U64 => self.eval_t::<u64>(input)?,
+            DatumType::I8 => self.eval_t::<i8>(input)?,
+            DatumType::I16 => self.eval_t::<i16>(input)?,
+            DatumType::I32 => self.eval_t::<i32>(input)?,
+            DatumType::I64 => self.eval_t::<i64>(input)?,
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, tvec![input]),
+            DatumType::F32 => self.eval_t::<f32>(input)?,
+            DatumType::F64 => self.eval_t::<f64>(input)?,
+            dt => bail!("Pad not covering {:?}", dt),
+        };
+        Ok(tvec![output])
+    }
+}
+
+impl InferenceRulesOp for Pad {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1);
+        self.shape_rules(solver, &inputs[0], &outputs[0]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad(mode: PadMode) -> Tensor {
+        let input = Tensor::i32s(&[3], &[1, 2, 3]).unwrap();
+        Pad::new(vec![(2, 1)], mode)
+            .eval(tvec![input.into()])
+            .unwrap()
+            .remove(0)
+            .into_tensor()
+    }
+
+    #[test]
+    fn pad_modes() {
+        assert_eq!(
+            pad(PadMode::Constant(7.)),
+            Tensor::i32s(&[6], &[7, 7, 1, 2, 3, 7]).unwrap()
+        );
+        assert_eq!(pad(PadMode::Edge), Tensor::i32s(&[6], &[1, 1, 1, 2, 3, 3]).unwrap());
+        assert_eq!(pad(PadMode::Reflect), Tensor::i32s(&[6], &[3, 2, 1, 2, 3, 2]).unwrap());
+    }
+}
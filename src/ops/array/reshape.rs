@@ -0,0 +1,130 @@
+use analyser::rules::prelude::*;
+use ops::prelude::*;
+
+use super::to_i64s;
+
+/// Reshapes its first input to the shape given by its second one.
+///
+/// A dimension of -1 is inferred from the number of elements, a dimension
+/// of 0 copies the matching dimension of the input, unless `allow_zero`
+/// makes it an actual 0, like in Tensorflow.
+#[derive(Debug, Clone, Default, new)]
+pub struct Reshape {
+    allow_zero: bool,
+}
+
+/// Resolves the requested shape against the input one.
+fn output_shape(input: &[TDim], shape: &[i64], allow_zero: bool) -> TfdResult<Vec<TDim>> {
+    let mut output = shape
+        .iter()
+        .enumerate()
+        .map(|(ix, &d)| match d {
+            -1 => Ok(None),
+            0 if allow_zero => Ok(Some(TDim::from(0))),
+            0 => match input.get(ix) {
+                Some(&d) => Ok(Some(d)),
+                None => bail!("Can not copy dimension {} of {:?}", ix, input),
+            },
+            d if d > 0 => Ok(Some(TDim::from(d))),
+            d => bail!("Invalid dimension {} in reshape", d),
+        })
+        .collect::<TfdResult<Vec<Option<TDim>>>>()?;
+    let missing: Vec<usize> = (0..output.len()).filter(|&ix| output[ix].is_none()).collect();
+    if missing.len() > 1 {
+        bail!("Reshape can only infer one dimension, got {:?}", shape)
+    }
+    if let Some(&ix) = missing.first() {
+        // Cancel the known dimensions against the input ones before
+        // dividing, so that streaming dimensions simplify away.
+        let mut remaining = input.to_vec();
+        let mut divisor = TDim::from(1);
+        for &d in output.iter().filter_map(|d| d.as_ref()) {
+            match remaining.iter().position(|&r| r == d) {
+                Some(pos) => {
+                    remaining.remove(pos);
+                }
+                None => divisor *= d,
+            }
+        }
+        let total = remaining.iter().fold(TDim::from(1), |a, &b| a * b);
+        let mut dim = if divisor.is_one() { total } else { total / divisor };
+        dim.reduce();
+        output[ix] = Some(dim);
+    }
+    Ok(output.into_iter().map(|d| d.unwrap()).collect())
+}
+
+impl Reshape {
+    fn eval_t<T: Datum>(input: Value, shape: &[usize]) -> TfdResult<Value> {
+        Ok(input.into_array::<T>()?.into_shape(shape)?.into())
+    }
+}
+
+impl Op for Reshape {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let (input, shape) = args_2!(inputs);
+        let input_shape: Vec<TDim> = input.shape().iter().map(|&d| d.into()).collect();
+        let shape = output_shape(&input_shape, &to_i64s(&shape)?.into_raw_vec(), self.allow_zero)?
+            .iter()
+            .map(|d| Ok(d.to_integer()? as usize))
+            .collect::<TfdResult<Vec<usize>>>()?;
+        Ok(tvec![dispatch_datum!(Self::eval_t(input.datum_type())(input, &shape))?])
+    }
+}
+
+impl InferenceRulesOp for Reshape {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 2)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[1].rank, 1)
+            .equals(&inputs[1].shape[0], outputs[0].rank.bex().to_dim())
+            .given(&inputs[1].value, move |solver, shape: Tensor| {
+                let shape = match to_i64s(&shape) {
+                    Ok(shape) => shape.into_raw_vec(),
+                    Err(_) => return,
+                };
+                solver.given(&inputs[0].shape, move |solver, input: Vec<TDim>| {
+                    if let Ok(output) = output_shape(&input, &shape, self.allow_zero) {
+                        solver.equals(&outputs[0].shape, output);
+                    }
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reshape_copy_and_infer() {
+        let input = Tensor::f32s(&[2, 3, 4], &[0.; 24]).unwrap();
+        let shape = Tensor::i64s(&[3], &[0, -1, 2]).unwrap();
+        let output = Reshape::default().eval(tvec![input.into(), shape.into()]).unwrap();
+        assert_eq!(output[0].shape(), &[2, 6, 2]);
+    }
+
+    #[test]
+    fn reshape_streaming_shape() {
+        let input = vec![TDim::stream(), 3.into(), 4.into()];
+        assert_eq!(
+            output_shape(&input, &[0, -1], false).unwrap(),
+            vec![TDim::stream(), 12.into()]
+        );
+    }
+    #[test]
+    fn reshape_allow_zero() {
+        let input = Tensor::f32s(&[2, 0], &[]).unwrap();
+        let shape = Tensor::i32s(&[3], &[0, 4, -1]).unwrap();
+        let output = Reshape::new(true).eval(tvec![input.into(), shape.into()]).unwrap();
+        assert_eq!(output[0].shape(), &[0, 4, 0]);
+    }
+}
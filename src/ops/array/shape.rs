@@ -0,0 +1,148 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ops::prelude::*;
+
+/// Outputs the shape of its input as a vector of the given datum type,
+/// which is one of `I32`, `I64` or `TDim`.
+#[derive(Debug, Clone, new)]
+pub struct Shape {
+    dt: DatumType,
+}
+
+impl Shape {
+    fn shape_tensor(&self, shape: &[TDim]) -> TfdResult<Tensor> {
+        if self.dt == DatumType::TDim {
+            return Ok(Array1::from_vec(shape.to_vec()).into());
+        }
+        let dims = shape
+            .iter()
+            .map(|d| d.to_integer())
+            .collect::<TfdResult<Vec<i64>>>()?;
+        Ok(match self.dt {
+            DatumType::I64 => Array1::from_vec(dims).into(),
+            DatumType::I32 => Array1::from_iter(dims.into_iter().map(|d| d as i32)).into(),
+            dt => bail!("Shape can not be expressed as {:?}", dt),
+        })
+    }
+}
+
+impl Op for Shape {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let shape: Vec<TDim> = input.shape().iter().map(|&d| d.into()).collect();
+        Ok(tvec![self.shape_tensor(&shape)?.into()])
+    }
+}
+
+impl InferenceRulesOp for Shape {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&outputs[0].datum_type, self.dt)
+            .equals(&outputs[0].rank, 1)
+            .given(&inputs[0].rank, move |solver, r| {
+                solver.equals(&outputs[0].shape[0], r.to_dim());
+            })
+            .given(&inputs[0].shape, move |solver, shape: Vec<TDim>| {
+                if let Ok(tensor) = self.shape_tensor(&shape) {
+                    solver.equals(&outputs[0].value, tensor);
+                }
+            })
+            .given(&outputs[0].value, move |solver, shape: Tensor| {
+                if let Ok(shape) = TDim::tensor_cast_to_array(&shape) {
+                    let shape = shape.view().iter().cloned().collect::<Vec<TDim>>();
+                    solver.equals(&inputs[0].shape, shape);
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shape_i64() {
+        let input = Tensor::f32s(&[1, 2, 3], &[0.; 6]).unwrap();
+        let output = Shape::new(DatumType::I64).eval(tvec![input.into()]).unwrap();
+        assert_eq!(output[0].as_tensor(), &Tensor::i64s(&[3], &[1, 2, 3]).unwrap());
+    }
+
+    #[test]
+    fn shape_inference_1() {
+        let input = TensorFact {
+            datum_type: typefact!(DatumType::F32),
+            shape: shapefact![1, _, _; ..],
+            value: valuefact!(_),
+        };
+        let output = TensorFact {
+            datum_type: typefact!(DatumType::TDim),
+            shape: shapefact![_],
+            value: valuefact!(_),
+        };
+        assert_forward!(Shape::new(DatumType::TDim), input, output);
+    }
+
+    #[test]
+    fn shape_inference_2() {
+        let input = TensorFact {
+            datum_type: typefact!(DatumType::F32),
+            shape: shapefact![1, _, _],
+            value: valuefact!(_),
+        };
+        let output = TensorFact {
+            datum_type: typefact!(DatumType::TDim),
+            shape: shapefact![3],
+            value: valuefact!(_),
+        };
+        assert_forward!(Shape::new(DatumType::TDim), input, output);
+    }
+
+    #[test]
+    fn shape_inference_3() {
+        let input = TensorFact {
+            datum_type: typefact!(DatumType::F32),
+            shape: shapefact![1, 2, 3],
+            value: valuefact!(_),
+        };
+        let output = TensorFact {
+            datum_type: typefact!(DatumType::TDim),
+            shape: shapefact![3],
+            value: valuefact!(Tensor::dims(&[3], &[1.to_dim(), 2.to_dim(), 3.to_dim()]).unwrap()),
+        };
+        assert_forward!(Shape::new(DatumType::TDim), input, output);
+    }
+
+    #[test]
+    fn shape_inference_streaming() {
+        let input = TensorFact::default()
+            .with_datum_type(DatumType::F32)
+            .with_shape(shapefact![1, (TDim::stream()), 3]);
+        let output = TensorFact::default()
+            .with_datum_type(DatumType::I64)
+            .with_shape(shapefact![3]);
+        assert_forward!(Shape::new(DatumType::I64), input, output);
+    }
+
+    #[test]
+    fn shape_inference_backward() {
+        let input = TensorFact {
+            datum_type: typefact!(_),
+            shape: shapefact![1, 2, 3],
+            value: valuefact!(_),
+        };
+        let output = TensorFact {
+            datum_type: typefact!(DatumType::TDim),
+            shape: shapefact![3],
+            value: valuefact!(Tensor::dims(&[3], &[1.to_dim(), 2.to_dim(), 3.to_dim()]).unwrap()),
+        };
+        assert_backward!(Shape::new(DatumType::TDim), input, output);
+    }
+}
@@ -0,0 +1,118 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ops::prelude::*;
+
+use super::resolve_axis;
+
+/// Extracts a slice along some axes.
+///
+/// Negative bounds count from the end of the axis, and bounds are clamped
+/// to it. Without axes, the bounds apply to the first axes.
+#[derive(Debug, Clone, new)]
+pub struct Slice {
+    axes: Option<Vec<i64>>,
+    starts: Vec<i64>,
+    ends: Vec<i64>,
+}
+
+impl Slice {
+    /// Resolves the sliced axes, with their start and end.
+    fn bounds(&self, rank: usize) -> TfdResult<Vec<(usize, i64, i64)>> {
+        if self.starts.len() != self.ends.len() {
+            bail!("Slice needs as many starts as ends")
+        }
+        let axes = match self.axes {
+            Some(ref axes) if axes.len() != self.starts.len() => {
+                bail!("Slice needs as many axes as starts")
+            }
+            Some(ref axes) => axes
+                .iter()
+                .map(|&a| resolve_axis(a, rank))
+                .collect::<TfdResult<Vec<_>>>()?,
+            None => (0..self.starts.len()).collect(),
+        };
+        Ok(axes
+            .into_iter()
+            .zip(self.starts.iter().zip(self.ends.iter()))
+            .map(|(axis, (&start, &end))| (axis, start, end))
+            .collect())
+    }
+
+    fn range(dim: usize, start: i64, end: i64) -> (usize, usize) {
+        let fix = |x: i64| {
+            let x = if x < 0 { x + dim as i64 } else { x };
+            x.max(0).min(dim as i64) as usize
+        };
+        let (start, end) = (fix(start), fix(end));
+        (start, end.max(start))
+    }
+
+    fn eval_t<T: Datum>(&self, input: Value) -> TfdResult<Value> {
+        let mut input = input.to_array_view::<T>()?;
+        for (axis, start, end) in self.bounds(input.ndim())? {
+            let (start, end) = Self::range(input.shape()[axis], start, end);
+            input.slice_axis_inplace(Axis(axis), (start..end).into());
+        }
+        Ok(input.to_owned().into())
+    }
+}
+
+impl Op for Slice {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        Ok(tvec![dispatch_datum!(Self::eval_t(input.datum_type())(self, input))?])
+    }
+}
+
+impl InferenceRulesOp for Slice {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].rank, &outputs[0].rank)
+            .given(&inputs[0].rank, move |solver, rank: i64| {
+                let bounds = match self.bounds(rank as usize) {
+                    Ok(bounds) => bounds,
+                    Err(_) => return,
+                };
+                for axis in 0..rank as usize {
+                    match bounds.iter().find(|b| b.0 == axis) {
+                        None => {
+                            solver.equals(&inputs[0].shape[axis], &outputs[0].shape[axis]);
+                        }
+                        Some(&(_, start, end)) => {
+                            solver.given(&inputs[0].shape[axis], move |solver, dim: TDim| {
+                                if let Ok(dim) = dim.to_integer() {
+                                    let (start, end) = Self::range(dim as usize, start, end);
+                                    solver.equals(&outputs[0].shape[axis], (end - start).to_dim());
+                                }
+                            });
+                        }
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_clamped_and_negative() {
+        let input = Tensor::i32s(&[2, 4], &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        let op = Slice::new(Some(vec![1]), vec![-3], vec![::std::i64::MAX]);
+        let output = op.eval(tvec![input.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::i32s(&[2, 3], &[2, 3, 4, 6, 7, 8]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ops::prelude::*;
+
+use super::resolve_axis;
+
+/// Splits its input along an axis into `outputs` parts, of the given lengths
+/// or of equal lengths.
+#[derive(Debug, Clone, new)]
+pub struct Split {
+    axis: i64,
+    outputs: usize,
+    split: Option<Vec<usize>>,
+}
+
+impl Split {
+    fn lengths(&self, dim: usize) -> TfdResult<Vec<usize>> {
+        match self.split {
+            Some(ref split) => {
+                if split.len() != self.outputs || split.iter().sum::<usize>() != dim {
+                    bail!("Can not split {} in {:?}", dim, split)
+                }
+                Ok(split.clone())
+            }
+            None => {
+                if self.outputs == 0 || dim % self.outputs != 0 {
+                    bail!("Can not split {} in {} equal parts", dim, self.outputs)
+                }
+                Ok(vec![dim / self.outputs; self.outputs])
+            }
+        }
+    }
+
+    fn eval_t<T: Datum>(&self, input: Value) -> TfdResult<TVec<Value>> {
+        let input = input.to_array_view::<T>()?;
+        let axis = resolve_axis(self.axis, input.ndim())?;
+        let mut offset = 0;
+        let mut outputs = tvec![];
+        for len in self.lengths(input.shape()[axis])? {
+            let part = input.slice_axis(Axis(axis), (offset..offset + len).into());
+            outputs.push(part.to_owned().into());
+            offset += len;
+        }
+        Ok(outputs)
+    }
+}
+
+impl Op for Split {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        dispatch_datum!(Self::eval_t(input.datum_type())(self, input))
+    }
+
+    // The analyser only tracks the first output of a node, so the last
+    // output of a constant split must not be propagated as its value.
+    fn infer_and_propagate(
+        &self,
+        inputs: TVec<TensorFact>,
+        outputs: TVec<TensorFact>,
+    ) -> TfdResult<(TVec<TensorFact>, TVec<TensorFact>)> {
+        self.infer(inputs, outputs)
+    }
+}
+
+impl InferenceRulesOp for Split {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        // The analyser hands every node a single output fact, shared by all
+        // the parts, so only what the parts have in common is constrained.
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].rank, &outputs[0].rank);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split() {
+        let input = Tensor::i32s(&[2, 3], &[1, 2, 3, 4, 5, 6]).unwrap();
+        let outputs = Split::new(1, 2, Some(vec![1, 2]))
+            .eval(tvec![input.into()])
+            .unwrap();
+        assert_eq!(outputs[0].as_tensor(), &Tensor::i32s(&[2, 1], &[1, 4]).unwrap());
+        assert_eq!(outputs[1].as_tensor(), &Tensor::i32s(&[2, 2], &[2, 3, 5, 6]).unwrap());
+    }
+}
@@ -0,0 +1,192 @@
+use analyser::rules::prelude::*;
+use ops::prelude::*;
+
+use super::resolve_axis;
+
+/// Removes axes of length one.
+///
+/// Without axes, or with an empty list of them, every axis of length one is
+/// removed. Listed axes of another length are kept.
+#[derive(Debug, Clone, new)]
+pub struct Squeeze {
+    axes: Option<Vec<i64>>,
+}
+
+impl Squeeze {
+    fn squeezable(&self, ix: usize, d: usize, rank: usize, stream_dim: Option<usize>) -> bool {
+        stream_dim != Some(ix) && d == 1
+            && self
+                .axes
+                .as_ref()
+                .filter(|v| !v.is_empty())
+                .map(|axes| axes.iter().any(|&a| resolve_axis(a, rank).ok() == Some(ix)))
+                .unwrap_or(true)
+    }
+
+    /// Removes the dimensions of size 1 from the given shape vector.
+    fn squeeze_shape(&self, shape: &[usize], stream_dim: Option<usize>) -> Vec<usize> {
+        shape
+            .iter()
+            .enumerate()
+            .filter(|&(ix, &d)| !self.squeezable(ix, d, shape.len(), stream_dim))
+            .map(|(_, &d)| d)
+            .collect()
+    }
+
+    fn eval_t<T: Datum>(&self, input: Value, stream_dim: Option<usize>) -> TfdResult<Value> {
+        let data = input.into_array::<T>()?;
+        let shape = self.squeeze_shape(data.shape(), stream_dim);
+        Ok(data.into_shape(shape)?.into())
+    }
+}
+
+impl Op for Squeeze {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        Ok(tvec![dispatch_datum!(Self::eval_t(input.datum_type())(self, input, None))?])
+    }
+
+    /// Evaluates one step of the operation on the given input tensors.
+    fn step(
+        &self,
+        mut inputs: TVec<StepValue>,
+        _buffer: &mut Box<OpBuffer>,
+    ) -> TfdResult<Option<TVec<Value>>> {
+        let input = args_1!(inputs);
+        if let Some(Stream {
+            info,
+            chunk: Some(chunk),
+            ..
+        }) = input.into_stream()
+        {
+            let axis = Some(info.axis);
+            Ok(Some(tvec![dispatch_datum!(Self::eval_t(chunk.datum_type())(self, chunk, axis))?]))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl InferenceRulesOp for Squeeze {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .given(&inputs[0].shape, move |solver, shape: Vec<TDim>| {
+                let rank = shape.len();
+                let stream_dim = shape.iter().position(|d| d.is_stream());
+                let shape: Vec<TDim> = shape
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(ix, d)| {
+                        // Only dimensions known to be 1 are squeezed, not
+                        // symbolic ones.
+                        let d = if d.to_integer().ok() == Some(1) { 1 } else { 0 };
+                        !self.squeezable(*ix, d, rank, stream_dim)
+                    })
+                    .map(|(_, d)| d)
+                    .collect();
+                solver.equals(&outputs[0].shape, shape);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+    use super::*;
+    use ndarray::*;
+
+    fn run<I>(op: Squeeze, input: I) -> Tensor
+    where
+        I: Into<Tensor>,
+    {
+        op.eval(tvec![input.into().into()])
+            .unwrap()
+            .pop()
+            .unwrap()
+            .into_tensor()
+    }
+
+    #[test]
+    fn squeeze_1() {
+        assert_eq!(
+            run(Squeeze::new(None), Array::from_elem([1, 2, 1, 3, 1, 1], 0)).shape(),
+            &[2, 3]
+        );
+    }
+
+    #[test]
+    fn squeeze_2() {
+        assert_eq!(
+            run(
+                Squeeze::new(Some(vec![2, 4])),
+                Array::from_elem([1, 2, 1, 3, 1, 1], 0)
+            ).shape(),
+            &[1, 2, 3, 1]
+        );
+    }
+
+    #[test]
+    fn squeeze_3() {
+        // Tf considers empty list as absent list.
+        assert_eq!(
+            run(
+                Squeeze::new(Some(vec![])),
+                Array::from_elem([1, 2, 1, 3, 1, 1], 0)
+            ).shape(),
+            &[2, 3]
+        );
+    }
+
+    #[test]
+    fn squeeze_negative_axis() {
+        assert_eq!(
+            run(Squeeze::new(Some(vec![-1])), Array::from_elem([1, 2, 1], 0)).shape(),
+            &[1, 2]
+        );
+    }
+
+    #[test]
+    fn squeeze_inference_1() {
+        let input = TensorFact::default()
+            .with_datum_type(DatumType::TDim)
+            .with_shape(shapefact![1, 1, (TDim::stream() - 2), 16]);
+
+        let op = Squeeze::new(Some(vec![1]));
+        let inferred = op
+            .infer(tvec!(input), tvec!(TensorFact::default()))
+            .unwrap();
+
+        let expect: TVec<_> = tvec!(
+            TensorFact::default()
+                .with_datum_type(DatumType::TDim)
+                .with_shape(shapefact![1, (TDim::stream() - 2), 16])
+        );
+
+        assert_eq!(inferred.1, expect);
+    }
+    #[test]
+    fn squeeze_inference_symbolic() {
+        let input = TensorFact::default()
+            .with_datum_type(DatumType::F32)
+            .with_shape(shapefact![1, (TDim::sym('N')), 3]);
+        let op = Squeeze::new(None);
+        let inferred = op
+            .infer(tvec!(input), tvec!(TensorFact::default()))
+            .unwrap();
+        let expect: TVec<_> = tvec!(
+            TensorFact::default()
+                .with_datum_type(DatumType::F32)
+                .with_shape(shapefact![(TDim::sym('N')), 3])
+        );
+        assert_eq!(inferred.1, expect);
+    }
+}
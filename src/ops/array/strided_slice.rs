@@ -1,32 +1,15 @@
-use std::marker::PhantomData;
-
-use tfdeploy::analyser::prelude::*;
-use tfdeploy::analyser::rules::prelude::*;
-use tfdeploy::ops::prelude::*;
+use analyser::rules::prelude::*;
+use analyser::TensorFact;
 use ndarray::prelude::*;
-use tfdeploy::TfdResult;
-
-pub fn build(pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
-    let begin_mask = pb.get_attr_opt_int("begin_mask")?.unwrap_or(0);
-    let end_mask = pb.get_attr_opt_int("end_mask")?.unwrap_or(0);
-    let shrink_axis_mask = pb.get_attr_opt_int("shrink_axis_mask")?.unwrap_or(0);
-    let datum_type = pb.get_attr_datum_type("T")?;
-    let base = BaseStridedSlice::new(begin_mask, end_mask, shrink_axis_mask);
-    if datum_type == DatumType::I32 {
-        Ok(Box::new(StridedSliceD::new(base)))
-    } else {
-        Ok(boxed_new!(StridedSlice(datum_type)(base)))
-    }
-}
-
-#[derive(Debug, Clone)]
-struct StrideSliceBuffer {
-    skip: Option<usize>,
-}
-impl OpBuffer for StrideSliceBuffer {}
+use ops::prelude::*;
 
+/// Tensorflow's StridedSlice: extracts a strided slice of its first input,
+/// with the begin, end and strides given as inputs.
+///
+/// Bits of the masks flag the axes to take from their beginning, up to
+/// their end, or to shrink away after taking a single element.
 #[derive(Debug, Default, Clone, new)]
-pub struct BaseStridedSlice {
+pub struct StridedSlice {
     begin_mask: i64,
     end_mask: i64,
     shrink_axis_mask: i64,
@@ -43,14 +26,14 @@ struct Dim {
 impl Dim {
     fn len(&self) -> TfdResult<usize> {
         Ok(
-            (((self.stride.abs() as i32 - 1) + (self.end - self.begin).to_integer()?.abs() as i32)
+            (((self.stride.abs() - 1) + (self.end - self.begin).to_integer()?.abs() as i32)
                 / self.stride.abs()) as usize,
         )
     }
 
     fn soft_len(&self) -> TfdResult<TDim> {
         if let Ok(len) = (self.end - self.begin).to_integer() {
-            Ok((((self.stride.abs() as i32 - 1) + len.abs() as i32) / self.stride.abs()).to_dim())
+            Ok((((self.stride.abs() - 1) + len.abs() as i32) / self.stride.abs()).to_dim())
         } else if self.stride == 1 {
             Ok(self.end - self.begin)
         } else {
@@ -59,7 +42,7 @@ impl Dim {
     }
 }
 
-impl BaseStridedSlice {
+impl StridedSlice {
     fn must_shrink(&self, ix: usize) -> bool {
         self.shrink_axis_mask & (1 << ix) != 0
     }
@@ -184,7 +167,7 @@ impl BaseStridedSlice {
         Ok((bounds, mid_shape, end_shape))
     }
 
-    fn eval<T: Datum>(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+    fn eval_t<T: Datum>(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
         let (input, begin, end, strides) = args_4!(inputs);
         let (bounds, mid_shape, end_shape) = self.prepare(input.shape(), begin, end, strides)?;
         let input = input.to_array_view::<T>()?;
@@ -198,13 +181,13 @@ impl BaseStridedSlice {
                         as usize
                 })
                 .collect();
-            input[&*coord]
+            input[&*coord].clone()
         });
         let output = output.into_shape(end_shape)?;
         Ok(tvec![output.into()])
     }
 
-    fn step<T: Datum>(
+    fn step_t<T: Datum>(
         &self,
         mut inputs: TVec<StepValue>,
         _buffer: &mut Box<OpBuffer>,
@@ -253,14 +236,79 @@ impl BaseStridedSlice {
         begin[dim] = 0.to_dim();
         end[dim] = 1.to_dim();
         strides[dim] = 1;
-        Ok(Some(self.eval::<T>(tvec!(
+        Ok(Some(self.eval_t::<T>(tvec!(
             input,
             begin.to_owned().into(),
             end.into(),
             strides.into()
         ))?))
     }
+}
+
+impl Op for StridedSlice {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        dispatch_datum!(Self::eval_t(inputs[0].datum_type())(self, inputs))
+    }
+
+    fn step(
+        &self,
+        inputs: TVec<StepValue>,
+        buffer: &mut Box<OpBuffer>,
+    ) -> TfdResult<Option<TVec<Value>>> {
+        let dt = inputs[0]
+            .as_stream()
+            .and_then(|s| s.chunk.as_ref())
+            .map(|t| t.datum_type());
+        if let Some(dt) = dt {
+            dispatch_datum!(Self::step_t(dt)(self, inputs, buffer))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn final_prep(
+        &self,
+        mut inputs: TVec<TensorFact>,
+        _outputs: TVec<TensorFact>,
+    ) -> TfdResult<Option<Box<Op>>> {
+        let (input, begin, end, strides) = args_4!(inputs);
+        if let (Some(shape), Some(begin), Some(end), Some(strides)) = (
+            input.shape.concretize(),
+            begin.concretize(),
+            end.concretize(),
+            strides.concretize(),
+        ) {
+            let casted_begin =TDim::tensor_cast_to_array(&begin)?;
+            let begin = casted_begin.view().into_dimensionality()?;
+            let casted_end =TDim::tensor_cast_to_array(&end)?;
+            let end = casted_end.view().into_dimensionality().unwrap();
+            let casted_strides = i32::tensor_cast_to_array(&strides)?;
+            let strides = casted_strides.view().into_dimensionality()?;
+
+            let bounds: Vec<Dim> = (0..shape.len())
+                .map(|ix| self.prepare_one_dim(ix, shape[ix], &begin, &end, &strides))
+                .collect::<Vec<_>>();
+
+            if shape.iter().zip(bounds.iter()).all(|(s, b)| {
+                s.is_stream()
+                    || (!b.shrink
+                        && b.begin.to_integer().unwrap() == 0
+                        && (b.end.to_integer().unwrap() == 0 || b.end == *s)
+                        && b.stride == 1)
+            }) {
+                if let Some(axis) = shape.iter().position(|d| d.is_stream()) {
+                    return Ok(Some(Box::new(SkipBeginStreamStridedSlice::new(
+                        bounds[axis].begin.to_integer().unwrap() as u64,
+                    ))));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
 
+impl InferenceRulesOp for StridedSlice {
     fn rules<'r, 'p: 'r, 's: 'r>(
         &'s self,
         solver: &mut Solver<'r>,
@@ -316,142 +364,6 @@ impl BaseStridedSlice {
                 });
             });
     }
-
-    fn final_prep(
-        &self,
-        mut inputs: TVec<TensorFact>,
-        _outputs: TVec<TensorFact>,
-    ) -> TfdResult<Option<Box<Op>>> {
-        let (input, begin, end, strides) = args_4!(inputs);
-        if let (Some(shape), Some(begin), Some(end), Some(strides)) = (
-            input.shape.concretize(),
-            begin.concretize(),
-            end.concretize(),
-            strides.concretize(),
-        ) {
-            let casted_begin =TDim::tensor_cast_to_array(&begin)?;
-            let begin = casted_begin.view().into_dimensionality()?;
-            let casted_end =TDim::tensor_cast_to_array(&end)?;
-            let end = casted_end.view().into_dimensionality().unwrap();
-            let casted_strides = i32::tensor_cast_to_array(&strides)?;
-            let strides = casted_strides.view().into_dimensionality()?;
-
-            let bounds: Vec<Dim> = (0..shape.len())
-                .map(|ix| self.prepare_one_dim(ix, shape[ix], &begin, &end, &strides))
-                .collect::<Vec<_>>();
-
-            if shape.iter().zip(bounds.iter()).all(|(s, b)| {
-                s.is_stream()
-                    || (!b.shrink
-                        && b.begin.to_integer().unwrap() == 0
-                        && (b.end.to_integer().unwrap() == 0 || b.end == *s)
-                        && b.stride == 1)
-            }) {
-                if let Some(axis) = shape.iter().position(|d| d.is_stream()) {
-                    return Ok(Some(Box::new(SkipBeginStreamStridedSlice::new(
-                        bounds[axis].begin.to_integer().unwrap() as u64,
-                    ))));
-                }
-            }
-        }
-        Ok(None)
-    }
-}
-
-#[derive(Debug, Default, Clone, new)]
-pub struct StridedSlice<T: Datum> {
-    base: BaseStridedSlice,
-    _phantom: PhantomData<T>,
-}
-
-impl<T: Datum> Op for StridedSlice<T> {
-    /// Evaluates the operation given the input tensors.
-    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
-        self.base.eval::<T>(inputs)
-    }
-
-    fn step(
-        &self,
-        inputs: TVec<StepValue>,
-        buffer: &mut Box<OpBuffer>,
-    ) -> TfdResult<Option<TVec<Value>>> {
-        self.base.step::<T>(inputs, buffer)
-    }
-
-    fn final_prep(
-        &self,
-        inputs: TVec<TensorFact>,
-        outputs: TVec<TensorFact>,
-    ) -> TfdResult<Option<Box<Op>>> {
-        self.base.final_prep(inputs, outputs)
-    }
-}
-
-impl<T: Datum> InferenceRulesOp for StridedSlice<T> {
-    fn rules<'r, 'p: 'r, 's: 'r>(
-        &'s self,
-        solver: &mut Solver<'r>,
-        inputs: &'p TensorsProxy,
-        outputs: &'p TensorsProxy,
-    ) {
-        self.base.rules(solver, inputs, outputs)
-    }
-}
-
-#[derive(Debug, Default, Clone, new)]
-pub struct StridedSliceD {
-    base: BaseStridedSlice,
-}
-
-impl Op for StridedSliceD {
-    /// Evaluates the operation given the input tensors.
-    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
-        let dt = inputs[0].datum_type();
-        match dt {
-            DatumType::TDim => self.base.eval::<TDim>(inputs),
-            DatumType::I32 => self.base.eval::<i32>(inputs),
-            _ => panic!("StridedSliceD only covering i32 and Dim"),
-        }
-    }
-
-    fn step(
-        &self,
-        inputs: TVec<StepValue>,
-        buffer: &mut Box<OpBuffer>,
-    ) -> TfdResult<Option<TVec<Value>>> {
-        let dt = inputs[0]
-            .as_stream()
-            .and_then(|s| s.chunk.as_ref())
-            .map(|t| t.datum_type());
-        if let Some(dt) = dt {
-            match dt {
-                DatumType::TDim => self.base.step::<TDim>(inputs, buffer),
-                DatumType::I32 => self.base.step::<i32>(inputs, buffer),
-                _ => panic!("StridedSliceD only covering i32 and Dim"),
-            }
-        } else {
-            Ok(None)
-        }
-    }
-
-    fn final_prep(
-        &self,
-        inputs: TVec<TensorFact>,
-        outputs: TVec<TensorFact>,
-    ) -> TfdResult<Option<Box<Op>>> {
-        self.base.final_prep(inputs, outputs)
-    }
-}
-
-impl InferenceRulesOp for StridedSliceD {
-    fn rules<'r, 'p: 'r, 's: 'r>(
-        &'s self,
-        solver: &mut Solver<'r>,
-        inputs: &'p TensorsProxy,
-        outputs: &'p TensorsProxy,
-    ) {
-        self.base.rules(solver, inputs, outputs)
-    }
 }
 
 #[derive(Debug, Default, Clone, new)]
@@ -491,11 +403,10 @@ impl InferenceOp for SkipBeginStreamStridedSlice {
 mod tests {
     #![allow(non_snake_case)]
     use super::*;
-    use ndarray::*;
-    use tfdeploy::Tensor;
-    use tfdeploy::ops::InferenceOp;
+    use ndarray::arr3;
+    use ops::InferenceOp;
 
-    fn eval<I, B, E, S>(op: StridedSlice<i32>, input: I, begin: B, end: E, strides: S) -> Tensor
+    fn eval<I, B, E, S>(op: StridedSlice, input: I, begin: B, end: E, strides: S) -> Tensor
     where
         I: Into<Tensor>,
         B: Into<Tensor>,
@@ -631,7 +542,7 @@ mod tests {
     #[test]
     fn eval_begin_mask_1() {
         let mut op = StridedSlice::default();
-        op.base.begin_mask = 1;
+        op.begin_mask = 1;
         assert_eq!(
             eval(op, arr1(&[0, 1]), arr1(&[1]), arr1(&[1]), arr1(&[1])),
             Tensor::from(arr1(&[0]))
@@ -641,7 +552,7 @@ mod tests {
     #[test]
     fn eval_shrink_1() {
         let mut op = StridedSlice::default();
-        op.base.shrink_axis_mask = 1;
+        op.shrink_axis_mask = 1;
         assert_eq!(
             eval(
                 op,
@@ -657,7 +568,7 @@ mod tests {
     #[test]
     fn eval_shrink_to_scalar() {
         let mut op = StridedSlice::default();
-        op.base.shrink_axis_mask = 1;
+        op.shrink_axis_mask = 1;
         assert_eq!(
             eval(op, arr1(&[0]), arr1(&[0]), arr1(&[0]), arr1(&[1])),
             Tensor::I32(arr0(0).into_dyn())
@@ -666,7 +577,7 @@ mod tests {
 
     #[test]
     fn inference_1() {
-        let op = StridedSlice::<f32>::new(BaseStridedSlice::new(5, 7, 0));
+        let op = StridedSlice::new(5, 7, 0);
         let input = TensorFact::default().with_datum_type(DatumType::F32);
         let begin = TensorFact::from(arr1(&[0i32, 2, 0]));
         let end = TensorFact::from(arr1(&[0i32, 0, 0]));
@@ -700,7 +611,7 @@ mod tests {
 
     #[test]
     fn inference_2() {
-        let op = StridedSlice::<f32>::new(BaseStridedSlice::new(1, 1, 2));
+        let op = StridedSlice::new(1, 1, 2);
         let input = TensorFact::default().with_datum_type(DatumType::F32);
         let begin = TensorFact::from(arr1(&[0i32, 0]));
         let end = TensorFact::from(arr1(&[0i32, 1]));
@@ -734,7 +645,7 @@ mod tests {
 
     #[test]
     fn inference_3() {
-        let op = StridedSlice::<f32>::new(BaseStridedSlice::new(5, 7, 0));
+        let op = StridedSlice::new(5, 7, 0);
         let input = TensorFact::dt_shape(DatumType::F32, shapefact!(1, (TDim::stream() - 2), 16));
         let begin = TensorFact::from(arr1(&[0i32, 2, 0]));
         let end = TensorFact::from(arr1(&[0i32, 0, 0]));
@@ -757,7 +668,7 @@ mod tests {
 
     #[test]
     fn inference_4() {
-        let op = StridedSlice::<f32>::new(BaseStridedSlice::new(5, 7, 0));
+        let op = StridedSlice::new(5, 7, 0);
         let input = TensorFact::dt_shape(DatumType::F32, shapefact!(1, (TDim::stream() - 2), 16));
         let begin = TensorFact::from(arr1(&[0i32, 2, 0]));
         let end = TensorFact::from(arr1(&[0i32, 0, 0]));
@@ -0,0 +1,79 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ops::prelude::*;
+
+/// Permutes the axes of its input. Without a permutation, the axes are
+/// reversed.
+#[derive(Debug, Clone, new)]
+pub struct Transpose {
+    perm: Option<Vec<usize>>,
+}
+
+impl Transpose {
+    fn permutation(&self, rank: usize) -> TfdResult<Vec<usize>> {
+        match self.perm {
+            None => Ok((0..rank).rev().collect()),
+            Some(ref perm) => {
+                let mut sorted = perm.clone();
+                sorted.sort();
+                if sorted != (0..rank).collect::<Vec<_>>() {
+                    bail!("Invalid permutation {:?} for rank {}", perm, rank)
+                }
+                Ok(perm.clone())
+            }
+        }
+    }
+
+    fn eval_t<T: Datum>(input: Value, perm: &[usize]) -> TfdResult<Value> {
+        let input = input.into_array::<T>()?.permuted_axes(perm);
+        // Lay the data out again, so that the following ops can reshape it.
+        let shape = input.shape().to_vec();
+        Ok(ArrayD::from_shape_vec(shape, input.iter().cloned().collect())?.into())
+    }
+}
+
+impl Op for Transpose {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let perm = self.permutation(input.shape().len())?;
+        Ok(tvec![dispatch_datum!(Self::eval_t(input.datum_type())(input, &perm))?])
+    }
+}
+
+impl InferenceRulesOp for Transpose {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].rank, &outputs[0].rank)
+            .given(&inputs[0].rank, move |solver, rank: i64| {
+                if let Ok(perm) = self.permutation(rank as usize) {
+                    for (output_axis, &input_axis) in perm.iter().enumerate() {
+                        solver.equals(&outputs[0].shape[output_axis], &inputs[0].shape[input_axis]);
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transpose() {
+        let input = Tensor::i32s(&[2, 3], &[1, 2, 3, 4, 5, 6]).unwrap();
+        let output = Transpose::new(None).eval(tvec![input.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::i32s(&[3, 2], &[1, 4, 2, 5, 3, 6]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use analyser::rules::prelude::*;
+use ops::prelude::*;
+
+use super::resolve_axis;
+
+/// Inserts axes of length one. The axes are positions in the output.
+#[derive(Debug, Clone, new)]
+pub struct Unsqueeze {
+    axes: Vec<i64>,
+}
+
+impl Unsqueeze {
+    fn output_shape<D: Copy + From<usize>>(&self, shape: &[D]) -> TfdResult<Vec<D>> {
+        let rank = shape.len() + self.axes.len();
+        let mut axes = self
+            .axes
+            .iter()
+            .map(|&a| resolve_axis(a, rank))
+            .collect::<TfdResult<Vec<usize>>>()?;
+        axes.sort();
+        axes.dedup();
+        if axes.len() != self.axes.len() {
+            bail!("Unsqueeze axes {:?} are repeated", self.axes)
+        }
+        let mut output = shape.to_vec();
+        for axis in axes {
+            output.insert(axis, D::from(1));
+        }
+        Ok(output)
+    }
+
+    fn eval_t<T: Datum>(input: Value, shape: &[usize]) -> TfdResult<Value> {
+        Ok(input.into_array::<T>()?.into_shape(shape)?.into())
+    }
+}
+
+impl Op for Unsqueeze {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let shape = self.output_shape(input.shape())?;
+        Ok(tvec![dispatch_datum!(Self::eval_t(input.datum_type())(input, &shape))?])
+    }
+}
+
+impl InferenceRulesOp for Unsqueeze {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(
+                &outputs[0].rank,
+                inputs[0].rank.bex() + self.axes.len() as i64,
+            )
+            .given(&inputs[0].shape, move |solver, shape: Vec<TDim>| {
+                if let Ok(shape) = self.output_shape(&shape) {
+                    solver.equals(&outputs[0].shape, shape);
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsqueeze() {
+        let input = Tensor::f32s(&[2, 3], &[0.; 6]).unwrap();
+        let output = Unsqueeze::new(vec![0, -1]).eval(tvec![input.into()]).unwrap();
+        assert_eq!(output[0].shape(), &[1, 2, 3, 1]);
+    }
+}
@@ -0,0 +1,47 @@
+use analyser::rules::prelude::*;
+use ops::prelude::*;
+
+use super::{condition, SubGraph};
+
+/// Runs one of two graphs depending on a boolean condition, its first input.
+///
+/// The branches take no explicit input and produce the op outputs.
+#[derive(Debug, Clone, new)]
+pub struct If {
+    then_branch: SubGraph,
+    else_branch: SubGraph,
+}
+
+impl Op for If {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        if inputs.is_empty() {
+            bail!("If expects a condition")
+        }
+        if condition(&inputs[0])? {
+            self.then_branch.run(tvec![], &inputs)
+        } else {
+            self.else_branch.run(tvec![], &inputs)
+        }
+    }
+
+    // The analyser only tracks the first output of a node.
+    fn infer_and_propagate(
+        &self,
+        inputs: TVec<TensorFact>,
+        outputs: TVec<TensorFact>,
+    ) -> TfdResult<(TVec<TensorFact>, TVec<TensorFact>)> {
+        self.infer(inputs, outputs)
+    }
+}
+
+impl InferenceRulesOp for If {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        _outputs: &'p TensorsProxy,
+    ) {
+        solver.equals(&inputs[0].datum_type, DatumType::Bool);
+    }
+}
@@ -0,0 +1,166 @@
+use analyser::rules::prelude::*;
+use ops::prelude::*;
+
+use super::{condition, empty, stack, SubGraph};
+
+/// Runs a graph while a condition holds and an optional trip count is not
+/// reached.
+///
+/// The op inputs are the trip count and the condition, when present, then
+/// the initial values of the loop-carried state. The body takes the
+/// iteration number, the condition and the state, and produces the new
+/// condition, the new state and values to accumulate. The op outputs the
+/// final state, then the accumulated values, stacked along a new first axis.
+#[derive(Debug, Clone, new)]
+pub struct Loop {
+    body: SubGraph,
+    has_trip_count: bool,
+    has_condition: bool,
+    state: usize,
+}
+
+impl Op for Loop {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let first_state = self.has_trip_count as usize + self.has_condition as usize;
+        if inputs.len() < first_state + self.state {
+            bail!("Loop expects {} inputs, got {}", first_state + self.state, inputs.len())
+        }
+        if self.body.inputs() != self.state + 2 || self.body.outputs() < self.state + 1 {
+            bail!("Loop body does not match a state of {} values", self.state)
+        }
+        let trip_count = if self.has_trip_count {
+            Some(
+                inputs[0]
+                    .cast_to_array::<i64>()?
+                    .view()
+                    .iter()
+                    .next()
+                    .cloned()
+                    .ok_or("Loop expects a trip count")?,
+            )
+        } else {
+            None
+        };
+        let mut cond = if self.has_condition {
+            condition(&inputs[first_state - 1])?
+        } else {
+            true
+        };
+        let mut state: TVec<Value> = inputs[first_state..first_state + self.state]
+            .iter()
+            .cloned()
+            .collect();
+        let mut scans: Vec<Vec<Value>> = vec![vec![]; self.body.outputs() - self.state - 1];
+        let mut i = 0;
+        while cond && trip_count.map(|n| i < n).unwrap_or(true) {
+            let mut body_inputs: TVec<Value> = tvec![Tensor::from(i).into(), Tensor::from(cond).into()];
+            body_inputs.extend(state);
+            let mut outputs = self.body.run(body_inputs, &inputs)?.into_iter();
+            cond = condition(&outputs.next().unwrap())?;
+            state = outputs.by_ref().take(self.state).collect();
+            for (scan, value) in scans.iter_mut().zip(outputs) {
+                scan.push(value);
+            }
+            i += 1;
+        }
+        if i == 0 && !scans.is_empty() {
+            // The body never ran: the accumulated values are built from
+            // what it would have produced on the initial state.
+            let mut body_inputs = tvec![
+                TensorFact::dt_shape(DatumType::I64, Vec::<usize>::new()),
+                TensorFact::dt_shape(DatumType::Bool, Vec::<usize>::new()),
+            ];
+            body_inputs.extend(state.iter().map(|v| TensorFact::from(v.as_tensor().clone())));
+            let facts = self.body.output_facts(body_inputs, &inputs)?;
+            for fact in &facts[self.state + 1..] {
+                state.push(empty(fact, 0)?);
+            }
+        } else {
+            for scan in &scans {
+                state.push(stack(scan, 0)?);
+            }
+        }
+        Ok(state)
+    }
+
+    // The analyser only tracks the first output of a node.
+    fn infer_and_propagate(
+        &self,
+        inputs: TVec<TensorFact>,
+        outputs: TVec<TensorFact>,
+    ) -> TfdResult<(TVec<TensorFact>, TVec<TensorFact>)> {
+        self.infer(inputs, outputs)
+    }
+}
+
+impl InferenceRulesOp for Loop {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        let first_state = self.has_trip_count as usize + self.has_condition as usize;
+        if self.has_condition {
+            solver.equals(&inputs[first_state - 1].datum_type, DatumType::Bool);
+        }
+        if self.state > 0 {
+            solver.equals(&inputs[first_state].datum_type, &outputs[0].datum_type);
+        }
+    }
+}
+
+/// Runs a graph while a condition graph holds.
+///
+/// The op inputs are the initial values of the loop-carried state. Both
+/// graphs take the state, the condition producing a single boolean and the
+/// body the new state. The op outputs the final state.
+#[derive(Debug, Clone, new)]
+pub struct While {
+    cond: SubGraph,
+    body: SubGraph,
+    state: usize,
+}
+
+impl Op for While {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        if inputs.len() < self.state {
+            bail!("While expects {} inputs, got {}", self.state, inputs.len())
+        }
+        if self.cond.inputs() != self.state
+            || self.body.inputs() != self.state
+            || self.body.outputs() != self.state
+        {
+            bail!("While graphs do not match a state of {} values", self.state)
+        }
+        let mut state: TVec<Value> = inputs[..self.state].iter().cloned().collect();
+        while condition(&self.cond.run(state.clone(), &inputs)?[0])? {
+            state = self.body.run(state, &inputs)?;
+        }
+        Ok(state)
+    }
+
+    // The analyser only tracks the first output of a node.
+    fn infer_and_propagate(
+        &self,
+        inputs: TVec<TensorFact>,
+        outputs: TVec<TensorFact>,
+    ) -> TfdResult<(TVec<TensorFact>, TVec<TensorFact>)> {
+        self.infer(inputs, outputs)
+    }
+}
+
+impl InferenceRulesOp for While {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        if self.state > 0 {
+            solver.equals(&inputs[0].datum_type, &outputs[0].datum_type);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Ops running nested graphs.
+use ndarray::prelude::*;
+
+use analyser::types::{Fact, TensorFact};
+use model::{Model, OutletId, TVec};
+use ops::array::resolve_axis;
+use ops::Value;
+use plan::SimplePlan;
+use tensor::Datum;
+use TfdResult;
+
+mod if_then_else;
+mod loops;
+mod scan;
+
+pub use self::if_then_else::If;
+pub use self::loops::{Loop, While};
+pub use self::scan::Scan;
+
+/// A graph owned by a control-flow op, evaluated with a nested plan.
+///
+/// On top of the values the op passes explicitly, the graph may use values
+/// from the outer scope. They come to the op as extra inputs, `captured`
+/// giving for each of them the index of the op input carrying it.
+#[derive(Debug, Clone)]
+pub struct SubGraph {
+    plan: SimplePlan,
+    outputs: Vec<OutletId>,
+    captured: Vec<usize>,
+}
+
+impl SubGraph {
+    /// The plan inputs are the explicit ones followed by the captured ones.
+    pub fn new(
+        model: &Model,
+        inputs: &[impl AsRef<str>],
+        outputs: Vec<OutletId>,
+        captured: Vec<usize>,
+    ) -> TfdResult<SubGraph> {
+        let output_nodes: Vec<&str> = outputs
+            .iter()
+            .map(|o| &*model.nodes()[o.node].name)
+            .collect();
+        let plan = SimplePlan::new(model, inputs, &output_nodes)?;
+        if plan.input_ids.len() < captured.len() {
+            bail!("Sub graph has less inputs than captured values")
+        }
+        Ok(SubGraph {
+            plan,
+            outputs,
+            captured,
+        })
+    }
+
+    /// The number of explicit inputs.
+    pub fn inputs(&self) -> usize {
+        self.plan.input_ids.len() - self.captured.len()
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Runs the graph on its explicit inputs, picking the captured values
+    /// from the inputs of the op.
+    pub fn run(&self, inputs: TVec<Value>, op_inputs: &[Value]) -> TfdResult<TVec<Value>> {
+        if inputs.len() != self.inputs() {
+            bail!("Sub graph expects {} inputs, got {}", self.inputs(), inputs.len())
+        }
+        let mut state = self.plan.state()?;
+        let captured = self.captured.iter().map(|&ix| op_inputs[ix].clone());
+        for (&id, value) in self.plan.input_ids.iter().zip(inputs.into_iter().chain(captured)) {
+            state.values[id] = Some(tvec![value]);
+        }
+        for &n in &self.plan.order {
+            if state.values[n].is_none() {
+                state.compute_one(n)?;
+            }
+        }
+        self.outputs
+            .iter()
+            .map(|o| {
+                let values = state.values[o.node]
+                    .as_ref()
+                    .ok_or_else(|| format!("Sub graph output {:?} not computed", o))?;
+                Ok(values
+                    .get(o.slot)
+                    .ok_or_else(|| format!("Sub graph output {:?} not computed", o))?
+                    .clone())
+            })
+            .collect()
+    }
+}
+
+impl SubGraph {
+    /// Infers the facts of the graph outputs from facts about its explicit
+    /// inputs, picking the captured values from the inputs of the op.
+    ///
+    /// The analyser only tracks the first output of a node, so outputs on
+    /// other slots are left unknown.
+    pub fn output_facts(
+        &self,
+        inputs: TVec<TensorFact>,
+        op_inputs: &[Value],
+    ) -> TfdResult<TVec<TensorFact>> {
+        let model = &self.plan.model;
+        let output = self.outputs.first().ok_or("Sub graph has no output")?;
+        let mut analyser = model.analyser(&model.nodes()[output.node].name)?;
+        let captured = self
+            .captured
+            .iter()
+            .map(|&ix| TensorFact::from(op_inputs[ix].as_tensor().clone()));
+        for (&id, fact) in self.plan.input_ids.iter().zip(inputs.into_iter().chain(captured)) {
+            analyser.hint_by_id(id, &fact)?;
+        }
+        analyser.analyse()?;
+        Ok(self
+            .outputs
+            .iter()
+            .map(|o| match analyser.next_edges[o.node].first() {
+                Some(&edge) if o.slot == 0 => analyser.edges[edge].fact.clone(),
+                _ => TensorFact::default(),
+            })
+            .collect())
+    }
+}
+
+/// Reads a condition, a single boolean.
+fn condition(value: &Value) -> TfdResult<bool> {
+    let cond = value.to_array_view::<bool>()?;
+    if cond.len() != 1 {
+        bail!("Expected a single boolean as condition, got {:?}", cond.shape())
+    }
+    Ok(cond.iter().next().cloned().unwrap())
+}
+
+fn stack_t<T: Datum>(values: &[Value], axis: usize) -> TfdResult<Value> {
+    let views = values
+        .iter()
+        .map(|v| Ok(v.to_array_view::<T>()?.insert_axis(Axis(axis))))
+        .collect::<TfdResult<Vec<_>>>()?;
+    Ok(::ops::array::stack(Axis(axis), &views)?.into())
+}
+
+/// Stacks the values produced by the iterations along a new axis.
+fn stack(values: &[Value], axis: usize) -> TfdResult<Value> {
+    if values.is_empty() {
+        bail!("Can not stack the outputs of zero iterations")
+    }
+    dispatch_datum!(stack_t(values[0].datum_type())(values, axis))
+}
+
+fn empty_t<T: Datum>(shape: &[usize]) -> TfdResult<Value> {
+    Ok(ArrayD::<T>::default(shape).into())
+}
+
+/// The values stacked over zero iterations: an empty tensor of the type and
+/// shape inferred for the value of one iteration, with a 0-length axis.
+fn empty(fact: &TensorFact, axis: i64) -> TfdResult<Value> {
+    let dt = fact.datum_type.concretize();
+    let shape = fact.shape.concretize().and_then(|shape| {
+        shape
+            .iter()
+            .map(|d| d.to_integer().ok().map(|d| d as usize))
+            .collect::<Option<Vec<usize>>>()
+    });
+    let (dt, mut shape) = match (dt, shape) {
+        (Some(dt), Some(shape)) => (dt, shape),
+        _ => bail!("Can not infer the outputs of zero iterations from {:?}", fact),
+    };
+    let axis = resolve_axis(axis, shape.len() + 1)?;
+    shape.insert(axis, 0);
+    dispatch_datum!(empty_t(dt)(&shape))
+}
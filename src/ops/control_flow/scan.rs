@@ -0,0 +1,128 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ops::prelude::*;
+
+use super::{empty, stack, SubGraph};
+use ops::array::resolve_axis;
+
+/// Runs a graph on the successive slices of its scanned inputs, carrying a
+/// state from one iteration to the next.
+///
+/// The op inputs are the initial values of the state, then the scanned
+/// inputs. The body takes the state and one slice of each scanned input,
+/// and produces the new state and one slice of each scanned output. The op
+/// outputs the final state, then the scanned outputs.
+///
+/// Each scanned input or output has an axis, possibly negative, and a
+/// direction, reversed when true.
+#[derive(Debug, Clone, new)]
+pub struct Scan {
+    body: SubGraph,
+    state: usize,
+    input_axes: Vec<(i64, bool)>,
+    output_axes: Vec<(i64, bool)>,
+}
+
+impl Scan {
+    fn slice_t<T: Datum>(input: &Value, axis: usize, ix: usize) -> TfdResult<Value> {
+        Ok(input
+            .to_array_view::<T>()?
+            .index_axis(Axis(axis), ix)
+            .to_owned()
+            .into())
+    }
+}
+
+impl Op for Scan {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let scanned = self.input_axes.len();
+        if inputs.len() < self.state + scanned {
+            bail!("Scan expects {} inputs, got {}", self.state + scanned, inputs.len())
+        }
+        let scanned_outputs = self.output_axes.len();
+        if self.body.inputs() != self.state + scanned
+            || self.body.outputs() != self.state + scanned_outputs
+        {
+            bail!("Scan body does not match its state and scanned values")
+        }
+        let mut len = None;
+        let mut input_axes = vec![];
+        for (input, &(axis, reverse)) in inputs[self.state..].iter().zip(&self.input_axes) {
+            let axis = resolve_axis(axis, input.shape().len())?;
+            input_axes.push((axis, reverse));
+            let dim = input.shape()[axis];
+            if len.unwrap_or(dim) != dim {
+                bail!("Scanned inputs have different lengths")
+            }
+            len = Some(dim);
+        }
+        let len = len.ok_or("Scan expects at least one scanned input")?;
+        let mut state: TVec<Value> = inputs[..self.state].iter().cloned().collect();
+        if len == 0 {
+            // The body never runs: the scanned outputs are built from what
+            // it would produce on the initial state and a slice of the inputs.
+            let mut body_inputs: TVec<TensorFact> = state
+                .iter()
+                .map(|v| TensorFact::from(v.as_tensor().clone()))
+                .collect();
+            for (input, &(axis, _)) in inputs[self.state..].iter().zip(&input_axes) {
+                let mut shape = input.shape().to_vec();
+                shape.remove(axis);
+                body_inputs.push(TensorFact::dt_shape(input.datum_type(), shape));
+            }
+            let facts = self.body.output_facts(body_inputs, &inputs)?;
+            for (fact, &(axis, _)) in facts[self.state..].iter().zip(&self.output_axes) {
+                state.push(empty(fact, axis)?);
+            }
+            return Ok(state);
+        }
+        let mut scans: Vec<Vec<Value>> = vec![vec![]; scanned_outputs];
+        for i in 0..len {
+            let mut body_inputs = state;
+            for (input, &(axis, reverse)) in inputs[self.state..].iter().zip(&input_axes) {
+                let ix = if reverse { len - 1 - i } else { i };
+                body_inputs.push(dispatch_datum!(Self::slice_t(input.datum_type())(
+                    input, axis, ix
+                ))?);
+            }
+            let mut outputs = self.body.run(body_inputs, &inputs)?.into_iter();
+            state = outputs.by_ref().take(self.state).collect();
+            for (scan, value) in scans.iter_mut().zip(outputs) {
+                scan.push(value);
+            }
+        }
+        for (mut scan, &(axis, reverse)) in scans.into_iter().zip(&self.output_axes) {
+            if reverse {
+                scan.reverse();
+            }
+            let rank = scan.first().map(|v| v.shape().len()).unwrap_or(0);
+            state.push(stack(&scan, resolve_axis(axis, rank + 1)?)?);
+        }
+        Ok(state)
+    }
+
+    // The analyser only tracks the first output of a node.
+    fn infer_and_propagate(
+        &self,
+        inputs: TVec<TensorFact>,
+        outputs: TVec<TensorFact>,
+    ) -> TfdResult<(TVec<TensorFact>, TVec<TensorFact>)> {
+        self.infer(inputs, outputs)
+    }
+}
+
+impl InferenceRulesOp for Scan {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        if self.state > 0 {
+            solver
+                .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+                .equals(&inputs[0].shape, &outputs[0].shape);
+        }
+    }
+}
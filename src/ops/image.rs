@@ -1,77 +1,594 @@
+//! Image decoding and resizing.
+use analyser::rules::prelude::*;
 use ndarray::prelude::*;
+use ops::prelude::*;
 
-use super::{Input, Op};
-use {Result, Tensor};
+/// Decodes a JPEG or PNG image, given as a string scalar, to a `u8` tensor
+/// of shape (height, width, channels).
+#[derive(Debug, Clone, new)]
+pub struct DecodeImage {
+    /// The number of channels to output, 0 for the ones of the image.
+    channels: usize,
+}
+
+impl DecodeImage {
+    pub fn decode_one(&self, input: &[u8]) -> TfdResult<Array3<u8>> {
+        use image::{ColorType, GenericImageView};
+        let image = ::image::load_from_memory(input)?;
+        let (width, height) = image.dimensions();
+        let channels = match (self.channels, image.color()) {
+            (0, ColorType::Gray(_)) => 1,
+            (0, ColorType::GrayA(_)) => 2,
+            (0, ColorType::RGBA(_)) | (0, ColorType::BGRA(_)) => 4,
+            (0, _) => 3,
+            (c, _) => c,
+        };
+        let pixels = match channels {
+            1 => image.to_luma().into_raw(),
+            2 => image.to_luma_alpha().into_raw(),
+            3 => image.to_rgb().into_raw(),
+            4 => image.to_rgba().into_raw(),
+            c => bail!("Can not decode an image to {} channels", c),
+        };
+        Ok(Array3::from_shape_vec(
+            (height as usize, width as usize, channels),
+            pixels,
+        )?)
+    }
+}
 
-#[derive(Debug)]
-pub struct DecodeJpeg {}
+impl Op for DecodeImage {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let input = input.to_array_view::<Vec<u8>>()?;
+        if input.len() != 1 {
+            bail!("DecodeImage expects a single image, got {:?}", input.shape())
+        }
+        let image = self.decode_one(input.iter().next().unwrap())?;
+        Ok(tvec![image.into_dyn().into()])
+    }
+}
 
-impl DecodeJpeg {
-    pub fn build(_pb: &::tfpb::node_def::NodeDef) -> TfdResult<DecodeJpeg> {
-        Ok(DecodeJpeg {})
+impl InferenceRulesOp for DecodeImage {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, DatumType::String)
+            .equals(&inputs[0].rank, 0)
+            .equals(&outputs[0].datum_type, DatumType::U8)
+            .equals(&outputs[0].rank, 3);
+        if self.channels > 0 {
+            solver.equals(&outputs[0].shape[2], self.channels.to_dim());
+        }
     }
 }
 
-pub fn decode_one(input: &[u8]) -> TfdResult<Array3<u8>> {
-    use image::GenericImage;
-    let image = ::image::load_from_memory(input)?;
-    let dim = image.dimensions();
-    Ok(Array1::from_vec(image.raw_pixels()).into_shape((dim.0 as usize, dim.1 as usize, 3))?)
+/// How output coordinates map to input coordinates along a resized axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoordinateTransform {
+    /// `x_out / scale`.
+    Asymmetric,
+    /// `(x_out + 0.5) / scale - 0.5`, matching the pixel centers.
+    HalfPixel,
+    /// Matching the first and last pixels of both sides.
+    AlignCorners,
 }
 
-impl Op for DecodeJpeg {
-    fn eval(&self, mut inputs: Vec<Input>) -> TfdResult<Vec<Input>> {
-        let m_input = args_1!(inputs);
-        let input = m_input.as_u8s().ok_or("Expected a string")?;
-        let image = decode_one(input.as_slice().unwrap())?;
-        Ok(vec![Tensor::U8(image.into_dyn()).into()])
+impl CoordinateTransform {
+    fn source(&self, x: usize, scale: f32, input: usize, output: usize) -> f32 {
+        match self {
+            CoordinateTransform::Asymmetric => x as f32 / scale,
+            CoordinateTransform::HalfPixel => (x as f32 + 0.5) / scale - 0.5,
+            CoordinateTransform::AlignCorners if output > 1 => {
+                x as f32 * (input - 1) as f32 / (output - 1) as f32
+            }
+            CoordinateTransform::AlignCorners => 0.0,
+        }
     }
 }
 
-#[derive(Debug)]
-pub struct ResizeBilinear {}
+/// How nearest neighbor resizing picks a pixel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    RoundPreferFloor,
+    RoundPreferCeil,
+}
 
-impl ResizeBilinear {
-    pub fn build(_pb: &::tfpb::node_def::NodeDef) -> TfdResult<ResizeBilinear> {
-        Ok(ResizeBilinear {})
+impl Rounding {
+    fn round(&self, x: f32) -> f32 {
+        match self {
+            Rounding::Floor => x.floor(),
+            Rounding::Ceil => x.ceil(),
+            Rounding::RoundPreferFloor => (x - 0.5).ceil(),
+            Rounding::RoundPreferCeil => (x + 0.5).floor(),
+        }
     }
 }
 
-impl Op for ResizeBilinear {
-    fn eval(&self, mut inputs: Vec<Input>) -> TfdResult<Vec<Input>> {
-        use std::cmp::min;
-        let (m_images, m_sizes) = args_2!(inputs);
-        let images = m_images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Nearest(Rounding),
+    /// Linear along each resized axis, always computed and output as `f32`.
+    Linear,
+}
+
+/// Where a resize finds its output shape.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResizeSize {
+    /// An input holds the output sizes of the given axes, or of all of them.
+    Sizes {
+        input: usize,
+        axes: Option<Vec<usize>>,
+    },
+    /// An input holds a scale for each axis.
+    Scales { input: usize },
+    /// A scale for each axis.
+    ConstScales(Vec<f32>),
+}
+
+/// Resizes its first input along some axes.
+#[derive(Debug, Clone, new)]
+pub struct Resize {
+    interpolation: Interpolation,
+    transform: CoordinateTransform,
+    size: ResizeSize,
+}
+
+/// For each output coordinate along an axis, the two input coordinates to
+/// interpolate and the weight of the second one.
+type AxisTable = Vec<(usize, usize, f32)>;
+
+impl Resize {
+    /// The output size and scale of each axis.
+    fn output_shape(&self, inputs: &[Value]) -> TfdResult<Vec<(usize, f32)>> {
+        let shape = inputs[0].shape();
+        let input = |ix: usize| {
+            inputs
+                .get(ix)
+                .ok_or_else(|| format!("Resize expects an input #{}", ix))
+        };
+        let scales = match &self.size {
+            ResizeSize::Sizes { input: ix, axes } => {
+                let sizes = ::ops::array::to_i64s(input(*ix)?)?;
+                let axes: Vec<usize> = match axes {
+                    Some(axes) => axes.clone(),
+                    None => (0..shape.len()).collect(),
+                };
+                if sizes.len() != axes.len() {
+                    bail!("Resize expects {} sizes, got {:?}", axes.len(), sizes)
+                }
+                if let Some(axis) = axes.iter().find(|&&axis| axis >= shape.len()) {
+                    bail!("Resize can not resize axis {} of a tensor of shape {:?}", axis, shape)
+                }
+                let mut output: Vec<(usize, f32)> = shape.iter().map(|&d| (d, 1.0)).collect();
+                for (&axis, &size) in axes.iter().zip(sizes.iter()) {
+                    if size < 0 || (size > 0 && shape[axis] == 0) {
+                        bail!("Resize can not resize axis {} of length {} to {}", axis, shape[axis], size)
+                    }
+                    let size = size as usize;
+                    output[axis] = (size, size as f32 / shape[axis] as f32);
+                }
+                return Ok(output);
+            }
+            ResizeSize::Scales { input: ix } => input(*ix)?.to_array_view::<f32>()?.iter().cloned().collect(),
+            ResizeSize::ConstScales(scales) => scales.clone(),
+        };
+        if scales.len() != shape.len() {
+            bail!("Resize expects {} scales, got {:?}", shape.len(), scales)
+        }
+        if scales.iter().any(|s| !s.is_finite() || *s < 0.0) {
+            bail!("Resize expects positive scales, got {:?}", scales)
+        }
+        Ok(shape
+            .iter()
+            .zip(scales)
+            .map(|(&d, s)| ((d as f32 * s).floor() as usize, s))
+            .collect())
+    }
+
+    fn table(&self, input: usize, output: usize, scale: f32) -> AxisTable {
+        (0..output)
+            .map(|x| {
+                let source = self.transform.source(x, scale, input, output);
+                let source = source.max(0.0).min((input - 1) as f32);
+                match self.interpolation {
+                    Interpolation::Nearest(rounding) => {
+                        let x = (rounding.round(source) as usize).min(input - 1);
+                        (x, x, 0.0)
+                    }
+                    Interpolation::Linear => {
+                        let low = source.floor() as usize;
+                        (low, (low + 1).min(input - 1), source - low as f32)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+fn nearest<T: Datum>(input: &Value, tables: &[AxisTable]) -> TfdResult<Value> {
+    let input = input.to_array_view::<T>()?;
+    let shape: Vec<usize> = tables.iter().map(|t| t.len()).collect();
+    let mut coords = vec![0; shape.len()];
+    let output = ArrayD::from_shape_fn(shape, |ix| {
+        for (axis, table) in tables.iter().enumerate() {
+            coords[axis] = table[ix[axis]].0;
+        }
+        input[&*coords].clone()
+    });
+    Ok(output.into())
+}
+
+/// Reads an image, in any numeric type, as `f32`s.
+fn to_f32s(tensor: &Tensor) -> TfdResult<ArrayD<f32>> {
+    Ok(match tensor.datum_type() {
+        DatumType::U8 => tensor.to_array_view::<u8>()?.mapv(|x| x as f32),
+        DatumType::U16 => tensor.to_array_view::<u16>()?.mapv(|x| x as f32),
+        DatumType::I8 => tensor.to_array_view::<i8>()?.mapv(|x| x as f32),
+        DatumType::I16 => tensor.to_array_view::<i16>()?.mapv(|x| x as f32),
+        DatumType::I32 => tensor.to_array_view::<i32>()?.mapv(|x| x as f32),
+        DatumType::I64 => tensor.to_array_view::<i64>()?.mapv(|x| x as f32),
+        DatumType::F64 => tensor.to_array_view::<f64>()?.mapv(|x| x as f32),
+        _ => tensor.cast_to_array::<f32>()?.into_owned(),
+    })
+}
+
+fn linear(input: &Value, tables: &[AxisTable]) -> TfdResult<Value> {
+    let input = to_f32s(input)?;
+    let shape: Vec<usize> = tables.iter().map(|t| t.len()).collect();
+    // Only the axes actually moving need interpolating.
+    let resized: Vec<usize> = (0..tables.len())
+        .filter(|&axis| {
+            tables[axis].len() != input.shape()[axis]
+                || tables[axis].iter().enumerate().any(|(x, t)| t.0 != x || t.2 != 0.0)
+        })
+        .collect();
+    let mut coords = vec![0; shape.len()];
+    let output = ArrayD::from_shape_fn(shape, |ix| {
+        for (axis, table) in tables.iter().enumerate() {
+            coords[axis] = table[ix[axis]].0;
+        }
+        let mut value = 0.0;
+        for corner in 0..(1usize << resized.len()) {
+            let mut weight = 1.0;
+            for (bit, &axis) in resized.iter().enumerate() {
+                let (low, high, frac) = tables[axis][ix[axis]];
+                if corner & (1 << bit) == 0 {
+                    coords[axis] = low;
+                    weight *= 1.0 - frac;
+                } else {
+                    coords[axis] = high;
+                    weight *= frac;
+                }
+            }
+            if weight != 0.0 {
+                value += weight * input[&*coords];
+            }
+        }
+        value
+    });
+    Ok(output.into())
+}
+
+impl Op for Resize {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let shape = self.output_shape(&inputs)?;
+        let input = &inputs[0];
+        let tables: Vec<AxisTable> = input
+            .shape()
+            .iter()
+            .zip(shape)
+            .map(|(&d, (size, scale))| self.table(d, size, scale))
+            .collect();
+        let output = match self.interpolation {
+            Interpolation::Nearest(_) => dispatch_datum!(nearest(input.datum_type())(input, &tables))?,
+            Interpolation::Linear => linear(input, &tables)?,
+        };
+        Ok(tvec![output])
+    }
+}
+
+impl InferenceRulesOp for Resize {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].rank, &outputs[0].rank);
+        match self.interpolation {
+            Interpolation::Nearest(_) => {
+                solver.equals(&inputs[0].datum_type, &outputs[0].datum_type);
+            }
+            Interpolation::Linear => {
+                solver.equals(&outputs[0].datum_type, DatumType::F32);
+            }
+        }
+        if let ResizeSize::Sizes {
+            input,
+            axes: Some(ref axes),
+        } = self.size
+        {
+            solver.given(&inputs[0].rank, move |solver, rank| {
+                for axis in 0..(rank as usize) {
+                    if !axes.contains(&axis) {
+                        solver.equals(&inputs[0].shape[axis], &outputs[0].shape[axis]);
+                    }
+                }
+            });
+            solver.given(&inputs[input].value, move |solver, sizes: Tensor| {
+                if let Ok(sizes) = ::ops::array::to_i64s(&sizes) {
+                    for (&axis, &size) in axes.iter().zip(sizes.iter()) {
+                        solver.equals(&outputs[0].shape[axis], size.to_dim());
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Crops boxes from a batch of NHWC images, resizing them to a common size.
+///
+/// The inputs are the images, the boxes as normalized `[y1, x1, y2, x2]`, the
+/// image index of each box, and the output (height, width). Pixels falling
+/// outside of the image take `extrapolation_value`.
+#[derive(Debug, Clone, new)]
+pub struct CropAndResize {
+    /// Only `Linear` and `Nearest(RoundPreferCeil)` are used by Tensorflow.
+    interpolation: Interpolation,
+    extrapolation_value: f32,
+}
+
+impl CropAndResize {
+    /// The input coordinate of an output one, given the box edges.
+    fn source(start: f32, end: f32, x: usize, input: usize, output: usize) -> f32 {
+        let scale = (input - 1) as f32;
+        if output > 1 {
+            start * scale + x as f32 * (end - start) * scale / (output - 1) as f32
+        } else {
+            0.5 * (start + end) * scale
+        }
+    }
+}
+
+impl Op for CropAndResize {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let (images, boxes, indices, size) = args_4!(inputs);
+        let images = to_f32s(&images)?.into_dimensionality::<Ix4>()?;
+        let boxes = boxes.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let indices = ::ops::array::to_i64s(&indices)?;
+        let size = ::ops::array::to_i64s(&size)?;
+        if boxes.shape()[1] != 4 || indices.len() != boxes.shape()[0] || size.len() != 2 {
+            bail!(
+                "CropAndResize got inconsistent boxes {:?}, indices {:?} and size {:?}",
+                boxes.shape(),
+                indices.shape(),
+                size.shape()
+            )
+        }
+        let (_, height, width, depth) = images.dim();
+        if height == 0 || width == 0 {
+            bail!("CropAndResize got an empty image, {:?}", images.shape())
+        }
+        if size[0] <= 0 || size[1] <= 0 {
+            bail!("CropAndResize expects a positive crop size, got {:?}", size)
+        }
+        let (crop_height, crop_width) = (size[0] as usize, size[1] as usize);
+        let mut output = Array4::from_elem(
+            (boxes.shape()[0], crop_height, crop_width, depth),
+            self.extrapolation_value,
+        );
+        for (b, mut crop) in output.outer_iter_mut().enumerate() {
+            let image = indices[b];
+            if image < 0 || image as usize >= images.shape()[0] {
+                bail!("CropAndResize box {} uses image {}, out of the batch", b, image)
+            }
+            let image = images.index_axis(Axis(0), image as usize);
+            for y in 0..crop_height {
+                let in_y = Self::source(boxes[(b, 0)], boxes[(b, 2)], y, height, crop_height);
+                if in_y < 0.0 || in_y > (height - 1) as f32 {
+                    continue;
+                }
+                for x in 0..crop_width {
+                    let in_x = Self::source(boxes[(b, 1)], boxes[(b, 3)], x, width, crop_width);
+                    if in_x < 0.0 || in_x > (width - 1) as f32 {
+                        continue;
+                    }
+                    for c in 0..depth {
+                        crop[(y, x, c)] = match self.interpolation {
+                            Interpolation::Nearest(rounding) => image[(
+                                rounding.round(in_y) as usize,
+                                rounding.round(in_x) as usize,
+                                c,
+                            )],
+                            Interpolation::Linear => {
+                                let (top, left) = (in_y.floor() as usize, in_x.floor() as usize);
+                                let (bottom, right) = (in_y.ceil() as usize, in_x.ceil() as usize);
+                                let (dy, dx) = (in_y - top as f32, in_x - left as f32);
+                                let top_value = image[(top, left, c)] * (1.0 - dx)
+                                    + image[(top, right, c)] * dx;
+                                let bottom_value = image[(bottom, left, c)] * (1.0 - dx)
+                                    + image[(bottom, right, c)] * dx;
+                                top_value * (1.0 - dy) + bottom_value * dy
+                            }
+                        };
+                    }
+                }
+            }
+        }
+        Ok(tvec![output.into_dyn().into()])
+    }
+}
+
+impl InferenceRulesOp for CropAndResize {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 4)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].rank, 4)
+            .equals(&inputs[1].datum_type, DatumType::F32)
+            .equals(&inputs[1].rank, 2)
+            .equals(&outputs[0].datum_type, DatumType::F32)
+            .equals(&outputs[0].rank, 4)
+            .equals(&outputs[0].shape[0], &inputs[1].shape[0])
+            .equals(&outputs[0].shape[3], &inputs[0].shape[3])
+            .given(&inputs[3].value, move |solver, size: Tensor| {
+                if let Ok(size) = ::ops::array::to_i64s(&size) {
+                    if size.len() == 2 {
+                        solver.equals(&outputs[0].shape[1], size[0].to_dim());
+                        solver.equals(&outputs[0].shape[2], size[1].to_dim());
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resize(interpolation: Interpolation, transform: CoordinateTransform, size: &[i32]) -> Tensor {
+        let input = Tensor::f32s(&[1, 2, 2, 1], &[0.0, 1.0, 2.0, 3.0]).unwrap();
+        let size = Tensor::i32s(&[2], size).unwrap();
+        let op = Resize::new(
+            interpolation,
+            transform,
+            ResizeSize::Sizes {
+                input: 1,
+                axes: Some(vec![1, 2]),
+            },
+        );
+        op.eval(tvec![input.into(), size.into()])
+            .unwrap()
+            .remove(0)
             .into_tensor()
-            .take_f32s()
-            .ok_or("Expect input #0 to be images")?;
-        let sizes = m_sizes.as_i32s().ok_or("Expect input #1 to be sizes")?;
-        let batches = images.shape()[0];
-        let old_height = images.shape()[1];
-        let old_width = images.shape()[2];
-        let channels = images.shape()[3];
-        let images = images.into_shape((batches, old_height, old_width, channels))?;
-        let new_height = sizes[0] as usize;
-        let new_width = sizes[1] as usize;
-        let new_shape = (batches, new_height, new_width, channels);
-        let result = Array4::from_shape_fn(new_shape, |(b, y, x, c)| {
-            let proj_x = old_width as f32 * x as f32 / new_width as f32;
-            let proj_y = old_height as f32 * y as f32 / new_width as f32;
-            let old_x = proj_x as usize;
-            let old_y = proj_y as usize;
-            let q11 = images[(b, old_y, old_x, c)];
-            let q12 = images[(b, min(old_y + 1, old_height - 1), old_x, c)];
-            let q21 = images[(b, old_y, min(old_x + 1, old_width - 1), c)];
-            let q22 = images[(
-                b,
-                min(old_y + 1, old_height - 1),
-                min(old_x + 1, old_width - 1),
-                c,
-            )];
-            let dx = proj_x - old_x as f32;
-            let dy = proj_y - old_y as f32;
-            (1.0 - dy) * ((1.0 - dx) * q11 + dx * q21) + dy * ((1.0 - dx) * q12 + dx * q22)
-        });
-        Ok(vec![Tensor::F32(result.into_dyn()).into()])
+    }
+
+    #[test]
+    fn resize_bilinear() {
+        assert_eq!(
+            resize(Interpolation::Linear, CoordinateTransform::Asymmetric, &[1, 4]),
+            Tensor::f32s(&[1, 1, 4, 1], &[0.0, 0.5, 1.0, 1.0]).unwrap()
+        );
+        assert_eq!(
+            resize(Interpolation::Linear, CoordinateTransform::AlignCorners, &[3, 1]),
+            Tensor::f32s(&[1, 3, 1, 1], &[0.0, 1.0, 2.0]).unwrap()
+        );
+        assert_eq!(
+            resize(Interpolation::Linear, CoordinateTransform::HalfPixel, &[1, 4]),
+            Tensor::f32s(&[1, 1, 4, 1], &[1.0, 1.25, 1.75, 2.0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn resize_nearest() {
+        assert_eq!(
+            resize(
+                Interpolation::Nearest(Rounding::Floor),
+                CoordinateTransform::Asymmetric,
+                &[4, 1]
+            ),
+            Tensor::f32s(&[1, 4, 1, 1], &[0.0, 0.0, 2.0, 2.0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_png() {
+        use image::png::PNGEncoder;
+        use image::ColorType;
+        let pixels: Vec<u8> = (0..12).collect();
+        let mut png = vec![];
+        PNGEncoder::new(&mut png)
+            .encode(&pixels, 2, 2, ColorType::RGB(8))
+            .unwrap();
+        let input = Tensor::from(arr0(png));
+        let output = DecodeImage::new(0)
+            .eval(tvec![input.into()])
+            .unwrap()
+            .remove(0)
+            .into_tensor();
+        assert_eq!(output, Tensor::from(Array::from_shape_vec((2, 2, 3), pixels).unwrap()));
+    }
+
+    #[test]
+    fn crop_and_resize() {
+        let images = Tensor::f32s(&[1, 3, 3, 1], &[0., 1., 2., 3., 4., 5., 6., 7., 8.]).unwrap();
+        let boxes = Tensor::f32s(&[2, 4], &[0.0, 0.0, 0.5, 0.5, 0.5, 0.5, 1.5, 1.0]).unwrap();
+        let indices = Tensor::i32s(&[2], &[0, 0]).unwrap();
+        let size = Tensor::i32s(&[2], &[2, 2]).unwrap();
+        let output = CropAndResize::new(Interpolation::Linear, -1.0)
+            .eval(tvec![images.into(), boxes.into(), indices.into(), size.into()])
+            .unwrap()
+            .remove(0)
+            .into_tensor();
+        assert_eq!(
+            output,
+            Tensor::f32s(&[2, 2, 2, 1], &[0., 1., 3., 4., 4., 5., -1., -1.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn resize_invalid_sizes() {
+        let op = Resize::new(
+            Interpolation::Linear,
+            CoordinateTransform::AlignCorners,
+            ResizeSize::Sizes { input: 1, axes: None },
+        );
+        let empty = Tensor::f32s(&[0, 2], &[]).unwrap();
+        let input = Tensor::f32s(&[1, 2], &[0.0, 1.0]).unwrap();
+        let sizes = |sizes: &[i32]| Tensor::i32s(&[2], sizes).unwrap().into();
+        assert!(op.eval(tvec![empty.into(), sizes(&[2, 2])]).is_err());
+        assert!(op.eval(tvec![input.into(), sizes(&[1, -2])]).is_err());
+        let op = Resize::new(
+            Interpolation::Linear,
+            CoordinateTransform::AlignCorners,
+            ResizeSize::Sizes { input: 1, axes: Some(vec![1, 2]) },
+        );
+        let rank_2 = Tensor::f32s(&[1, 2], &[0.0, 1.0]).unwrap();
+        assert!(op.eval(tvec![rank_2.into(), sizes(&[2, 2])]).is_err());
+    }
+
+    #[test]
+    fn resize_empty_batch() {
+        let input = Tensor::f32s(&[0, 2, 2, 1], &[]).unwrap();
+        let size = Tensor::i32s(&[2], &[3, 3]).unwrap();
+        let op = Resize::new(
+            Interpolation::Linear,
+            CoordinateTransform::AlignCorners,
+            ResizeSize::Sizes { input: 1, axes: Some(vec![1, 2]) },
+        );
+        let output = op.eval(tvec![input.into(), size.into()]).unwrap();
+        assert_eq!(output[0].shape(), &[0, 3, 3, 1]);
+    }
+
+    #[test]
+    fn crop_and_resize_invalid() {
+        let crop = |images: Tensor, size: &[i32]| {
+            let boxes = Tensor::f32s(&[1, 4], &[0.0, 0.0, 1.0, 1.0]).unwrap();
+            let indices = Tensor::i32s(&[1], &[0]).unwrap();
+            let size = Tensor::i32s(&[2], size).unwrap();
+            CropAndResize::new(Interpolation::Linear, 0.0)
+                .eval(tvec![images.into(), boxes.into(), indices.into(), size.into()])
+        };
+        assert!(crop(Tensor::f32s(&[1, 0, 2, 1], &[]).unwrap(), &[2, 2]).is_err());
+        assert!(crop(Tensor::f32s(&[1, 1, 1, 1], &[0.0]).unwrap(), &[-1, 2]).is_err());
     }
 }
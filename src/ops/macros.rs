@@ -1,4 +1,12 @@
 macro_rules! element_map {
+    ($Name:ident, [$($type:ty),*], { $($param:ident: $ptype:ty),* }, |$op:ident, $x:ident| $expr:expr) => {
+        #[derive(Debug, Clone, new)]
+        pub struct $Name {
+            $(pub $param: $ptype),*
+        }
+
+        element_map!(@impl $Name, $op, match $($type => |$x| $expr),*);
+    };
     ($Name:ident, [$($type:ty),*], $expr:expr) => {
         element_map!($Name, match $($type => $expr),*);
     };
@@ -6,6 +14,9 @@ macro_rules! element_map {
         #[derive(Debug, Clone, new, Default)]
         pub struct $Name($crate::analyser::TypeFact);
 
+        element_map!(@impl $Name, _op, match $($type => $expr),*);
+    };
+    (@impl $Name:ident, $op:ident, match $($type:ty => $expr:expr),*) => {
         impl ::ops::Op for $Name {
             /// Evaluates the operation given the input tensors.
             fn eval(
@@ -13,6 +24,7 @@ macro_rules! element_map {
                 mut inputs: $crate::TVec<$crate::ops::Value>,
             ) -> $crate::TfdResult<$crate::TVec<$crate::ops::Value>> {
                 use $crate::tensor::Datum;
+                let $op = self;
                 let a = args_1!(inputs);
                 let dt = a.datum_type();
                 $(if dt == <$type>::datum_type() {
@@ -20,6 +32,9 @@ macro_rules! element_map {
                     a.mapv_inplace($expr);
                     return Ok(tvec![a.into()])
                 })*
+                if dt == $crate::DatumType::F16 && [$(<$type>::datum_type()),*].contains(&$crate::DatumType::F32) {
+                    return $crate::ops::eval_f16_as_f32(self, tvec![a])
+                }
                 bail!("{} not covering {:?}", stringify!($Name), dt)
             }
 
@@ -94,6 +109,9 @@ macro_rules! element_bin {
                         .apply(|c,&a:&$type,&b:&$type| *c = $expr(a,b));
                     return Ok(tvec![c.into()])
                 })*
+                if dt == $crate::DatumType::F16 && [$(<$type>::datum_type()),*].contains(&$crate::DatumType::F32) {
+                    return $crate::ops::eval_f16_as_f32(self, tvec![a, b])
+                }
                 bail!("{} not covering {:?}", stringify!($Name), dt)
             }
 
@@ -203,6 +221,9 @@ macro_rules! element_nary {
                     });
                     return Ok(tvec![c.into()])
                 })*
+                if dt == DatumType::F16 && [$(<$type>::datum_type()),*].contains(&DatumType::F32) {
+                    return $crate::ops::eval_f16_as_f32(self, inputs)
+                }
                 bail!("{} not covering {:?}", stringify!($Name), dt)
             }
 
@@ -339,6 +360,67 @@ macro_rules! boxed_new {
     } }
 }
 
+/// Same as `boxed_new!`, but instantiates the op for every datum type,
+/// strings included. Meant for ops that only move data around.
+#[allow(unused_macros)]
+#[macro_export]
+macro_rules! boxed_new_any {
+    ($op:tt($dtype:expr)($($arg:expr),*)) => { {
+        use $crate::DatumType;
+        match $dtype {
+            DatumType::Bool => Box::new($op::<bool>::new($($arg),*)) as Box<Op>,
+            DatumType::U8 => Box::new($op::<u8>::new($($arg),*)) as Box<Op>,
+            DatumType::U16 => Box::new($op::<u16>::new($($arg),*)) as Box<Op>,
+            DatumType::U32 => Box::new($op::<u32>::new($($arg),*)) as Box<Op>,
+            DatumType::U64 => Box::new($op::<u64>::new($($arg),*)) as Box<Op>,
+            DatumType::I8 => Box::new($op::<i8>::new($($arg),*)) as Box<Op>,
+            DatumType::I16 => Box::new($op::<i16>::new($($arg),*)) as Box<Op>,
+            DatumType::I32 => Box::new($op::<i32>::new($($arg),*)) as Box<Op>,
+            DatumType::I64 => Box::new($op::<i64>::new($($arg),*)) as Box<Op>,
+            DatumType::F16 => Box::new($op::<$crate::tensor::f16>::new($($arg),*)) as Box<Op>,
+            DatumType::F32 => Box::new($op::<f32>::new($($arg),*)) as Box<Op>,
+            DatumType::F64 => Box::new($op::<f64>::new($($arg),*)) as Box<Op>,
+            DatumType::ComplexF32 => {
+                Box::new($op::<$crate::tensor::Complex<f32>>::new($($arg),*)) as Box<Op>
+            }
+            DatumType::ComplexF64 => {
+                Box::new($op::<$crate::tensor::Complex<f64>>::new($($arg),*)) as Box<Op>
+            }
+            DatumType::TDim => Box::new($op::<$crate::dim::TDim>::new($($arg),*)) as Box<Op>,
+            DatumType::String => Box::new($op::<Vec<u8>>::new($($arg),*)) as Box<Op>,
+        }
+    } }
+}
+
+/// Calls a function generic over the datum type, picking the type from a
+/// `DatumType` at runtime. Meant for ops that only move data around, like
+/// `boxed_new_any!`.
+#[allow(unused_macros)]
+#[macro_export]
+macro_rules! dispatch_datum {
+    ($($path:ident)::*($dt:expr)($($arg:expr),*)) => { {
+        use $crate::DatumType;
+        match $dt {
+            DatumType::Bool => $($path)::*::<bool>($($arg),*),
+            DatumType::U8 => $($path)::*::<u8>($($arg),*),
+            DatumType::U16 => $($path)::*::<u16>($($arg),*),
+            DatumType::U32 => $($path)::*::<u32>($($arg),*),
+            DatumType::U64 => $($path)::*::<u64>($($arg),*),
+            DatumType::I8 => $($path)::*::<i8>($($arg),*),
+            DatumType::I16 => $($path)::*::<i16>($($arg),*),
+            DatumType::I32 => $($path)::*::<i32>($($arg),*),
+            DatumType::I64 => $($path)::*::<i64>($($arg),*),
+            DatumType::F16 => $($path)::*::<$crate::tensor::f16>($($arg),*),
+            DatumType::F32 => $($path)::*::<f32>($($arg),*),
+            DatumType::F64 => $($path)::*::<f64>($($arg),*),
+            DatumType::ComplexF32 => $($path)::*::<$crate::tensor::Complex<f32>>($($arg),*),
+            DatumType::ComplexF64 => $($path)::*::<$crate::tensor::Complex<f64>>($($arg),*),
+            DatumType::TDim => $($path)::*::<$crate::dim::TDim>($($arg),*),
+            DatumType::String => $($path)::*::<Vec<u8>>($($arg),*),
+        }
+    } }
+}
+
 /// Asserts that forward inference results work as expected.
 #[allow(unused_macros)]
 #[macro_export]
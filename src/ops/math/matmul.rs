@@ -0,0 +1,319 @@
+use analyser::rules::prelude::*;
+use ndarray::linalg::general_mat_mul;
+use ndarray::prelude::*;
+use ndarray::LinalgScalar;
+use num::Float;
+use ops::prelude::*;
+
+/// Broadcasts the batch dimensions of two operands, numpy style.
+fn broadcast_batch(a: &[TDim], b: &[TDim]) -> Option<Vec<TDim>> {
+    let rank = a.len().max(b.len());
+    let mut shape = Vec::with_capacity(rank);
+    for i in 0..rank {
+        let a = if i + a.len() >= rank { a[i + a.len() - rank] } else { 1.to_dim() };
+        let b = if i + b.len() >= rank { b[i + b.len() - rank] } else { 1.to_dim() };
+        shape.push(if a == 1.to_dim() {
+            b
+        } else if b == 1.to_dim() || a == b {
+            a
+        } else {
+            return None;
+        });
+    }
+    Some(shape)
+}
+
+/// Computes the output shape of a matrix product from its operand shapes.
+///
+/// Operands of rank 1 are promoted to matrices, and the extra dimension is
+/// removed from the result.
+fn output_shape(
+    a: &[TDim],
+    b: &[TDim],
+    transpose_a: bool,
+    transpose_b: bool,
+) -> TfdResult<Vec<TDim>> {
+    if a.len() == 0 || b.len() == 0 {
+        bail!("Can not multiply scalars as matrices")
+    }
+    let (a_batch, m, ka) = match a.len() {
+        1 => (&a[..0], None, a[0]),
+        r if transpose_a => (&a[..r - 2], Some(a[r - 1]), a[r - 2]),
+        r => (&a[..r - 2], Some(a[r - 2]), a[r - 1]),
+    };
+    let (b_batch, kb, n) = match b.len() {
+        1 => (&b[..0], b[0], None),
+        r if transpose_b => (&b[..r - 2], b[r - 1], Some(b[r - 2])),
+        r => (&b[..r - 2], b[r - 2], Some(b[r - 1])),
+    };
+    if ka != kb {
+        bail!("Inner dimensions mismatch in matrix product: {:?} and {:?}", a, b)
+    }
+    let mut shape = broadcast_batch(a_batch, b_batch)
+        .ok_or_else(|| format!("Can not broadcast batch dimensions of {:?} and {:?}", a, b))?;
+    shape.extend(m);
+    shape.extend(n);
+    Ok(shape)
+}
+
+/// Matrix product, batched over the leading dimensions with broadcasting.
+///
+/// Either operand can be transposed on its last two axes first.
+#[derive(Debug, Clone, Default, new)]
+pub struct MatMul {
+    transpose_a: bool,
+    transpose_b: bool,
+}
+
+impl MatMul {
+    fn eval_t<T: Datum + LinalgScalar>(&self, a: &Tensor, b: &Tensor) -> TfdResult<Tensor> {
+        if a.shape().len() == 0 || b.shape().len() == 0 {
+            bail!("Can not multiply scalars as matrices")
+        }
+        let mut a = a.to_array_view::<T>()?;
+        let mut b = b.to_array_view::<T>()?;
+        let a_vector = a.ndim() == 1;
+        let b_vector = b.ndim() == 1;
+        if a_vector {
+            a = a.insert_axis(Axis(0));
+        } else if self.transpose_a {
+            a.swap_axes(a.ndim() - 2, a.ndim() - 1);
+        }
+        if b_vector {
+            b = b.insert_axis(Axis(1));
+        } else if self.transpose_b {
+            b.swap_axes(b.ndim() - 2, b.ndim() - 1);
+        }
+        let (m, k) = (a.shape()[a.ndim() - 2], a.shape()[a.ndim() - 1]);
+        let n = b.shape()[b.ndim() - 1];
+        if b.shape()[b.ndim() - 2] != k {
+            bail!("Inner dimensions mismatch in matrix product: {:?} and {:?}", a.shape(), b.shape())
+        }
+        let batch = ::broadcast::multi_broadcast(&[
+            &a.shape()[..a.ndim() - 2],
+            &b.shape()[..b.ndim() - 2],
+        ]).ok_or("Could not broadcast batch dimensions")?;
+        let a_shape: Vec<usize> = batch.iter().cloned().chain(vec![m, k]).collect();
+        let b_shape: Vec<usize> = batch.iter().cloned().chain(vec![k, n]).collect();
+        let a = a.broadcast(&*a_shape).ok_or("Could not broadcast first operand")?;
+        let b = b.broadcast(&*b_shape).ok_or("Could not broadcast second operand")?;
+        let c_shape: Vec<usize> = batch.iter().cloned().chain(vec![m, n]).collect();
+        let mut c = ArrayD::<T>::zeros(c_shape);
+        for prefix in ::ndarray::indices(&*batch) {
+            let mut a = a.view();
+            let mut b = b.view();
+            let mut c = c.view_mut();
+            for &i in prefix.slice() {
+                a = a.index_axis_move(Axis(0), i);
+                b = b.index_axis_move(Axis(0), i);
+                c = c.index_axis_move(Axis(0), i);
+            }
+            let a = a.into_dimensionality::<Ix2>()?;
+            let b = b.into_dimensionality::<Ix2>()?;
+            let mut c = c.into_dimensionality::<Ix2>()?;
+            general_mat_mul(T::one(), &a, &b, T::zero(), &mut c);
+        }
+        let mut shape = batch;
+        if !a_vector {
+            shape.push(m);
+        }
+        if !b_vector {
+            shape.push(n);
+        }
+        Ok(c.into_shape(shape)?.into())
+    }
+}
+
+impl Op for MatMul {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let (a, b) = args_2!(inputs);
+        let c = match a.datum_type() {
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, tvec![a, b]),
+            DatumType::F32 => self.eval_t::<f32>(&a, &b)?,
+            DatumType::F64 => self.eval_t::<f64>(&a, &b)?,
+            DatumType::I32 => self.eval_t::<i32>(&a, &b)?,
+            DatumType::I64 => self.eval_t::<i64>(&a, &b)?,
+            dt => bail!("MatMul not covering {:?}", dt),
+        };
+        Ok(tvec![c.into()])
+    }
+}
+
+impl InferenceRulesOp for MatMul {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 2)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &inputs[1].datum_type)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .given(&inputs[0].shape, move |solver, a: Vec<TDim>| {
+                solver.given(&inputs[1].shape, move |solver, b: Vec<TDim>| {
+                    if let Ok(shape) = output_shape(&a, &b, self.transpose_a, self.transpose_b) {
+                        solver.equals(&outputs[0].shape, shape);
+                    }
+                });
+            });
+    }
+}
+
+/// General matrix multiplication: `alpha * A * B + beta * C`.
+///
+/// A and B are matrices, optionally transposed, and the optional C is
+/// broadcast to the shape of the product.
+#[derive(Debug, Clone, new)]
+pub struct Gemm {
+    alpha: f32,
+    beta: f32,
+    transpose_a: bool,
+    transpose_b: bool,
+}
+
+impl Gemm {
+    fn eval_t<T: Datum + LinalgScalar + Float>(
+        &self,
+        a: &Tensor,
+        b: &Tensor,
+        c: Option<&Tensor>,
+    ) -> TfdResult<Tensor> {
+        let mut a = a.to_array_view::<T>()?.into_dimensionality::<Ix2>()?;
+        let mut b = b.to_array_view::<T>()?.into_dimensionality::<Ix2>()?;
+        if self.transpose_a {
+            a = a.reversed_axes();
+        }
+        if self.transpose_b {
+            b = b.reversed_axes();
+        }
+        let shape = (a.rows(), b.cols());
+        let (mut output, beta) = match c {
+            Some(c) => {
+                let c = c.to_array_view::<T>()?;
+                let c = c
+                    .broadcast(vec![shape.0, shape.1])
+                    .ok_or_else(|| format!("Can not broadcast {:?} to {:?}", c.shape(), shape))?
+                    .into_dimensionality::<Ix2>()?
+                    .to_owned();
+                (c, T::from(self.beta).unwrap())
+            }
+            None => (Array2::zeros(shape), T::zero()),
+        };
+        if a.cols() != b.rows() {
+            bail!("Inner dimensions mismatch in Gemm: {:?} and {:?}", a.shape(), b.shape())
+        }
+        general_mat_mul(T::from(self.alpha).unwrap(), &a, &b, beta, &mut output);
+        Ok(output.into_dyn().into())
+    }
+}
+
+impl Op for Gemm {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        if inputs.len() < 2 || inputs.len() > 3 {
+            bail!("Gemm expects 2 or 3 inputs, got {}", inputs.len())
+        }
+        let c = inputs.get(2).map(|c| c.as_tensor());
+        let output = match inputs[0].datum_type() {
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, inputs),
+            DatumType::F32 => self.eval_t::<f32>(&inputs[0], &inputs[1], c)?,
+            DatumType::F64 => self.eval_t::<f64>(&inputs[0], &inputs[1], c)?,
+            dt => bail!("Gemm not covering {:?}", dt),
+        };
+        Ok(tvec![output.into()])
+    }
+}
+
+impl InferenceRulesOp for Gemm {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        let (m, n) = if self.transpose_a { (1, 0) } else { (0, 1) };
+        let (k, n_b) = if self.transpose_b { (1, 0) } else { (0, 1) };
+        solver
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &inputs[1].datum_type)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].rank, 2)
+            .equals(&inputs[1].rank, 2)
+            .equals(&outputs[0].rank, 2)
+            .equals(&inputs[0].shape[n], &inputs[1].shape[k])
+            .equals(&outputs[0].shape[0], &inputs[0].shape[m])
+            .equals(&outputs[0].shape[1], &inputs[1].shape[n_b]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matmul(op: MatMul, a: Tensor, b: Tensor) -> Tensor {
+        op.eval(tvec![a.into(), b.into()]).unwrap().remove(0).into_tensor()
+    }
+
+    #[test]
+    fn matmul_transposed() {
+        let a = Tensor::f32s(&[2, 3], &[1., 2., 3., 4., 5., 6.]).unwrap();
+        let b = Tensor::f32s(&[2, 3], &[1., 0., 0., 0., 1., 0.]).unwrap();
+        assert_eq!(
+            matmul(MatMul::new(false, true), a.clone(), b.clone()),
+            Tensor::f32s(&[2, 2], &[1., 2., 4., 5.]).unwrap()
+        );
+        assert_eq!(
+            matmul(MatMul::new(true, false), a, b),
+            Tensor::f32s(&[3, 3], &[1., 4., 0., 2., 5., 0., 3., 6., 0.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn matmul_broadcast_batch() {
+        let a = Tensor::i32s(&[2, 1, 2], &[1, 2, 3, 4]).unwrap();
+        let b = Tensor::i32s(&[2, 1], &[1, 1]).unwrap();
+        assert_eq!(
+            matmul(MatMul::default(), a, b),
+            Tensor::i32s(&[2, 1, 1], &[3, 7]).unwrap()
+        );
+    }
+
+    #[test]
+    fn matmul_vectors() {
+        let a = Tensor::f64s(&[2], &[1., 2.]).unwrap();
+        let b = Tensor::f64s(&[2, 2], &[1., 2., 3., 4.]).unwrap();
+        assert_eq!(
+            matmul(MatMul::default(), a.clone(), b.clone()),
+            Tensor::f64s(&[2], &[7., 10.]).unwrap()
+        );
+        assert_eq!(matmul(MatMul::default(), a.clone(), a), Tensor::f64s(&[], &[5.]).unwrap());
+        let scalar = Tensor::from(2f64);
+        assert!(MatMul::default().eval(tvec![scalar.into(), b.into()]).is_err());
+    }
+
+    #[test]
+    fn matmul_rules() {
+        let dims = |d: &[i64]| d.iter().map(|d| d.to_dim()).collect::<Vec<_>>();
+        let shape = output_shape(&dims(&[5, 2, 3]), &dims(&[3, 4]), false, false);
+        assert_eq!(shape.unwrap(), dims(&[5, 2, 4]));
+        let shape = output_shape(&dims(&[2, 3]), &dims(&[2, 4]), true, false);
+        assert_eq!(shape.unwrap(), dims(&[3, 4]));
+        assert!(output_shape(&dims(&[2, 3]), &dims(&[2, 4]), false, false).is_err());
+    }
+
+    #[test]
+    fn gemm() {
+        let a = Tensor::f32s(&[2, 1], &[1., 2.]).unwrap();
+        let b = Tensor::f32s(&[1, 2], &[3., 4.]).unwrap();
+        let c = Tensor::f32s(&[1], &[1.]).unwrap();
+        let op = Gemm::new(2., 0.5, true, true);
+        let output = op.eval(tvec![a.into(), b.into(), c.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::f32s(&[1, 1], &[22.5]).unwrap()
+        );
+    }
+}
@@ -1,5 +1,10 @@
 use ops::prelude::*;
 
+mod matmul;
+mod reduce;
+pub use self::matmul::{Gemm, MatMul};
+pub use self::reduce::{ArgMaxMin, Reduce, Reducer};
+
 element_map!(Abs, [f32, i32], |x| x.abs());
 
 element_map!(Exp, [f32, f64], |x| x.exp());
@@ -7,6 +12,7 @@ element_map!(Ln, [f32, f64], |x| x.ln());
 element_map!(Sqrt, [f32, f64], |x| x.sqrt());
 element_map!(Recip, [f32], |x| x.recip());
 element_map!(Rsqrt, [f32], |x| x.sqrt().recip());
+element_map!(Square, [i32, i64, f32, f64], |x| x * x);
 
 element_map!(Ceil, [f32, f64], |x| x.ceil());
 element_map!(Floor, [f32, f64], |x| x.floor());
@@ -18,12 +24,15 @@ element_map!(Acos, [f32, f64], |x| x.acos());
 element_map!(Asin, [f32, f64], |x| x.asin());
 element_map!(Atan, [f32, f64], |x| x.atan());
 
-element_map!(Neg, [i8, i16, i32, i64, f32, f64, TDim], |x| -x);
-element_bin!(Add, [u8, u16, i8, i16, i32, i64, f32, f64, TDim] { |a, b| a + b });
-element_bin!(Sub, [u8, u16, i8, i16, i32, i64, f32, f64, TDim] { |a, b| a - b });
-element_bin!(Mul, [u8, u16, i8, i16, i32, i64, f32, f64, TDim] { |a, b| a * b });
-element_bin!(Div, [u8, u16, i8, i16, i32, i64, f32, f64, TDim] { |a, b| a / b });
-element_bin!(Rem, [u8, u16, i8, i16, i32, i64, f32, f64, TDim] { |a, b| a % b });
+element_map!(Neg, [i8, i16, i32, i64, f32, f64, Complex<f32>, Complex<f64>, TDim], |x| -x);
+element_bin!(Add, [u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, Complex<f32>, Complex<f64>, TDim] { |a, b| a + b });
+element_bin!(Sub, [u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, Complex<f32>, Complex<f64>, TDim] { |a, b| a - b });
+element_bin!(Mul, [u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, Complex<f32>, Complex<f64>, TDim] { |a, b| a * b });
+element_bin!(Div, [u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, Complex<f32>, Complex<f64>, TDim] { |a, b| a / b });
+element_bin!(Rem, [u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, TDim] { |a, b| a % b });
+element_bin!(SquaredDifference, [i32, i64, f32, f64] { |a, b| (a - b) * (a - b) });
+element_bin!(Maximum, [u8, u16, i8, i16, i32, i64, f32, f64] { |a, b| if a < b || is_nan(&b) { b } else { a } });
+element_bin!(Minimum, [u8, u16, i8, i16, i32, i64, f32, f64] { |a, b| if b < a || is_nan(&b) { b } else { a } });
 element_bin!(Pow, match
      f32 => f32 { |a:f32, b| a.powf(b) },
      f64 => f64 { |a:f64, b| a.powf(b) }
@@ -31,6 +40,11 @@ element_bin!(Pow, match
 
 element_map!(Tanh, [f32, f64], |x| x.tanh());
 
+/// NaN is the only value not comparable to itself.
+fn is_nan<T: PartialOrd>(x: &T) -> bool {
+    x.partial_cmp(x).is_none()
+}
+
 fn fcmp<F: ::num::Float>(a:&F,b:&F) -> ::std::cmp::Ordering {
     a.partial_cmp(b).unwrap()
 }
@@ -52,6 +66,7 @@ element_nary!(MeanN, match
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use ndarray::arr2;
     #[test]
     fn mul() {
@@ -65,4 +80,54 @@ mod tests {
         let b = arr2(&[[1., 0.], [0., 0.]]);
         assert_eq!(a.dot(&b), arr2(&[[1., 0.], [3., 0.]]));
     }
+
+    #[test]
+    fn f16_through_f32() {
+        let a = Tensor::f16s(&[2], &[f16::from_f32(1.0), f16::from_f32(4.0)]).unwrap();
+        let sum = Add::default().eval(tvec![a.clone().into(), a.clone().into()]).unwrap();
+        assert_eq!(
+            sum[0].as_tensor(),
+            &Tensor::f16s(&[2], &[f16::from_f32(2.0), f16::from_f32(8.0)]).unwrap()
+        );
+        let sqrt = Sqrt::default().eval(tvec![a.into()]).unwrap();
+        assert_eq!(
+            sqrt[0].as_tensor(),
+            &Tensor::f16s(&[2], &[f16::from_f32(1.0), f16::from_f32(2.0)]).unwrap()
+        );
+    }
+
+    #[test]
+    fn unsigned_and_complex() {
+        let a = Tensor::u64s(&[1], &[1 << 40]).unwrap();
+        let b = Tensor::u8s(&[1], &[2]).unwrap();
+        let prod = Mul::default().eval(tvec![a.into(), b.into()]).unwrap();
+        assert_eq!(prod[0].as_tensor(), &Tensor::u64s(&[1], &[1 << 41]).unwrap());
+        let c = Tensor::complex_f32s(&[1], &[Complex::new(0.0, 1.0)]).unwrap();
+        let square = Mul::default().eval(tvec![c.clone().into(), c.into()]).unwrap();
+        assert_eq!(
+            square[0].as_tensor(),
+            &Tensor::complex_f32s(&[1], &[Complex::new(-1.0, 0.0)]).unwrap()
+        );
+    }
+
+    #[test]
+    fn broadcast_maximum_and_squared_difference() {
+        let a = Tensor::f32s(&[2, 2], &[1.0, 4.0, -2.0, 0.5]).unwrap();
+        let b = Tensor::f32s(&[2], &[2.0, 1.0]).unwrap();
+        let max = Maximum::default().eval(tvec![a.clone().into(), b.clone().into()]).unwrap();
+        assert_eq!(max[0].as_tensor(), &Tensor::f32s(&[2, 2], &[2.0, 4.0, 2.0, 1.0]).unwrap());
+        let diff = SquaredDifference::default().eval(tvec![a.into(), b.into()]).unwrap();
+        assert_eq!(diff[0].as_tensor(), &Tensor::f32s(&[2, 2], &[1.0, 9.0, 16.0, 0.25]).unwrap());
+    }
+
+    #[test]
+    fn maximum_and_minimum_propagate_nan() {
+        let a = || Tensor::f32s(&[3], &[::std::f32::NAN, 1.0, 2.0]).unwrap().into();
+        let b = || Tensor::f32s(&[3], &[1.0, ::std::f32::NAN, 0.0]).unwrap().into();
+        for op in &[Box::new(Maximum::default()) as Box<Op>, Box::new(Minimum::default())] {
+            let result = op.eval(tvec![a(), b()]).unwrap();
+            let result = result[0].to_array_view::<f32>().unwrap();
+            assert!(result[0].is_nan() && result[1].is_nan() && !result[2].is_nan());
+        }
+    }
 }
@@ -0,0 +1,407 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ops::prelude::*;
+
+/// The reduction applied to each lane of a `Reduce` op.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reducer {
+    Sum,
+    Mean,
+    Max,
+    Min,
+    Prod,
+}
+
+/// Values the reductions start from, whether dividing by zero is defined for
+/// the type, and the arithmetic reductions.
+///
+/// Floats start extrema from the infinities, so that empty or infinite lanes
+/// give infinities like TensorFlow. Integers accumulate in 64 bits and
+/// saturate, both while accumulating and when casting back.
+trait Reducible: Datum + PartialOrd + Copy {
+    fn lowest() -> Self;
+    fn highest() -> Self;
+    fn is_float() -> bool;
+    fn sum(lane: ArrayView1<Self>) -> Self;
+    fn prod(lane: ArrayView1<Self>) -> Self;
+    fn mean(lane: ArrayView1<Self>) -> Self;
+}
+
+macro_rules! reducible_float {
+    ($t:ident) => {
+        impl Reducible for $t {
+            fn lowest() -> $t {
+                <$t as ::num::Float>::neg_infinity()
+            }
+            fn highest() -> $t {
+                <$t as ::num::Float>::infinity()
+            }
+            fn is_float() -> bool {
+                true
+            }
+            fn sum(lane: ArrayView1<$t>) -> $t {
+                lane.iter().sum()
+            }
+            fn prod(lane: ArrayView1<$t>) -> $t {
+                lane.iter().product()
+            }
+            fn mean(lane: ArrayView1<$t>) -> $t {
+                Self::sum(lane) / lane.len() as $t
+            }
+        }
+    };
+}
+
+macro_rules! reducible_int {
+    ($t:ident, $acc:ident) => {
+        impl Reducible for $t {
+            fn lowest() -> $t {
+                <$t as ::num::Bounded>::min_value()
+            }
+            fn highest() -> $t {
+                <$t as ::num::Bounded>::max_value()
+            }
+            fn is_float() -> bool {
+                false
+            }
+            fn sum(lane: ArrayView1<$t>) -> $t {
+                let sum = lane.iter().fold(0, |a: $acc, &b| a.saturating_add(b as $acc));
+                ::num::cast(sum).unwrap_or(if sum > 0 { Self::highest() } else { Self::lowest() })
+            }
+            fn prod(lane: ArrayView1<$t>) -> $t {
+                let prod = lane.iter().fold(1, |a: $acc, &b| a.saturating_mul(b as $acc));
+                ::num::cast(prod).unwrap_or(if prod > 0 { Self::highest() } else { Self::lowest() })
+            }
+            fn mean(lane: ArrayView1<$t>) -> $t {
+                let sum = lane.iter().fold(0, |a: $acc, &b| a.saturating_add(b as $acc));
+                // The mean of values of a type is in its range.
+                ::num::cast(sum / lane.len() as $acc).unwrap()
+            }
+        }
+    };
+}
+
+reducible_float!(f32);
+reducible_float!(f64);
+reducible_int!(i8, i64);
+reducible_int!(i16, i64);
+reducible_int!(i32, i64);
+reducible_int!(i64, i64);
+reducible_int!(u8, u64);
+reducible_int!(u16, u64);
+reducible_int!(u32, u64);
+reducible_int!(u64, u64);
+
+impl Reducer {
+    fn reduce<T: Reducible>(&self, lane: ArrayView1<T>) -> T {
+        match *self {
+            Reducer::Sum => T::sum(lane),
+            Reducer::Prod => T::prod(lane),
+            Reducer::Max => lane
+                .iter()
+                .fold(T::lowest(), |a, &b| if b > a { b } else { a }),
+            Reducer::Min => lane
+                .iter()
+                .fold(T::highest(), |a, &b| if b < a { b } else { a }),
+            Reducer::Mean => T::mean(lane),
+        }
+    }
+}
+
+/// Normalizes reduction axes against the input rank.
+///
+/// `None` stands for all the axes, negative axes count from the end. The
+/// result is sorted and deduplicated.
+fn resolve_axes(axes: Option<&[i64]>, rank: usize) -> TfdResult<Vec<usize>> {
+    let mut resolved = match axes {
+        None => (0..rank).collect(),
+        Some(axes) => axes
+            .iter()
+            .map(|&axis| {
+                let fixed = if axis < 0 { axis + rank as i64 } else { axis };
+                if fixed < 0 || fixed >= rank as i64 {
+                    bail!("Invalid axis {} for rank {}", axis, rank)
+                }
+                Ok(fixed as usize)
+            }).collect::<TfdResult<Vec<usize>>>()?,
+    };
+    resolved.sort();
+    resolved.dedup();
+    Ok(resolved)
+}
+
+/// Output shape rules shared by the reductions: the reduced axes are
+/// dropped, or kept with a dimension of one.
+fn reduced_shape_rules<'r, 'p: 'r>(
+    solver: &mut Solver<'r>,
+    input: &'p TensorProxy,
+    output: &'p TensorProxy,
+    axes: Option<Vec<i64>>,
+    keep_dims: bool,
+) {
+    solver.given(&input.rank, move |solver, rank: i64| {
+        let rank = rank as usize;
+        let axes = match resolve_axes(axes.as_ref().map(|a| &**a), rank) {
+            Ok(axes) => axes,
+            Err(_) => return,
+        };
+        let output_rank = if keep_dims { rank } else { rank - axes.len() };
+        solver.equals(&output.rank, output_rank as i64);
+        let mut output_axis = 0;
+        for axis in 0..rank {
+            if !axes.contains(&axis) {
+                solver.equals(&input.shape[axis], &output.shape[output_axis]);
+                output_axis += 1;
+            } else if keep_dims {
+                solver.equals(&output.shape[output_axis], 1.to_dim());
+                output_axis += 1;
+            }
+        }
+    });
+}
+
+/// Reduces its input along some axes.
+#[derive(Debug, Clone, new)]
+pub struct Reduce {
+    reducer: Reducer,
+    axes: Option<Vec<i64>>,
+    keep_dims: bool,
+}
+
+impl Reduce {
+    fn eval_t<T: Reducible>(&self, input: Value) -> TfdResult<Tensor> {
+        let mut array = input.into_array::<T>()?;
+        let full_shape = array.shape().to_vec();
+        let axes = resolve_axes(self.axes.as_ref().map(|a| &**a), full_shape.len())?;
+        if self.reducer == Reducer::Mean
+            && !T::is_float()
+            && axes.iter().any(|&axis| full_shape[axis] == 0)
+        {
+            bail!("Can not compute the integer mean of an empty axis")
+        }
+        for &axis in axes.iter().rev() {
+            array = if array.shape()[axis] == 0 {
+                let mut shape = array.shape().to_vec();
+                shape.remove(axis);
+                let empty: &[T] = &[];
+                ArrayD::from_elem(shape, self.reducer.reduce(empty.into()))
+            } else {
+                array.map_axis(Axis(axis), |lane| self.reducer.reduce(lane))
+            };
+        }
+        if self.keep_dims {
+            let shape: Vec<usize> = full_shape
+                .iter()
+                .enumerate()
+                .map(|(ix, &d)| if axes.contains(&ix) { 1 } else { d })
+                .collect();
+            array = array.into_shape(shape)?;
+        }
+        Ok(array.into())
+    }
+
+    /// Inference rules for an input and output tensor once the axes are
+    /// known, for frontends getting them from an input.
+    pub fn shape_rules<'r, 'p: 'r>(
+        &self,
+        solver: &mut Solver<'r>,
+        input: &'p TensorProxy,
+        output: &'p TensorProxy,
+    ) {
+        reduced_shape_rules(solver, input, output, self.axes.clone(), self.keep_dims);
+    }
+}
+
+impl Op for Reduce {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let output = match input.datum_type() {
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, tvec![input]),
+            DatumType::F32 => self.eval_t::<f32>(input)?,
+            DatumType::F64 => self.eval_t::<f64>(input)?,
+            DatumType::I8 => self.eval_t::<i8>(input)?,
+            DatumType::I16 => self.eval_t::<i16>(input)?,
+            DatumType::I32 => self.eval_t::<i32>(input)?,
+            DatumType::I64 => self.eval_t::<i64>(input)?,
+            DatumType::U8 => self.eval_t::<u8>(input)?,
+            DatumType::U16 => self.eval_t::<u16>(input)?,
+            DatumType::U32 => self.eval_t::<u32>(input)?,
+            DatumType::U64 => self.eval_t::<u64>(input)?,
+            dt => bail!("{:?} reduction not covering {:?}", self.reducer, dt),
+        };
+        Ok(tvec![output.into()])
+    }
+}
+
+impl InferenceRulesOp for Reduce {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type);
+        self.shape_rules(solver, &inputs[0], &outputs[0]);
+    }
+}
+
+/// Finds the index of the greatest (or smallest) value along an axis.
+///
+/// Ties resolve to the first index. The output is always a i64 tensor.
+#[derive(Debug, Clone, new)]
+pub struct ArgMaxMin {
+    max: bool,
+    axis: i64,
+    keep_dims: bool,
+}
+
+impl ArgMaxMin {
+    fn eval_t<T: Datum + PartialOrd>(&self, input: Value) -> TfdResult<Tensor> {
+        let array = input.to_array_view::<T>()?;
+        let axis = resolve_axes(Some(&[self.axis]), array.ndim())?[0];
+        if array.shape()[axis] == 0 {
+            bail!("Can not find the arg {} of an empty axis", if self.max { "max" } else { "min" })
+        }
+        let mut result = array.map_axis(Axis(axis), |lane| {
+            let mut best = 0;
+            for (ix, v) in lane.iter().enumerate() {
+                let better = if self.max { *v > lane[best] } else { *v < lane[best] };
+                if better {
+                    best = ix;
+                }
+            }
+            best as i64
+        });
+        if self.keep_dims {
+            result = result.insert_axis(Axis(axis));
+        }
+        Ok(result.into())
+    }
+
+    /// Inference rules for an input and output tensor once the axis is
+    /// known, for frontends getting it from an input.
+    pub fn shape_rules<'r, 'p: 'r>(
+        &self,
+        solver: &mut Solver<'r>,
+        input: &'p TensorProxy,
+        output: &'p TensorProxy,
+    ) {
+        reduced_shape_rules(solver, input, output, Some(vec![self.axis]), self.keep_dims);
+    }
+}
+
+impl Op for ArgMaxMin {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let output = match input.datum_type() {
+            DatumType::F16 => self.eval_t::<f16>(input)?,
+            DatumType::F32 => self.eval_t::<f32>(input)?,
+            DatumType::F64 => self.eval_t::<f64>(input)?,
+            DatumType::I8 => self.eval_t::<i8>(input)?,
+            DatumType::I16 => self.eval_t::<i16>(input)?,
+            DatumType::I32 => self.eval_t::<i32>(input)?,
+            DatumType::I64 => self.eval_t::<i64>(input)?,
+            DatumType::U8 => self.eval_t::<u8>(input)?,
+            DatumType::U16 => self.eval_t::<u16>(input)?,
+            DatumType::U32 => self.eval_t::<u32>(input)?,
+            DatumType::U64 => self.eval_t::<u64>(input)?,
+            dt => bail!("ArgMaxMin not covering {:?}", dt),
+        };
+        Ok(tvec![output.into()])
+    }
+}
+
+impl InferenceRulesOp for ArgMaxMin {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&outputs[0].datum_type, DatumType::I64);
+        self.shape_rules(solver, &inputs[0], &outputs[0]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(op: &Op, input: Tensor) -> Tensor {
+        op.eval(tvec![input.into()]).unwrap().pop().unwrap().into_tensor()
+    }
+
+    #[test]
+    fn sum_axes() {
+        let input = Tensor::f32s(&[2, 3], &[1., 2., 3., 4., 5., 6.]).unwrap();
+        let op = Reduce::new(Reducer::Sum, Some(vec![1]), false);
+        assert_eq!(run(&op, input.clone()), Tensor::f32s(&[2], &[6., 15.]).unwrap());
+        let op = Reduce::new(Reducer::Sum, Some(vec![-2]), true);
+        assert_eq!(run(&op, input.clone()), Tensor::f32s(&[1, 3], &[5., 7., 9.]).unwrap());
+        let op = Reduce::new(Reducer::Sum, None, false);
+        assert_eq!(run(&op, input), Tensor::from(21f32));
+    }
+
+    #[test]
+    fn max_min_mean_prod() {
+        let input = Tensor::i32s(&[2, 2], &[3, -1, 4, 2]).unwrap();
+        let reduce = |r| run(&Reduce::new(r, Some(vec![0]), false), input.clone());
+        assert_eq!(reduce(Reducer::Max), Tensor::i32s(&[2], &[4, 2]).unwrap());
+        assert_eq!(reduce(Reducer::Min), Tensor::i32s(&[2], &[3, -1]).unwrap());
+        assert_eq!(reduce(Reducer::Mean), Tensor::i32s(&[2], &[3, 0]).unwrap());
+        assert_eq!(reduce(Reducer::Prod), Tensor::i32s(&[2], &[12, -2]).unwrap());
+    }
+
+    #[test]
+    fn empty_axis() {
+        let reduce = |r, input: Tensor| Reduce::new(r, Some(vec![1]), false).eval(tvec![input.into()]);
+        let floats = Tensor::f32s(&[2, 0], &[]).unwrap();
+        let max = reduce(Reducer::Max, floats.clone()).unwrap().pop().unwrap().into_tensor();
+        assert_eq!(max, Tensor::f32s(&[2], &[::std::f32::NEG_INFINITY; 2]).unwrap());
+        let mean = reduce(Reducer::Mean, floats).unwrap().pop().unwrap().into_tensor();
+        assert!(mean.to_array_view::<f32>().unwrap().iter().all(|x| x.is_nan()));
+        assert!(reduce(Reducer::Mean, Tensor::i32s(&[2, 0], &[]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn long_narrow_lanes() {
+        let reduce = |r, input: &Tensor| run(&Reduce::new(r, None, false), input.clone());
+        let bytes = Tensor::u8s(&[300], &[200; 300]).unwrap();
+        assert_eq!(reduce(Reducer::Mean, &bytes), Tensor::from(200u8));
+        assert_eq!(reduce(Reducer::Sum, &bytes), Tensor::from(255u8));
+        assert_eq!(reduce(Reducer::Prod, &bytes), Tensor::from(255u8));
+        let signed = Tensor::i8s(&[300], &[-100; 300]).unwrap();
+        assert_eq!(reduce(Reducer::Mean, &signed), Tensor::from(-100i8));
+        assert_eq!(reduce(Reducer::Sum, &signed), Tensor::from(-128i8));
+    }
+
+    #[test]
+    fn arg_max_min() {
+        let input = Tensor::f32s(&[2, 3], &[1., 5., 5., 4., 0., 6.]).unwrap();
+        let op = ArgMaxMin::new(true, 1, false);
+        assert_eq!(run(&op, input.clone()), Tensor::i64s(&[2], &[1, 2]).unwrap());
+        let op = ArgMaxMin::new(false, 0, true);
+        assert_eq!(run(&op, input), Tensor::i64s(&[1, 3], &[0, 1, 0]).unwrap());
+    }
+
+    #[test]
+    fn reduce_rules() {
+        use analyser::TensorFact;
+        use ops::InferenceOp;
+        let op = Reduce::new(Reducer::Mean, Some(vec![1, -1]), true);
+        let input = TensorFact::dt_shape(DatumType::F32, vec![2, 3, 4]);
+        let (_, output) = op.infer(tvec![input], tvec![TensorFact::default()]).unwrap();
+        assert_eq!(
+            output[0],
+            TensorFact::dt_shape(DatumType::F32, vec![2, 1, 1])
+        );
+    }
+}
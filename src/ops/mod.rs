@@ -16,7 +16,9 @@ use objekt;
 #[macro_use]
 mod macros;
 
-#[cfg(features = "image_ops")]
+pub mod array;
+pub mod control_flow;
+#[cfg(feature = "image_ops")]
 pub mod image;
 pub mod konst;
 pub mod logic;
@@ -33,7 +35,7 @@ pub mod prelude {
     pub use model::TVec;
     pub use std::collections::HashMap;
     pub use std::marker::PhantomData;
-    pub use tensor::{Datum, DatumType, Tensor};
+    pub use tensor::{f16, Complex, Datum, DatumType, Tensor};
     pub use TfdResult;
 }
 
@@ -210,7 +212,7 @@ impl StepValue {
 }
 
 /// A Tensorflow operation.
-pub trait Op: Debug + objekt::Clone + Send + Sync + 'static + InferenceOp {
+pub trait Op: Downcast + Debug + objekt::Clone + Send + Sync + 'static + InferenceOp {
     /// Evaluates the operation given the input tensors.
     fn eval(&self, _inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
         bail!("Unexpected call on op.eval(). {:?}", self)
@@ -293,6 +295,15 @@ pub trait Op: Debug + objekt::Clone + Send + Sync + 'static + InferenceOp {
         None
     }
 
+    /// For ops linear in their first input and taking a kernel as second
+    /// input, the channel axis of the output and the matching axis of the
+    /// kernel: scaling the kernel along the latter scales the output along
+    /// the former, for an output of the given rank. The analyser uses it to
+    /// fold normalizations into kernels.
+    fn kernel_channel_axes(&self, _rank: usize) -> Option<(usize, usize)> {
+        None
+    }
+
     fn rounding_errors(&self) -> bool {
         false
     }
@@ -307,6 +318,31 @@ pub trait InferenceOp {
 }
 
 clone_trait_object!(Op);
+impl_downcast!(Op);
+
+/// Evaluates an op on half precision inputs by running it in single
+/// precision, then converting single precision results back.
+pub fn eval_f16_as_f32(op: &Op, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+    use tensor::{f16, DatumType};
+    let inputs = inputs
+        .into_iter()
+        .map(|v| {
+            if v.datum_type() == DatumType::F16 {
+                Ok(v.cast_to::<f32>()?.into())
+            } else {
+                Ok(v)
+            }
+        }).collect::<TfdResult<TVec<Value>>>()?;
+    op.eval(inputs)?
+        .into_iter()
+        .map(|v| {
+            if v.datum_type() == DatumType::F32 {
+                Ok(v.to_array_view::<f32>()?.mapv(f16::from_f32).into())
+            } else {
+                Ok(v)
+            }
+        }).collect()
+}
 
 /// A streaming buffer for a Tensorflow operation.
 ///
@@ -0,0 +1,746 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use ndarray::{indices, LinalgScalar};
+use ops::array::stack;
+use ops::prelude::*;
+
+use super::patches::{spatial_param, DataFormat, PaddingSpec, Patch};
+
+fn as_slice(v: &Option<Vec<usize>>) -> Option<&[usize]> {
+    v.as_ref().map(|v| &**v)
+}
+
+fn kernel_spatial_shape(kernel: &[TDim]) -> TfdResult<Vec<usize>> {
+    kernel[2..]
+        .iter()
+        .map(|d| Ok(d.to_integer()? as usize))
+        .collect()
+}
+
+/// Rearranges a `[batch, spatial, channels]` result in the data format.
+fn into_data_format<T: Datum>(
+    data_format: DataFormat,
+    output: Array3<T>,
+    spatial: &[usize],
+) -> TfdResult<ArrayD<T>> {
+    let (n, _, c) = output.dim();
+    let shape = data_format.shape(n, c, spatial);
+    Ok(match data_format {
+        DataFormat::NHWC => output.into_shape(shape)?,
+        DataFormat::NCHW => ArrayD::from_shape_vec(
+            shape,
+            output.permuted_axes([0, 2, 1]).iter().cloned().collect(),
+        )?,
+    })
+}
+
+/// Checks the channel counts of a grouped convolution, and returns the
+/// number of input and output channels per group.
+fn group_channels(group: usize, input: usize, output: usize) -> TfdResult<(usize, usize)> {
+    if group == 0 || input % group != 0 || output % group != 0 {
+        bail!(
+            "Can not split {} input and {} output channels in {} groups",
+            input,
+            output,
+            group
+        )
+    }
+    Ok((input / group, output / group))
+}
+
+/// Layout of convolution kernels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum KernelFormat {
+    /// `[out_channels, in_channels / group, spatial...]`, as in ONNX.
+    OIHW,
+    /// `[spatial..., in_channels / group, out_channels]`, as in TensorFlow.
+    HWIO,
+}
+
+impl KernelFormat {
+    pub fn spatial_shape<'a, D>(&self, shape: &'a [D]) -> &'a [D] {
+        match self {
+            KernelFormat::OIHW => &shape[2..],
+            KernelFormat::HWIO => &shape[..shape.len() - 2],
+        }
+    }
+
+    pub fn input_channels<'a, D>(&self, shape: &'a [D]) -> &'a D {
+        match self {
+            KernelFormat::OIHW => &shape[1],
+            KernelFormat::HWIO => &shape[shape.len() - 2],
+        }
+    }
+
+    pub fn output_channels<'a, D>(&self, shape: &'a [D]) -> &'a D {
+        match self {
+            KernelFormat::OIHW => &shape[0],
+            KernelFormat::HWIO => &shape[shape.len() - 1],
+        }
+    }
+}
+
+/// N-dimensional convolution, optionally grouped and dilated.
+///
+/// An optional third input holds a bias per output channel. A depthwise
+/// convolution convolves each input channel with its own set of kernels: its
+/// HWIO kernel is laid out as `[spatial..., in_channels, multiplier]`.
+///
+/// The convolution streams along the batch axis or a spatial one. In the
+/// latter case, the streaming axis is not padded.
+#[derive(Debug, Clone, new)]
+pub struct Conv {
+    data_format: DataFormat,
+    kernel_format: KernelFormat,
+    padding: PaddingSpec,
+    dilations: Option<Vec<usize>>,
+    strides: Option<Vec<usize>>,
+    group: usize,
+    #[new(value = "false")]
+    depthwise: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConvBuffer {
+    // The number of future chunks to skip before storing them again.
+    skip: usize,
+
+    // The previous chunks which are still needed to compute the next
+    // output chunk.
+    prev: Option<Tensor>,
+}
+
+impl OpBuffer for ConvBuffer {}
+
+impl Conv {
+    /// A depthwise convolution, with a HWIO kernel.
+    pub fn depthwise(
+        data_format: DataFormat,
+        padding: PaddingSpec,
+        dilations: Option<Vec<usize>>,
+        strides: Option<Vec<usize>>,
+    ) -> Conv {
+        Conv {
+            depthwise: true,
+            ..Conv::new(data_format, KernelFormat::HWIO, padding, dilations, strides, 1)
+        }
+    }
+
+    fn output_shape(&self, input: &[TDim], kernel: &[TDim]) -> TfdResult<Vec<TDim>> {
+        let spatial = &input[self.data_format.spatial_axes(input.len())];
+        let kernel_spatial = self
+            .kernel_format
+            .spatial_shape(kernel)
+            .iter()
+            .map(|d| Ok(d.to_integer()? as usize))
+            .collect::<TfdResult<Vec<_>>>()?;
+        if kernel_spatial.len() != spatial.len() {
+            bail!("Kernel rank does not match input rank")
+        }
+        let dilations = spatial_param(as_slice(&self.dilations), spatial.len(), "dilations")?;
+        let strides = spatial_param(as_slice(&self.strides), spatial.len(), "strides")?;
+        let output = (0..spatial.len())
+            .map(|ax| {
+                self.padding
+                    .output_dim(ax, &spatial[ax], kernel_spatial[ax], dilations[ax], strides[ax])
+            })
+            .collect::<TfdResult<Vec<_>>>()?;
+        let channels = if self.depthwise {
+            *self.kernel_format.input_channels(kernel) * *self.kernel_format.output_channels(kernel)
+        } else {
+            *self.kernel_format.output_channels(kernel)
+        };
+        Ok(self.data_format.shape(input[0], channels, &output))
+    }
+
+    /// Convolves `input`. Padding is skipped along `unpadded_axis`, a
+    /// spatial axis index, if any.
+    fn convolve<T: Datum + LinalgScalar>(
+        &self,
+        input: ArrayViewD<T>,
+        kernel: ArrayViewD<T>,
+        bias: Option<ArrayViewD<T>>,
+        unpadded_axis: Option<usize>,
+    ) -> TfdResult<ArrayD<T>> {
+        let rank = input.ndim();
+        if rank < 3 || kernel.ndim() != rank {
+            bail!(
+                "Conv expects input and kernel of the same rank, at least 3, got {:?} and {:?}",
+                input.shape(),
+                kernel.shape()
+            )
+        }
+        let spatial = self.data_format.spatial_axes(rank);
+        let n = input.shape()[0];
+        let c = input.shape()[self.data_format.channel_axis(rank)];
+        let (kernel, group) = if self.depthwise {
+            let mut shape = kernel.shape().to_vec();
+            shape[rank - 1] *= shape[rank - 2];
+            shape[rank - 2] = 1;
+            (kernel.into_shape(shape)?, c)
+        } else {
+            (kernel, self.group)
+        };
+        let m = *self.kernel_format.output_channels(kernel.shape());
+        let (cg, mg) = group_channels(group, c, m)?;
+        if *self.kernel_format.input_channels(kernel.shape()) != cg {
+            bail!(
+                "Conv kernel expects {} channels per group, got {}",
+                self.kernel_format.input_channels(kernel.shape()),
+                cg
+            )
+        }
+        let mut patch = Patch::new(
+            &self.padding,
+            &input.shape()[spatial.clone()],
+            self.kernel_format.spatial_shape(kernel.shape()),
+            as_slice(&self.dilations),
+            as_slice(&self.strides),
+        )?;
+        if let Some(ax) = unpadded_axis {
+            patch.pads[ax] = PaddingSpec::Valid.compute(
+                ax,
+                patch.input_shape[ax],
+                patch.kernel_shape[ax],
+                patch.dilations[ax],
+                patch.strides[ax],
+            )?;
+        }
+        let output_spatial = patch.output_shape();
+        let output_len: usize = output_spatial.iter().product();
+        let k_len = patch.kernel_len();
+        let kernel = match self.kernel_format {
+            KernelFormat::OIHW => kernel.into_shape((m, cg, k_len))?,
+            KernelFormat::HWIO => kernel.into_shape((k_len, cg, m))?.permuted_axes([2, 1, 0]),
+        };
+
+        let mut output = Array3::<T>::zeros((n, output_len, m));
+        for b in 0..n {
+            for g in 0..group {
+                // Gathers the windows, one per row, then multiplies them by
+                // the kernel of the group.
+                let mut patches = Array2::<T>::zeros((output_len, cg * k_len));
+                for (row, coords) in indices(&*output_spatial).into_iter().enumerate() {
+                    let mut patch_row = patches.row_mut(row);
+                    patch.visit(coords.slice(), |k, x| {
+                        for ci in 0..cg {
+                            let index = self.data_format.shape(b, g * cg + ci, x);
+                            patch_row[ci * k_len + k] = input[&*index];
+                        }
+                    });
+                }
+                let kernel = Array2::from_shape_fn((cg * k_len, mg), |(r, mi)| {
+                    kernel[(g * mg + mi, r / k_len, r % k_len)]
+                });
+                output
+                    .slice_mut(s![b, .., g * mg..(g + 1) * mg])
+                    .assign(&patches.dot(&kernel));
+            }
+        }
+        if let Some(bias) = bias {
+            output = output + &bias.into_dimensionality::<Ix1>()?;
+        }
+        into_data_format(self.data_format, output, &output_spatial)
+    }
+
+    fn eval_t<T: Datum + LinalgScalar>(&self, inputs: &[Value]) -> TfdResult<Tensor> {
+        let bias = match inputs.get(2) {
+            Some(bias) => Some(bias.to_array_view::<T>()?),
+            None => None,
+        };
+        let output = self.convolve(
+            inputs[0].to_array_view::<T>()?,
+            inputs[1].to_array_view::<T>()?,
+            bias,
+            None,
+        )?;
+        Ok(output.into())
+    }
+
+    /// Buffers chunks streamed along a spatial axis until they cover the
+    /// window of the kernel, then convolves the buffer.
+    fn step_t<T: Datum + LinalgScalar>(
+        &self,
+        chunk: Value,
+        axis: usize,
+        inputs: &[Value],
+        buffer: &mut ConvBuffer,
+    ) -> TfdResult<Option<Tensor>> {
+        let chunk = chunk.to_array_view::<T>()?;
+        let spatial = self.data_format.spatial_axes(chunk.ndim());
+        if axis < spatial.start || axis >= spatial.end {
+            bail!("Conv only supports streaming along the batch or a spatial axis")
+        }
+        if chunk.shape()[axis] != 1 {
+            bail!("Conv expects chunks of dim 1 in streamed dim")
+        }
+        let ax = axis - spatial.start;
+        let kernel = inputs[0].to_array_view::<T>()?;
+        let kernel_len = match self.kernel_format.spatial_shape(kernel.shape()).get(ax) {
+            Some(&len) => len,
+            None => bail!("Kernel rank does not match input rank"),
+        };
+        let dilation = self.dilations.as_ref().map(|d| d[ax]).unwrap_or(1);
+        let stride = self.strides.as_ref().map(|s| s[ax]).unwrap_or(1);
+        let field = (kernel_len - 1) * dilation + 1;
+
+        if buffer.skip > 0 {
+            buffer.skip -= 1;
+            return Ok(None);
+        }
+        let mut next = match buffer.prev.take() {
+            Some(prev) => stack(Axis(axis), &[prev.to_array_view::<T>()?, chunk])?,
+            None => chunk.to_owned(),
+        };
+        // Maybe we don't have enough chunks to compute the convolution yet.
+        if next.shape()[axis] < field {
+            buffer.prev = Some(next.into());
+            return Ok(None);
+        }
+        let bias = match inputs.get(1) {
+            Some(bias) => Some(bias.to_array_view::<T>()?),
+            None => None,
+        };
+        let output = self.convolve(next.view(), kernel, bias, Some(ax))?;
+        if stride > field {
+            buffer.skip = stride - field;
+        } else {
+            next.slice_axis_inplace(Axis(axis), (stride..).into());
+            buffer.prev = Some(next.into());
+        }
+        Ok(Some(output.into()))
+    }
+}
+
+impl Op for Conv {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        if inputs.len() != 2 && inputs.len() != 3 {
+            bail!("Conv expects 2 or 3 inputs, got {}", inputs.len())
+        }
+        let output = match inputs[0].datum_type() {
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, inputs),
+            DatumType::F32 => self.eval_t::<f32>(&inputs)?,
+            DatumType::F64 => self.eval_t::<f64>(&inputs)?,
+            dt => bail!("Conv not covering {:?}", dt),
+        };
+        Ok(tvec![output.into()])
+    }
+
+    /// Returns a new streaming buffer for the operation.
+    fn new_buffer(&self) -> Box<OpBuffer> {
+        Box::new(ConvBuffer::default())
+    }
+
+    /// Evaluates one step of the operation on the given input tensors.
+    fn step(
+        &self,
+        inputs: TVec<StepValue>,
+        buffer: &mut Box<OpBuffer>,
+    ) -> TfdResult<Option<TVec<Value>>> {
+        let mut inputs = inputs.into_iter();
+        let data = inputs.next().ok_or("Conv expects a data input")?;
+        let params = inputs
+            .map(|i| i.into_const().ok_or("Conv kernel and bias can not be streamed"))
+            .collect::<Result<TVec<Value>, _>>()?;
+        let Stream { info, chunk, .. } = data.into_stream().ok_or("data must be streamed")?;
+        let chunk = match chunk {
+            Some(chunk) => chunk,
+            None => return Ok(None),
+        };
+        // Maybe the data is streamed along the batch dimension.
+        if info.axis == 0 {
+            let mut inputs = tvec![chunk];
+            inputs.extend(params);
+            return Ok(Some(self.eval(inputs)?));
+        }
+        let buffer = buffer
+            .downcast_mut::<ConvBuffer>()
+            .ok_or("The buffer can't be downcasted to ConvBuffer.")?;
+        let output = match chunk.datum_type() {
+            DatumType::F32 => self.step_t::<f32>(chunk, info.axis, &params, buffer)?,
+            DatumType::F64 => self.step_t::<f64>(chunk, info.axis, &params, buffer)?,
+            dt => bail!("Conv streaming not covering {:?}", dt),
+        };
+        Ok(output.map(|t| tvec![t.into()]))
+    }
+
+    /// Scaling the kernel along its output channels scales the output along
+    /// its channel axis.
+    fn kernel_channel_axes(&self, rank: usize) -> Option<(usize, usize)> {
+        if self.depthwise {
+            return None;
+        }
+        let kernel_axis = match self.kernel_format {
+            KernelFormat::OIHW => 0,
+            KernelFormat::HWIO => rank - 1,
+        };
+        Some((self.data_format.channel_axis(rank), kernel_axis))
+    }
+}
+
+impl InferenceRulesOp for Conv {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &inputs[1].datum_type)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].rank, &inputs[1].rank)
+            .equals(&inputs[0].rank, &outputs[0].rank)
+            .given(&inputs.len, move |solver, len| {
+                if len == 3 {
+                    solver
+                        .equals(&inputs[2].datum_type, &inputs[0].datum_type)
+                        .equals(&inputs[2].rank, 1);
+                }
+            })
+            .given(&inputs[0].shape, move |solver, input: Vec<TDim>| {
+                solver.given(&inputs[1].shape, move |solver, kernel: Vec<TDim>| {
+                    if let Ok(shape) = self.output_shape(&input, &kernel) {
+                        solver.equals(&outputs[0].shape, shape);
+                    }
+                });
+            });
+    }
+}
+
+/// N-dimensional transposed convolution, the gradient of `Conv` with respect
+/// to its input.
+///
+/// The kernel is laid out as `[in_channels, out_channels / group,
+/// spatial...]`. The output shape, if given, overrides the padding.
+#[derive(Debug, Clone, new)]
+pub struct ConvTranspose {
+    data_format: DataFormat,
+    padding: PaddingSpec,
+    dilations: Option<Vec<usize>>,
+    strides: Option<Vec<usize>>,
+    output_padding: Option<Vec<usize>>,
+    output_shape: Option<Vec<usize>>,
+    group: usize,
+}
+
+impl ConvTranspose {
+    fn resolve(&self, input: &[usize], kernel: &[usize]) -> TfdResult<Patch> {
+        let rank = input.len();
+        if kernel.len() != rank {
+            bail!("Kernel rank does not match input rank")
+        }
+        let dilations = spatial_param(as_slice(&self.dilations), rank, "dilations")?;
+        let strides = spatial_param(as_slice(&self.strides), rank, "strides")?;
+        let output_padding = match self.output_padding {
+            Some(ref p) if p.len() == rank => p.clone(),
+            Some(ref p) => bail!("Invalid output padding {:?}", p),
+            None => vec![0; rank],
+        };
+        if let Some(ref shape) = self.output_shape {
+            if shape.len() != rank {
+                bail!("Invalid output shape {:?}", shape)
+            }
+        }
+        let pads = (0..rank)
+            .map(|ax| {
+                self.padding.compute_transposed(
+                    ax,
+                    input[ax],
+                    self.output_shape.as_ref().map(|s| s[ax]),
+                    (kernel[ax] - 1) * dilations[ax] + 1,
+                    strides[ax],
+                    output_padding[ax],
+                )
+            })
+            .collect::<TfdResult<_>>()?;
+        Ok(Patch {
+            input_shape: input.to_vec(),
+            kernel_shape: kernel.to_vec(),
+            dilations,
+            strides,
+            pads,
+        })
+    }
+
+    fn output_shape(&self, input: &[TDim], kernel: &[TDim]) -> TfdResult<Vec<TDim>> {
+        let spatial = input[self.data_format.spatial_axes(input.len())]
+            .iter()
+            .map(|d| Ok(d.to_integer()? as usize))
+            .collect::<TfdResult<Vec<_>>>()?;
+        let patch = self.resolve(&spatial, &kernel_spatial_shape(kernel)?)?;
+        let output: Vec<TDim> = patch.output_shape().into_iter().map(|d| d.to_dim()).collect();
+        let channels = kernel[1] * self.group as i64;
+        Ok(self.data_format.shape(input[0], channels, &output))
+    }
+
+    fn eval_t<T: Datum + LinalgScalar>(&self, inputs: &[Value]) -> TfdResult<Tensor> {
+        let input = inputs[0].to_array_view::<T>()?;
+        let kernel = inputs[1].to_array_view::<T>()?;
+        let rank = input.ndim();
+        if rank < 3 || kernel.ndim() != rank {
+            bail!(
+                "ConvTranspose expects input and kernel of the same rank, at least 3, got {:?} and {:?}",
+                input.shape(),
+                kernel.shape()
+            )
+        }
+        let spatial = self.data_format.spatial_axes(rank);
+        let n = input.shape()[0];
+        let c = input.shape()[self.data_format.channel_axis(rank)];
+        if kernel.shape()[0] != c {
+            bail!("ConvTranspose kernel expects {} channels, got {}", kernel.shape()[0], c)
+        }
+        let m = kernel.shape()[1] * self.group;
+        let (cg, mg) = group_channels(self.group, c, m)?;
+        let input_spatial = &input.shape()[spatial];
+        let patch = self.resolve(input_spatial, &kernel.shape()[2..])?;
+        let output_spatial = patch.output_shape();
+        let input_len: usize = input_spatial.iter().product();
+        let k_len = patch.kernel_len();
+
+        let mut output = Array3::<T>::zeros((n, output_spatial.iter().product(), m));
+        for b in 0..n {
+            for g in 0..self.group {
+                let mut x = Array2::<T>::zeros((input_len, cg));
+                for (row, coords) in indices(input_spatial).into_iter().enumerate() {
+                    for ci in 0..cg {
+                        let index = self.data_format.shape(b, g * cg + ci, coords.slice());
+                        x[(row, ci)] = input[&*index];
+                    }
+                }
+                let kernel = kernel
+                    .slice_axis(Axis(0), (g * cg..(g + 1) * cg).into())
+                    .into_shape((cg, mg * k_len))?;
+                let columns = x.dot(&kernel);
+                // Scatters every input point times the kernel on the window
+                // it spreads over.
+                for (row, coords) in indices(input_spatial).into_iter().enumerate() {
+                    'kernel: for k in 0..k_len {
+                        let mut rest = k;
+                        let mut flat = 0;
+                        let mut scale = 1;
+                        for ax in (0..output_spatial.len()).rev() {
+                            let kx = rest % patch.kernel_shape[ax];
+                            rest /= patch.kernel_shape[ax];
+                            let y = (coords[ax] * patch.strides[ax] + kx * patch.dilations[ax])
+                                as isize
+                                - patch.pads[ax].pad_before as isize;
+                            if y < 0 || y >= output_spatial[ax] as isize {
+                                continue 'kernel;
+                            }
+                            flat += y as usize * scale;
+                            scale *= output_spatial[ax];
+                        }
+                        for mi in 0..mg {
+                            let v = columns[(row, mi * k_len + k)];
+                            let out = &mut output[(b, flat, g * mg + mi)];
+                            *out = *out + v;
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(bias) = inputs.get(2) {
+            output = output + &bias.to_array_view::<T>()?.into_dimensionality::<Ix1>()?;
+        }
+        Ok(into_data_format(self.data_format, output, &output_spatial)?.into())
+    }
+}
+
+impl Op for ConvTranspose {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        if inputs.len() != 2 && inputs.len() != 3 {
+            bail!("ConvTranspose expects 2 or 3 inputs, got {}", inputs.len())
+        }
+        let output = match inputs[0].datum_type() {
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, inputs),
+            DatumType::F32 => self.eval_t::<f32>(&inputs)?,
+            DatumType::F64 => self.eval_t::<f64>(&inputs)?,
+            dt => bail!("ConvTranspose not covering {:?}", dt),
+        };
+        Ok(tvec![output.into()])
+    }
+}
+
+impl InferenceRulesOp for ConvTranspose {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &inputs[1].datum_type)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].rank, &inputs[1].rank)
+            .equals(&inputs[0].rank, &outputs[0].rank)
+            .given(&inputs[0].shape, move |solver, input: Vec<TDim>| {
+                solver.given(&inputs[1].shape, move |solver, kernel: Vec<TDim>| {
+                    if let Ok(shape) = self.output_shape(&input, &kernel) {
+                        solver.equals(&outputs[0].shape, shape);
+                    }
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conv(op: &Op, inputs: Vec<Tensor>) -> Tensor {
+        op.eval(inputs.into_iter().map(|t| t.into()).collect())
+            .unwrap()
+            .remove(0)
+            .into_tensor()
+    }
+
+    #[test]
+    fn conv_1d_padded_strided() {
+        let op = Conv::new(
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+            PaddingSpec::Explicit(vec![1], vec![1]),
+            None,
+            Some(vec![2]),
+            1,
+        );
+        let input = Tensor::f32s(&[1, 1, 5], &[1., 2., 3., 4., 5.]).unwrap();
+        let kernel = Tensor::f32s(&[1, 1, 3], &[1., 1., 1.]).unwrap();
+        assert_eq!(
+            conv(&op, vec![input, kernel]),
+            Tensor::f32s(&[1, 1, 3], &[3., 9., 9.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn conv_2d_dilated_with_bias() {
+        let op = Conv::new(
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+            PaddingSpec::Valid,
+            Some(vec![2, 2]),
+            None,
+            1,
+        );
+        let input = Tensor::f32s(&[1, 1, 3, 3], &[1., 2., 3., 4., 5., 6., 7., 8., 9.]).unwrap();
+        let kernel = Tensor::f32s(&[2, 1, 2, 2], &[1., 0., 0., 1., 0., 1., 0., 0.]).unwrap();
+        let bias = Tensor::f32s(&[2], &[0., 10.]).unwrap();
+        assert_eq!(
+            conv(&op, vec![input, kernel, bias]),
+            Tensor::f32s(&[1, 2, 1, 1], &[10., 13.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn conv_grouped_nhwc() {
+        let op = Conv::new(
+            DataFormat::NHWC,
+            KernelFormat::OIHW,
+            PaddingSpec::SameUpper,
+            None,
+            None,
+            2,
+        );
+        let input = Tensor::f32s(&[1, 2, 2], &[1., 10., 2., 20.]).unwrap();
+        let kernel = Tensor::f32s(&[2, 1, 2], &[1., 1., 1., -1.]).unwrap();
+        assert_eq!(
+            conv(&op, vec![input, kernel]),
+            Tensor::f32s(&[1, 2, 2], &[3., -10., 2., 20.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn conv_depthwise_hwio() {
+        let op = Conv::depthwise(DataFormat::NHWC, PaddingSpec::Valid, None, None);
+        let input = Tensor::f32s(&[1, 2, 2], &[1., 10., 2., 20.]).unwrap();
+        let kernel = Tensor::f32s(&[2, 2, 2], &[1., -1., 1., 2., 1., 1., 1., 0.]).unwrap();
+        assert_eq!(
+            conv(&op, vec![input, kernel]),
+            Tensor::f32s(&[1, 1, 4], &[3., 1., 30., 20.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn conv_streaming_spatial_axis() {
+        let op = Conv::new(
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+            PaddingSpec::SameUpper,
+            None,
+            Some(vec![2, 1]),
+            1,
+        );
+        let kernel = Tensor::f32s(&[1, 1, 2, 2], &[1., 1., 1., 1.]).unwrap();
+        let input = Tensor::f32s(&[1, 1, 4, 2], &[1., 2., 3., 4., 5., 6., 7., 8.]).unwrap();
+        let mut buffer = op.new_buffer();
+        let mut outputs = vec![];
+        for row in input.as_f32s().unwrap().axis_iter(Axis(2)) {
+            let chunk: Tensor = row.insert_axis(Axis(2)).to_owned().into();
+            let inputs = tvec![
+                StepValue::Stream(Stream {
+                    info: StreamInfo { axis: 2, len: TDim::stream() },
+                    offset: 0,
+                    chunk: Some(chunk.into()),
+                }),
+                StepValue::Const(kernel.clone().into()),
+            ];
+            if let Some(mut output) = op.step(inputs, &mut buffer).unwrap() {
+                outputs.push(output.remove(0).into_tensor());
+            }
+        }
+        // Rows are not padded along the streaming axis, columns are.
+        assert_eq!(
+            outputs,
+            vec![
+                Tensor::f32s(&[1, 1, 1, 2], &[10., 6.]).unwrap(),
+                Tensor::f32s(&[1, 1, 1, 2], &[26., 14.]).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn conv_transpose_strided() {
+        let op = ConvTranspose::new(
+            DataFormat::NCHW,
+            PaddingSpec::Valid,
+            None,
+            Some(vec![2]),
+            None,
+            None,
+            1,
+        );
+        let input = Tensor::f32s(&[1, 1, 2], &[1., 2.]).unwrap();
+        let kernel = Tensor::f32s(&[1, 2, 3], &[1., 1., 1., 1., 2., 3.]).unwrap();
+        assert_eq!(
+            conv(&op, vec![input, kernel]),
+            Tensor::f32s(&[1, 2, 5], &[1., 1., 3., 2., 2., 1., 2., 5., 4., 6.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn conv_rules() {
+        let op = Conv::new(
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+            PaddingSpec::SameUpper,
+            None,
+            Some(vec![2, 2]),
+            1,
+        );
+        let input = TensorFact::dt_shape(DatumType::F32, vec![1, 3, 7, 8]);
+        let kernel = TensorFact::dt_shape(DatumType::F32, vec![4, 3, 3, 3]);
+        let (_, output) = op
+            .infer(tvec![input, kernel], tvec![TensorFact::default()])
+            .unwrap();
+        assert_eq!(
+            output[0],
+            TensorFact::dt_shape(DatumType::F32, vec![1, 4, 4, 4])
+        );
+    }
+}
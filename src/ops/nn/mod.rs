@@ -0,0 +1,42 @@
+mod conv;
+mod norm;
+mod patches;
+mod pools;
+mod softmax;
+pub use self::conv::{Conv, ConvBuffer, ConvTranspose, KernelFormat};
+pub use self::norm::{per_channel, BatchNorm, InstanceNorm, Lrn};
+pub use self::patches::{DataFormat, PaddingSpec, Patch};
+pub use self::pools::{AvgPool, GlobalAvgPool, GlobalMaxPool, MaxPool, PoolSpec};
+pub use self::softmax::{LogSoftmax, Softmax};
+
+element_map!(Relu, [f32,i32], |x| if x < 0 as _ { 0 as _ } else { x });
+element_map!(Sigmoid, [f32], |x| ((-x).exp() + 1.0).recip());
+element_map!(Relu6, [f32, i32], |x| if x < 0 as _ { 0 as _ } else if x > 6 as _ { 6 as _ } else { x });
+element_map!(Elu, [f32, f64], |x| if x < 0.0 { x.exp_m1() } else { x });
+element_map!(Selu, [f32, f64], |x| {
+    // The f64 constants, rounded when used as f32.
+    #[allow(clippy::excessive_precision)]
+    let (alpha, scale) = (1.6732632423543772, 1.0507009873554805);
+    if x < 0.0 { scale * alpha * x.exp_m1() } else { scale * x }
+});
+element_map!(LeakyRelu, [f32], { alpha: f32 }, |op, x| if x < 0.0 { op.alpha * x } else { x });
+// ln(1 + e^x), without overflowing for large x.
+element_map!(Softplus, [f32, f64], |x| x.max(0.0) + (-x.abs()).exp().ln_1p());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ops::prelude::*;
+
+    #[test]
+    fn activations() {
+        let x = || Tensor::f32s(&[3], &[-1.0, 0.5, 8.0]).unwrap().into();
+        let relu6 = Relu6::default().eval(tvec![x()]).unwrap();
+        assert_eq!(relu6[0].as_tensor(), &Tensor::f32s(&[3], &[0.0, 0.5, 6.0]).unwrap());
+        let leaky = LeakyRelu::new(0.1).eval(tvec![x()]).unwrap();
+        assert_eq!(leaky[0].as_tensor(), &Tensor::f32s(&[3], &[-0.1, 0.5, 8.0]).unwrap());
+        let large = Tensor::f32s(&[2], &[0.0, 100.0]).unwrap();
+        let softplus = Softplus::default().eval(tvec![large.into()]).unwrap();
+        assert_eq!(softplus[0].as_tensor(), &Tensor::f32s(&[2], &[2.0f32.ln(), 100.0]).unwrap());
+    }
+}
@@ -0,0 +1,324 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use num::Float;
+use ops::prelude::*;
+
+/// Reshapes a per-channel vector so that it broadcasts against a tensor of
+/// the given rank along `axis`.
+pub fn per_channel<T: Datum>(values: Array1<T>, rank: usize, axis: usize) -> TfdResult<ArrayD<T>> {
+    let mut shape = vec![1; rank];
+    shape[axis] = values.len();
+    Ok(values.into_shape(shape)?)
+}
+
+/// Inference-time batch normalization, with precomputed statistics.
+///
+/// Inputs are the data, then per-channel scale, offset, mean and variance.
+/// After the normalized data, the op can forward the mean and the variance,
+/// alternately, as extra outputs, like TensorFlow's FusedBatchNorm does at
+/// inference time.
+#[derive(Debug, Clone, new)]
+pub struct BatchNorm {
+    epsilon: f32,
+    channel_axis: usize,
+    #[new(value = "0")]
+    statistics_outputs: usize,
+}
+
+impl BatchNorm {
+    /// A batch normalization forwarding its mean and variance in
+    /// `statistics_outputs` extra outputs.
+    pub fn with_statistics(epsilon: f32, channel_axis: usize, statistics_outputs: usize) -> BatchNorm {
+        BatchNorm {
+            statistics_outputs,
+            ..BatchNorm::new(epsilon, channel_axis)
+        }
+    }
+
+    pub fn channel_axis(&self) -> usize {
+        self.channel_axis
+    }
+
+    pub fn statistics_outputs(&self) -> usize {
+        self.statistics_outputs
+    }
+
+    /// Folds the normalization parameters into a per-channel multiplier and
+    /// bias: `y = x * multiplier + bias`.
+    pub fn multiplier_and_bias<T: Datum + Float>(
+        &self,
+        scale: &Tensor,
+        offset: &Tensor,
+        mean: &Tensor,
+        variance: &Tensor,
+    ) -> TfdResult<(Array1<T>, Array1<T>)> {
+        let view = |t: &Tensor| -> TfdResult<Array1<T>> {
+            Ok(t.to_array_view::<T>()?.into_dimensionality::<Ix1>()?.to_owned())
+        };
+        let epsilon = T::from(self.epsilon).unwrap();
+        let multiplier = view(scale)? / &view(variance)?.mapv(|v| (v + epsilon).sqrt());
+        let bias = view(offset)? - &(view(mean)? * &multiplier);
+        Ok((multiplier, bias))
+    }
+
+    fn eval_t<T: Datum + Float>(&self, inputs: &[Value]) -> TfdResult<Tensor> {
+        let x = inputs[0].to_array_view::<T>()?;
+        let (multiplier, bias) =
+            self.multiplier_and_bias::<T>(&inputs[1], &inputs[2], &inputs[3], &inputs[4])?;
+        let multiplier = per_channel(multiplier, x.ndim(), self.channel_axis)?;
+        let bias = per_channel(bias, x.ndim(), self.channel_axis)?;
+        Ok((&x * &multiplier + &bias).into())
+    }
+}
+
+impl Op for BatchNorm {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        if inputs.len() != 5 {
+            bail!("BatchNorm expects 5 inputs, got {}", inputs.len())
+        }
+        let output = match inputs[0].datum_type() {
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, inputs),
+            DatumType::F32 => self.eval_t::<f32>(&inputs)?,
+            DatumType::F64 => self.eval_t::<f64>(&inputs)?,
+            dt => bail!("BatchNorm not covering {:?}", dt),
+        };
+        let mut outputs = tvec![output.into()];
+        for i in 0..self.statistics_outputs {
+            outputs.push(inputs[3 + i % 2].clone());
+        }
+        Ok(outputs)
+    }
+
+    // The analyser only tracks the first output of a node: propagate the
+    // normalized data, not the last statistics output, as the value.
+    fn infer_and_propagate(
+        &self,
+        inputs: TVec<TensorFact>,
+        outputs: TVec<TensorFact>,
+    ) -> TfdResult<(TVec<TensorFact>, TVec<TensorFact>)> {
+        let (inputs, outputs) = self.infer(inputs, outputs)?;
+        if !inputs.iter().all(|i| i.value.is_concrete()) {
+            return Ok((inputs, outputs));
+        }
+        let values = inputs
+            .iter()
+            .map(|i| i.value.concretize().unwrap().clone().into())
+            .collect();
+        let output = self.eval(values)?.remove(0).into_tensor();
+        Ok((inputs, tvec![::analyser::helpers::tensor_to_fact(output)]))
+    }
+}
+
+impl InferenceRulesOp for BatchNorm {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        // With statistics, the analyser hands the node a single output fact
+        // for the normalized data, so the number of outputs is left open.
+        if self.statistics_outputs == 0 {
+            solver.equals(&outputs.len, 1);
+        }
+        solver
+            .equals(&inputs.len, 5)
+            .equals_all((0..5).map(|i| (&inputs[i].datum_type).bex()).collect())
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].shape, &outputs[0].shape);
+        for i in 1..5 {
+            solver
+                .equals(&inputs[i].rank, 1)
+                .equals(&inputs[i].shape[0], &inputs[0].shape[self.channel_axis]);
+        }
+    }
+}
+
+/// Instance normalization: normalizes each channel of each item of the
+/// batch over its spatial dimensions, then applies a per-channel scale and
+/// bias.
+///
+/// The input is laid out as `[N, C, ...]`.
+#[derive(Debug, Clone, new)]
+pub struct InstanceNorm {
+    epsilon: f32,
+}
+
+impl InstanceNorm {
+    fn eval_t<T: Datum + Float>(&self, inputs: &[Value]) -> TfdResult<Tensor> {
+        let mut x = inputs[0].to_array_view::<T>()?.to_owned();
+        let scale = inputs[1].to_array_view::<T>()?.into_dimensionality::<Ix1>()?;
+        let bias = inputs[2].to_array_view::<T>()?.into_dimensionality::<Ix1>()?;
+        let epsilon = T::from(self.epsilon).unwrap();
+        for mut item in x.outer_iter_mut() {
+            for (c, mut channel) in item.outer_iter_mut().enumerate() {
+                let len = T::from(channel.len()).unwrap();
+                let mean = channel.iter().fold(T::zero(), |a, &b| a + b) / len;
+                let variance = channel
+                    .iter()
+                    .fold(T::zero(), |a, &b| a + (b - mean) * (b - mean))
+                    / len;
+                let multiplier = scale[c] / (variance + epsilon).sqrt();
+                channel.mapv_inplace(|v| (v - mean) * multiplier + bias[c]);
+            }
+        }
+        Ok(x.into())
+    }
+}
+
+impl Op for InstanceNorm {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        if inputs.len() != 3 {
+            bail!("InstanceNorm expects 3 inputs, got {}", inputs.len())
+        }
+        let output = match inputs[0].datum_type() {
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, inputs),
+            DatumType::F32 => self.eval_t::<f32>(&inputs)?,
+            DatumType::F64 => self.eval_t::<f64>(&inputs)?,
+            dt => bail!("InstanceNorm not covering {:?}", dt),
+        };
+        Ok(tvec![output.into()])
+    }
+}
+
+impl InferenceRulesOp for InstanceNorm {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 3)
+            .equals(&outputs.len, 1)
+            .equals_all((0..3).map(|i| (&inputs[i].datum_type).bex()).collect())
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].shape, &outputs[0].shape)
+            .equals(&inputs[1].rank, 1)
+            .equals(&inputs[2].rank, 1)
+            .equals(&inputs[1].shape[0], &inputs[0].shape[1])
+            .equals(&inputs[2].shape[0], &inputs[0].shape[1]);
+    }
+}
+
+/// Local response normalization across channels.
+///
+/// Each value is divided by `(bias + alpha * sum(x^2)) ^ beta`, where the
+/// sum runs over `size` neighbouring channels centered on the value's one.
+#[derive(Debug, Clone, new)]
+pub struct Lrn {
+    alpha: f32,
+    beta: f32,
+    bias: f32,
+    size: usize,
+    channel_axis: usize,
+}
+
+impl Lrn {
+    fn eval_t<T: Datum + Float>(&self, input: Value) -> TfdResult<Tensor> {
+        let x = input.to_array_view::<T>()?;
+        if self.channel_axis >= x.ndim() {
+            bail!("Lrn channel axis {} is invalid for rank {}", self.channel_axis, x.ndim())
+        }
+        if self.size == 0 {
+            bail!("Lrn needs a window of at least one channel")
+        }
+        let channels = x.shape()[self.channel_axis];
+        let before = (self.size - 1) / 2;
+        let after = self.size - 1 - before;
+        let (alpha, beta, bias) = (
+            T::from(self.alpha).unwrap(),
+            T::from(self.beta).unwrap(),
+            T::from(self.bias).unwrap(),
+        );
+        let output = ArrayD::from_shape_fn(x.shape(), |coords| {
+            let c = coords[self.channel_axis];
+            let mut coords = coords.clone();
+            let mut square_sum = T::zero();
+            for other in c.saturating_sub(before)..(c + after + 1).min(channels) {
+                coords[self.channel_axis] = other;
+                square_sum = square_sum + x[&coords] * x[&coords];
+            }
+            coords[self.channel_axis] = c;
+            x[&coords] / (bias + alpha * square_sum).powf(beta)
+        });
+        Ok(output.into())
+    }
+}
+
+impl Op for Lrn {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let input = args_1!(inputs);
+        let output = match input.datum_type() {
+            DatumType::F16 => return ::ops::eval_f16_as_f32(self, tvec![input]),
+            DatumType::F32 => self.eval_t::<f32>(input)?,
+            DatumType::F64 => self.eval_t::<f64>(input)?,
+            dt => bail!("Lrn not covering {:?}", dt),
+        };
+        Ok(tvec![output.into()])
+    }
+}
+
+impl InferenceRulesOp for Lrn {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].shape, &outputs[0].shape);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_norm_nchw() {
+        let x = Tensor::f32s(&[1, 2, 1, 2], &[1., 2., 3., 4.]).unwrap();
+        let scale = Tensor::f32s(&[2], &[1., 2.]).unwrap();
+        let offset = Tensor::f32s(&[2], &[0., 1.]).unwrap();
+        let mean = Tensor::f32s(&[2], &[1., 3.]).unwrap();
+        let variance = Tensor::f32s(&[2], &[4., 1.]).unwrap();
+        let op = BatchNorm::new(0., 1);
+        let output = op
+            .eval(tvec![x.into(), scale.into(), offset.into(), mean.into(), variance.into()])
+            .unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::f32s(&[1, 2, 1, 2], &[0., 0.5, 1., 3.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn instance_norm() {
+        let x = Tensor::f32s(&[1, 2, 2], &[1., 3., 4., 6.]).unwrap();
+        let scale = Tensor::f32s(&[2], &[2., 1.]).unwrap();
+        let bias = Tensor::f32s(&[2], &[1., 1.]).unwrap();
+        let output = InstanceNorm::new(0.)
+            .eval(tvec![x.into(), scale.into(), bias.into()])
+            .unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::f32s(&[1, 2, 2], &[-1., 3., 0., 2.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn lrn() {
+        let x = Tensor::f32s(&[1, 3], &[1., 2., 2.]).unwrap();
+        let output = Lrn::new(1., 1., 0., 3, 1).eval(tvec![x.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::f32s(&[1, 3], &[0.2, 2. / 9., 0.25]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use ops::prelude::*;
+use std::ops::Range;
+
+/// Layout of the data tensors of convolutions and pools.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum DataFormat {
+    /// Channels come right after the batch axis, as in ONNX.
+    NCHW,
+    /// Channels are the last axis, as in TensorFlow.
+    NHWC,
+}
+
+impl DataFormat {
+    pub fn channel_axis(&self, rank: usize) -> usize {
+        match self {
+            DataFormat::NCHW => 1,
+            DataFormat::NHWC => rank - 1,
+        }
+    }
+
+    pub fn spatial_axes(&self, rank: usize) -> Range<usize> {
+        match self {
+            DataFormat::NCHW => 2..rank,
+            DataFormat::NHWC => 1..rank - 1,
+        }
+    }
+
+    /// Lays out batch, channel and spatial dimensions (or coordinates) in
+    /// this format.
+    pub fn shape<D: Clone>(&self, n: D, c: D, spatial: &[D]) -> Vec<D> {
+        let mut shape = Vec::with_capacity(spatial.len() + 2);
+        shape.push(n);
+        if *self == DataFormat::NCHW {
+            shape.push(c.clone());
+        }
+        shape.extend(spatial.iter().cloned());
+        if *self == DataFormat::NHWC {
+            shape.push(c);
+        }
+        shape
+    }
+}
+
+/// How the borders of the input are padded.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum PaddingSpec {
+    /// Explicit padding before and after each spatial axis.
+    Explicit(Vec<usize>, Vec<usize>),
+    /// No padding: the windows stay inside the input.
+    Valid,
+    /// Pads so that the output is `ceil(input / stride)` long, putting the
+    /// odd padding unit at the end.
+    SameUpper,
+    /// Same as `SameUpper`, with the odd padding unit at the beginning.
+    SameLower,
+}
+
+/// Padding and output length resolved for one spatial axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedPaddedDim {
+    pub output: usize,
+    pub pad_before: usize,
+    pub pad_after: usize,
+}
+
+impl PaddingSpec {
+    /// Splits the padding needed by a "same" padding between both ends.
+    fn split_same(&self, total: usize) -> (usize, usize) {
+        match self {
+            PaddingSpec::SameLower => (total - total / 2, total / 2),
+            _ => (total / 2, total - total / 2),
+        }
+    }
+
+    /// Computes the output length of a spatial axis.
+    pub fn output_dim(
+        &self,
+        axis: usize,
+        input: &TDim,
+        kernel: usize,
+        dilation: usize,
+        stride: usize,
+    ) -> TfdResult<TDim> {
+        let field = (kernel - 1) * dilation + 1;
+        Ok(match self {
+            PaddingSpec::Valid => (*input - field + 1).div_ceil(TDim::from(stride)),
+            PaddingSpec::SameUpper | PaddingSpec::SameLower => input.div_ceil(TDim::from(stride)),
+            PaddingSpec::Explicit(before, after) => {
+                let (before, after) = match (before.get(axis), after.get(axis)) {
+                    (Some(b), Some(a)) => (*b, *a),
+                    _ => bail!("No explicit padding for axis {}", axis),
+                };
+                (*input + before + after - field) / stride + 1
+            }
+        })
+    }
+
+    /// Computes the output length and the padding of a spatial axis.
+    pub fn compute(
+        &self,
+        axis: usize,
+        input: usize,
+        kernel: usize,
+        dilation: usize,
+        stride: usize,
+    ) -> TfdResult<ComputedPaddedDim> {
+        let field = (kernel - 1) * dilation + 1;
+        let (pad_before, pad_after) = match self {
+            PaddingSpec::Valid => (0, 0),
+            PaddingSpec::SameUpper | PaddingSpec::SameLower => {
+                let output = (input + stride - 1) / stride;
+                let total = ((output.max(1) - 1) * stride + field).saturating_sub(input);
+                self.split_same(total)
+            }
+            PaddingSpec::Explicit(before, after) => match (before.get(axis), after.get(axis)) {
+                (Some(b), Some(a)) => (*b, *a),
+                _ => bail!("No explicit padding for axis {}", axis),
+            },
+        };
+        let padded = input + pad_before + pad_after;
+        if padded < field {
+            bail!(
+                "Window of {} does not fit in input of {} padded to {}",
+                field,
+                input,
+                padded
+            )
+        }
+        Ok(ComputedPaddedDim {
+            output: (padded - field) / stride + 1,
+            pad_before,
+            pad_after,
+        })
+    }
+
+    /// Resolves the padding of a transposed convolution, which maps an
+    /// input of `input` onto an output of `output`, with a dilated kernel
+    /// spanning `field`.
+    pub fn compute_transposed(
+        &self,
+        axis: usize,
+        input: usize,
+        output: Option<usize>,
+        field: usize,
+        stride: usize,
+        output_padding: usize,
+    ) -> TfdResult<ComputedPaddedDim> {
+        let full = stride * (input.max(1) - 1) + output_padding + field;
+        let (pad_before, pad_after) = match (self, output) {
+            (PaddingSpec::Explicit(before, after), None) => {
+                match (before.get(axis), after.get(axis)) {
+                    (Some(b), Some(a)) => (*b, *a),
+                    _ => bail!("No explicit padding for axis {}", axis),
+                }
+            }
+            (PaddingSpec::Valid, None) => (0, 0),
+            (_, output) => {
+                let output = output.unwrap_or(input * stride);
+                if output > full {
+                    bail!("Output length {} is too long for input {}", output, input)
+                }
+                self.split_same(full - output)
+            }
+        };
+        if pad_before + pad_after > full {
+            bail!("Padding exceeds the output of transposed convolution")
+        }
+        Ok(ComputedPaddedDim {
+            output: full - pad_before - pad_after,
+            pad_before,
+            pad_after,
+        })
+    }
+}
+
+/// A sliding window over the spatial axes of an input, resolved for
+/// concrete input and kernel shapes.
+#[derive(Debug, Clone)]
+pub struct Patch {
+    pub input_shape: Vec<usize>,
+    pub kernel_shape: Vec<usize>,
+    pub dilations: Vec<usize>,
+    pub strides: Vec<usize>,
+    pub pads: Vec<ComputedPaddedDim>,
+}
+
+impl Patch {
+    /// Resolves a patch. Missing dilations and strides default to 1.
+    pub fn new(
+        padding: &PaddingSpec,
+        input_shape: &[usize],
+        kernel_shape: &[usize],
+        dilations: Option<&[usize]>,
+        strides: Option<&[usize]>,
+    ) -> TfdResult<Patch> {
+        let rank = input_shape.len();
+        if kernel_shape.len() != rank {
+            bail!(
+                "Kernel shape {:?} does not match input spatial shape {:?}",
+                kernel_shape,
+                input_shape
+            )
+        }
+        let dilations = spatial_param(dilations, rank, "dilations")?;
+        let strides = spatial_param(strides, rank, "strides")?;
+        let pads = (0..rank)
+            .map(|ax| {
+                padding.compute(ax, input_shape[ax], kernel_shape[ax], dilations[ax], strides[ax])
+            })
+            .collect::<TfdResult<_>>()?;
+        Ok(Patch {
+            input_shape: input_shape.to_vec(),
+            kernel_shape: kernel_shape.to_vec(),
+            dilations,
+            strides,
+            pads,
+        })
+    }
+
+    pub fn output_shape(&self) -> Vec<usize> {
+        self.pads.iter().map(|p| p.output).collect()
+    }
+
+    /// Number of points in the window, padding included.
+    pub fn kernel_len(&self) -> usize {
+        self.kernel_shape.iter().product()
+    }
+
+    /// Calls `f` with the index of the kernel point and the input spatial
+    /// coordinates for every point of the window at `output` that falls
+    /// inside the input. Points in the padding are skipped.
+    pub fn visit<F: FnMut(usize, &[usize])>(&self, output: &[usize], mut f: F) {
+        let rank = self.input_shape.len();
+        let mut coords = vec![0; rank];
+        'kernel: for k in 0..self.kernel_len() {
+            let mut rest = k;
+            for ax in (0..rank).rev() {
+                let kx = rest % self.kernel_shape[ax];
+                rest /= self.kernel_shape[ax];
+                let x = (output[ax] * self.strides[ax] + kx * self.dilations[ax]) as isize
+                    - self.pads[ax].pad_before as isize;
+                if x < 0 || x >= self.input_shape[ax] as isize {
+                    continue 'kernel;
+                }
+                coords[ax] = x as usize;
+            }
+            f(k, &coords)
+        }
+    }
+}
+
+/// Checks a per spatial axis parameter, defaulting it to ones.
+pub fn spatial_param(values: Option<&[usize]>, rank: usize, name: &str) -> TfdResult<Vec<usize>> {
+    match values {
+        None => Ok(vec![1; rank]),
+        Some(v) if v.len() == rank && v.iter().all(|&x| x > 0) => Ok(v.to_vec()),
+        Some(v) => bail!("Invalid {} {:?} for {} spatial axes", name, v, rank),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_padding() {
+        let same = PaddingSpec::SameUpper.compute(0, 5, 2, 1, 2).unwrap();
+        assert_eq!(
+            same,
+            ComputedPaddedDim { output: 3, pad_before: 0, pad_after: 1 }
+        );
+        let same = PaddingSpec::SameLower.compute(0, 5, 2, 1, 2).unwrap();
+        assert_eq!(
+            same,
+            ComputedPaddedDim { output: 3, pad_before: 1, pad_after: 0 }
+        );
+        let valid = PaddingSpec::Valid.compute(0, 7, 3, 2, 1).unwrap();
+        assert_eq!(valid.output, 3);
+    }
+
+    #[test]
+    fn visit_skips_padding() {
+        let patch = Patch::new(
+            &PaddingSpec::Explicit(vec![1], vec![1]),
+            &[3],
+            &[3],
+            None,
+            None,
+        ).unwrap();
+        assert_eq!(patch.output_shape(), vec![3]);
+        let mut seen = vec![];
+        patch.visit(&[0], |k, x| seen.push((k, x[0])));
+        assert_eq!(seen, vec![(1, 0), (2, 1)]);
+    }
+}
@@ -0,0 +1,329 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use num::Float;
+use ops::prelude::*;
+
+use super::patches::{spatial_param, DataFormat, PaddingSpec, Patch};
+
+/// Window parameters shared by the pooling operators.
+#[derive(Debug, Clone, new)]
+pub struct PoolSpec {
+    data_format: DataFormat,
+    kernel_shape: Vec<usize>,
+    padding: PaddingSpec,
+    strides: Option<Vec<usize>>,
+}
+
+impl PoolSpec {
+    fn output_shape(&self, input: &[TDim]) -> TfdResult<Vec<TDim>> {
+        let rank = input.len();
+        let spatial = &input[self.data_format.spatial_axes(rank)];
+        if spatial.len() != self.kernel_shape.len() {
+            bail!("Pool kernel rank does not match input rank")
+        }
+        let strides = spatial_param(
+            self.strides.as_ref().map(|s| &**s),
+            spatial.len(),
+            "strides",
+        )?;
+        let output = (0..spatial.len())
+            .map(|ax| {
+                self.padding
+                    .output_dim(ax, &spatial[ax], self.kernel_shape[ax], 1, strides[ax])
+            })
+            .collect::<TfdResult<Vec<_>>>()?;
+        let channels = input[self.data_format.channel_axis(rank)];
+        Ok(self.data_format.shape(input[0], channels, &output))
+    }
+
+    /// Reduces every window with `f`, which is given the values of the
+    /// window falling inside the input and the full window size.
+    fn pool<T, F>(&self, input: ArrayViewD<T>, f: F) -> TfdResult<ArrayD<T>>
+    where
+        T: Datum + Copy,
+        F: Fn(&[T], usize) -> T,
+    {
+        let rank = input.ndim();
+        if rank < 2 {
+            bail!("Pools expect at least a batch and a channel axis")
+        }
+        let spatial = self.data_format.spatial_axes(rank);
+        let channel_axis = self.data_format.channel_axis(rank);
+        let patch = Patch::new(
+            &self.padding,
+            &input.shape()[spatial.clone()],
+            &self.kernel_shape,
+            None,
+            self.strides.as_ref().map(|s| &**s),
+        )?;
+        let shape = self.data_format.shape(
+            input.shape()[0],
+            input.shape()[channel_axis],
+            &patch.output_shape(),
+        );
+        let k_len = patch.kernel_len();
+        let mut values = Vec::with_capacity(k_len);
+        Ok(ArrayD::from_shape_fn(shape, |coords| {
+            let (n, c) = (coords[0], coords[channel_axis]);
+            values.clear();
+            patch.visit(&coords.slice()[spatial.clone()], |_, x| {
+                values.push(input[&*self.data_format.shape(n, c, x)])
+            });
+            f(&values, k_len)
+        }))
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 1)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].rank, self.kernel_shape.len() as i64 + 2)
+            .equals(&inputs[0].rank, &outputs[0].rank)
+            .given(&inputs[0].shape, move |solver, shape: Vec<TDim>| {
+                if let Ok(shape) = self.output_shape(&shape) {
+                    solver.equals(&outputs[0].shape, shape);
+                }
+            });
+    }
+}
+
+/// Takes the maximum of every window.
+#[derive(Debug, Clone, new)]
+pub struct MaxPool {
+    spec: PoolSpec,
+}
+
+impl MaxPool {
+    fn eval_t<T: Datum + Float>(&self, input: Value) -> TfdResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let output = self.spec.pool(input, |values, _| {
+            values.iter().fold(T::neg_infinity(), |a, &b| a.max(b))
+        })?;
+        Ok(output.into())
+    }
+}
+
+/// Averages every window.
+///
+/// Unless `count_include_pad` is set, the padding is left out of the average.
+#[derive(Debug, Clone, new)]
+pub struct AvgPool {
+    spec: PoolSpec,
+    count_include_pad: bool,
+}
+
+impl AvgPool {
+    fn eval_t<T: Datum + Float>(&self, input: Value) -> TfdResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let output = self.spec.pool(input, |values, window| {
+            let count = if self.count_include_pad { window } else { values.len() };
+            values.iter().fold(T::zero(), |a, &b| a + b) / T::from(count).unwrap()
+        })?;
+        Ok(output.into())
+    }
+}
+
+/// Reduces the whole spatial extent of the input, keeping the spatial axes
+/// with a length of one.
+fn global_pool<T, F>(data_format: DataFormat, input: Value, init: T, f: F) -> TfdResult<ArrayD<T>>
+where
+    T: Datum + Copy,
+    F: Fn(T, T) -> T,
+{
+    let input = input.to_array_view::<T>()?;
+    let rank = input.ndim();
+    if rank < 2 {
+        bail!("Pools expect at least a batch and a channel axis")
+    }
+    let mut output = input.to_owned();
+    for axis in data_format.spatial_axes(rank) {
+        output = output
+            .fold_axis(Axis(axis), init, |&a, &b| f(a, b))
+            .insert_axis(Axis(axis));
+    }
+    Ok(output)
+}
+
+/// Takes the maximum over all the spatial axes.
+#[derive(Debug, Clone, new)]
+pub struct GlobalMaxPool {
+    data_format: DataFormat,
+}
+
+impl GlobalMaxPool {
+    fn eval_t<T: Datum + Float>(&self, input: Value) -> TfdResult<Tensor> {
+        Ok(global_pool(self.data_format, input, T::neg_infinity(), T::max)?.into())
+    }
+}
+
+/// Averages over all the spatial axes.
+#[derive(Debug, Clone, new)]
+pub struct GlobalAvgPool {
+    data_format: DataFormat,
+}
+
+impl GlobalAvgPool {
+    fn eval_t<T: Datum + Float>(&self, input: Value) -> TfdResult<Tensor> {
+        let rank = input.shape().len();
+        let count: usize = input.shape()[self.data_format.spatial_axes(rank)]
+            .iter()
+            .product();
+        let sum = global_pool(self.data_format, input, T::zero(), |a, b| a + b)?;
+        let count = T::from(count).unwrap();
+        Ok(sum.mapv(|x| x / count).into())
+    }
+}
+
+fn global_rules<'r, 'p: 'r, 's: 'r>(
+    data_format: DataFormat,
+    solver: &mut Solver<'r>,
+    inputs: &'p TensorsProxy,
+    outputs: &'p TensorsProxy,
+) {
+    solver
+        .equals(&inputs.len, 1)
+        .equals(&outputs.len, 1)
+        .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+        .equals(&inputs[0].rank, &outputs[0].rank)
+        .equals(&inputs[0].shape[0], &outputs[0].shape[0])
+        .given(&inputs[0].rank, move |solver, rank: i64| {
+            let rank = rank as usize;
+            let channel_axis = data_format.channel_axis(rank);
+            solver.equals(
+                &inputs[0].shape[channel_axis],
+                &outputs[0].shape[channel_axis],
+            );
+            for axis in data_format.spatial_axes(rank) {
+                solver.equals(&outputs[0].shape[axis], 1.to_dim());
+            }
+        });
+}
+
+macro_rules! pool_op {
+    ($Name:ident) => {
+        impl Op for $Name {
+            /// Evaluates the operation given the input tensors.
+            fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+                let input = args_1!(inputs);
+                let output = match input.datum_type() {
+                    DatumType::F16 => return ::ops::eval_f16_as_f32(self, tvec![input]),
+                    DatumType::F32 => self.eval_t::<f32>(input)?,
+                    DatumType::F64 => self.eval_t::<f64>(input)?,
+                    dt => bail!("{} not covering {:?}", stringify!($Name), dt),
+                };
+                Ok(tvec![output.into()])
+            }
+        }
+    };
+}
+
+pool_op!(MaxPool);
+pool_op!(AvgPool);
+pool_op!(GlobalMaxPool);
+pool_op!(GlobalAvgPool);
+
+impl InferenceRulesOp for MaxPool {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        self.spec.rules(solver, inputs, outputs)
+    }
+}
+
+impl InferenceRulesOp for AvgPool {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        self.spec.rules(solver, inputs, outputs)
+    }
+}
+
+impl InferenceRulesOp for GlobalMaxPool {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        global_rules(self.data_format, solver, inputs, outputs)
+    }
+}
+
+impl InferenceRulesOp for GlobalAvgPool {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        global_rules(self.data_format, solver, inputs, outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(op: &Op, input: Tensor) -> Tensor {
+        op.eval(tvec![input.into()]).unwrap().remove(0).into_tensor()
+    }
+
+    #[test]
+    fn max_pool_same() {
+        let spec = PoolSpec::new(
+            DataFormat::NHWC,
+            vec![3, 3],
+            PaddingSpec::SameUpper,
+            Some(vec![3, 3]),
+        );
+        let input = Tensor::f32s(&[1, 2, 4, 1], &[1., 0., 0., 0., 0., 0., 0., -1.]).unwrap();
+        assert_eq!(
+            pool(&MaxPool::new(spec), input),
+            Tensor::f32s(&[1, 1, 2, 1], &[1., 0.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn avg_pool_count_include_pad() {
+        let spec = PoolSpec::new(
+            DataFormat::NCHW,
+            vec![2],
+            PaddingSpec::Explicit(vec![1], vec![0]),
+            Some(vec![2]),
+        );
+        let input = Tensor::f32s(&[1, 1, 3], &[2., 4., 6.]).unwrap();
+        assert_eq!(
+            pool(&AvgPool::new(spec.clone(), false), input.clone()),
+            Tensor::f32s(&[1, 1, 2], &[2., 5.]).unwrap()
+        );
+        assert_eq!(
+            pool(&AvgPool::new(spec, true), input),
+            Tensor::f32s(&[1, 1, 2], &[1., 5.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn global_pools() {
+        let input = Tensor::f32s(&[1, 2, 2, 1], &[1., 2., 3., 6.]).unwrap();
+        assert_eq!(
+            pool(&GlobalAvgPool::new(DataFormat::NCHW), input.clone()),
+            Tensor::f32s(&[1, 2, 1, 1], &[1.5, 4.5]).unwrap()
+        );
+        assert_eq!(
+            pool(&GlobalMaxPool::new(DataFormat::NCHW), input),
+            Tensor::f32s(&[1, 2, 1, 1], &[2., 6.]).unwrap()
+        );
+    }
+}
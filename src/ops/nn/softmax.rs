@@ -0,0 +1,183 @@
+use analyser::rules::prelude::*;
+use ndarray::prelude::*;
+use num::Float;
+use ops::prelude::*;
+
+/// Resolves the axes a softmax normalizes over for a given input rank.
+///
+/// With `coerce_2d`, the input is seen as a matrix whose rows start at
+/// `axis`, as ONNX does, so all the axes from `axis` on are normalized
+/// together.
+fn softmax_axes(axis: i64, coerce_2d: bool, rank: usize) -> TfdResult<Vec<usize>> {
+    let fixed = if axis < 0 { axis + rank as i64 } else { axis };
+    if fixed < 0 || fixed > rank as i64 || (!coerce_2d && fixed == rank as i64) {
+        bail!("Invalid softmax axis {} for rank {}", axis, rank)
+    }
+    if coerce_2d {
+        Ok((fixed as usize..rank).collect())
+    } else {
+        Ok(vec![fixed as usize])
+    }
+}
+
+fn softmax<T: Datum + Float>(input: ArrayViewD<T>, axes: &[usize], log: bool) -> ArrayD<T> {
+    let mut max = input.to_owned();
+    for &axis in axes {
+        max = max
+            .fold_axis(Axis(axis), T::neg_infinity(), |&a, &b| a.max(b))
+            .insert_axis(Axis(axis));
+    }
+    let shifted = &input - &max;
+    let exp = shifted.mapv(|x| x.exp());
+    let mut sum = exp.clone();
+    for &axis in axes {
+        sum = sum
+            .fold_axis(Axis(axis), T::zero(), |&a, &b| a + b)
+            .insert_axis(Axis(axis));
+    }
+    if log {
+        shifted - &sum.mapv(|x| x.ln())
+    } else {
+        exp / &sum
+    }
+}
+
+macro_rules! softmax_op {
+    ($Name:ident, $log:expr, $doc:expr) => {
+        #[doc=$doc]
+        #[derive(Debug, Clone, new)]
+        pub struct $Name {
+            axis: i64,
+            coerce_2d: bool,
+        }
+
+        impl $Name {
+            fn compute(&self, input: Value, stream_axis: Option<usize>) -> TfdResult<Value> {
+                let axes = softmax_axes(self.axis, self.coerce_2d, input.shape().len())?;
+                if let Some(stream_axis) = stream_axis {
+                    if axes.contains(&stream_axis) {
+                        bail!("{} can not stream along its own axis", stringify!($Name))
+                    }
+                }
+                let output: Tensor = match input.datum_type() {
+                    DatumType::F32 => softmax(input.to_array_view::<f32>()?, &axes, $log).into(),
+                    DatumType::F64 => softmax(input.to_array_view::<f64>()?, &axes, $log).into(),
+                    DatumType::F16 => {
+                        let input = input.to_array_view::<f16>()?.mapv(|x| x.to_f32());
+                        softmax(input.view(), &axes, $log).mapv(f16::from_f32).into()
+                    }
+                    dt => bail!("{} not covering {:?}", stringify!($Name), dt),
+                };
+                Ok(output.into())
+            }
+        }
+
+        impl Op for $Name {
+            /// Evaluates the operation given the input tensors.
+            fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+                let input = args_1!(inputs);
+                Ok(tvec![self.compute(input, None)?])
+            }
+
+            /// Evaluates one step of the operation on the given input tensors.
+            fn step(
+                &self,
+                mut inputs: TVec<StepValue>,
+                _buffer: &mut Box<OpBuffer>,
+            ) -> TfdResult<Option<TVec<Value>>> {
+                let input = args_1!(inputs);
+                if let Some(Stream {
+                    info,
+                    chunk: Some(chunk),
+                    ..
+                }) = input.into_stream()
+                {
+                    Ok(Some(tvec![self.compute(chunk, Some(info.axis))?]))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+
+        impl InferenceRulesOp for $Name {
+            fn rules<'r, 'p: 'r, 's: 'r>(
+                &'s self,
+                solver: &mut Solver<'r>,
+                inputs: &'p TensorsProxy,
+                outputs: &'p TensorsProxy,
+            ) {
+                solver
+                    .equals(&inputs.len, 1)
+                    .equals(&outputs.len, 1)
+                    .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+                    .equals(&inputs[0].shape, &outputs[0].shape);
+            }
+        }
+    };
+}
+
+softmax_op!(
+    Softmax,
+    false,
+    "Exponentiates its input and normalizes it along an axis.\n\n\
+     The input is shifted by its maximum first, so large values do not \
+     overflow."
+);
+softmax_op!(
+    LogSoftmax,
+    true,
+    "Logarithm of the softmax of its input, computed without going through \
+     the exponentiated values."
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn softmax_last_axis() {
+        let input = Tensor::f32s(&[2, 2], &[0., 0., 1000., 1000. + 2f32.ln()]).unwrap();
+        let output = Softmax::new(-1, false).eval(tvec![input.into()]).unwrap();
+        assert!(output[0].close_enough(
+            &Tensor::f32s(&[2, 2], &[0.5, 0.5, 1. / 3., 2. / 3.]).unwrap(),
+            true
+        ));
+    }
+
+    #[test]
+    fn softmax_coerce_2d() {
+        let input = Tensor::f64s(&[1, 2, 2], &[0., 0., 0., 0.]).unwrap();
+        let output = Softmax::new(1, true).eval(tvec![input.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::f64s(&[1, 2, 2], &[0.25; 4]).unwrap()
+        );
+    }
+
+    #[test]
+    fn log_softmax() {
+        let input = Tensor::f32s(&[3], &[1., 1., 1.]).unwrap();
+        let output = LogSoftmax::new(0, false).eval(tvec![input.into()]).unwrap();
+        assert!(output[0].close_enough(
+            &Tensor::f32s(&[3], &[-(3f32.ln()); 3]).unwrap(),
+            true
+        ));
+    }
+
+    #[test]
+    fn softmax_stream_other_axis() {
+        let op = Softmax::new(1, false);
+        let chunk = Tensor::f32s(&[1, 2], &[0., 0.]).unwrap();
+        let stream = |chunk: &Tensor, axis| {
+            tvec![StepValue::Stream(Stream {
+                info: StreamInfo { axis, len: TDim::stream() },
+                offset: 0,
+                chunk: Some(chunk.clone().into()),
+            })]
+        };
+        let mut buffer = op.new_buffer();
+        let output = op.step(stream(&chunk, 0), &mut buffer).unwrap().unwrap();
+        assert_eq!(output[0].as_tensor(), &Tensor::f32s(&[1, 2], &[0.5, 0.5]).unwrap());
+        assert!(op.step(stream(&chunk, 1), &mut buffer).is_err());
+    }
+}
@@ -24,12 +24,7 @@ impl InferenceRulesOp for Sink {
         solver
             .equals(&inputs.len, 1)
             .equals(&outputs.len, 0);
-        if let GenericFact::Only(dt) = self.fact.datum_type {
-            solver.equals(&inputs[0].datum_type, dt);
-        }
-        if let Some(shape) = self.fact.shape.concretize() {
-            solver.equals(&inputs[0].shape, shape);
-        }
+        super::source::interface_rules(solver, &inputs[0], &self.fact);
     }
 }
 
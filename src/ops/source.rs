@@ -24,11 +24,32 @@ impl InferenceRulesOp for Source {
         solver
             .equals(&inputs.len, 0)
             .equals(&outputs.len, 1);
-        if let GenericFact::Only(dt) = self.fact.datum_type {
-            solver.equals(&outputs[0].datum_type, dt);
-        }
-        if let Some(shape) = self.fact.shape.concretize() {
-            solver.equals(&outputs[0].shape, shape);
+        interface_rules(solver, &outputs[0], &self.fact);
+    }
+}
+
+/// Binds a tensor of the model interface to the fact the model declares.
+///
+/// Dimensions depending on symbols of the model, like ONNX `dim_param`s, are
+/// only known at runtime: the analyser does not reason on them, they are
+/// left for the hints to fill, and dimensions sharing a symbol are not
+/// unified.
+pub fn interface_rules<'r, 'p: 'r>(
+    solver: &mut Solver<'r>,
+    tensor: &'p TensorProxy,
+    fact: &TensorFact,
+) {
+    if let GenericFact::Only(dt) = fact.datum_type {
+        solver.equals(&tensor.datum_type, dt);
+    }
+    if !fact.shape.open {
+        solver.equals(&tensor.rank, fact.shape.dims.len() as i64);
+    }
+    for (axis, dim) in fact.shape.dims.iter().enumerate() {
+        if let GenericFact::Only(dim) = dim {
+            if dim.symbols().iter().all(|&s| s == 'S') {
+                solver.equals(&tensor.shape[axis], *dim);
+            }
         }
     }
 }
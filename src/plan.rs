@@ -1,4 +1,6 @@
+use std::fs;
 use std::ops::Deref;
+use std::path::Path;
 use std::sync::Arc;
 
 use model::{eval_order_for_nodes, Model, Node, TVec};
@@ -78,11 +80,91 @@ impl SimplePlan {
         state.take_outputs()
     }
 
+    /// Runs the plan in debug mode, checking every float output.
+    ///
+    /// Stops at the first node producing a value rejected by `checks`.
+    pub fn run_checked(
+        &self,
+        inputs: TVec<Tensor>,
+        checks: &ValueChecks,
+    ) -> TfdResult<Vec<TVec<Tensor>>> {
+        let mut state = SimpleState::new(&self)?;
+        state.set_inputs(inputs)?;
+        for &n in &self.order {
+            if state.values[n].is_none() {
+                state.compute_one_checked(n, checks)?;
+            }
+        }
+        state.take_outputs()
+    }
+
     pub fn state(&self) -> TfdResult<SimpleState> {
         SimpleState::new(self)
     }
 }
 
+/// Sanity checks applied to float outputs in debug execution mode.
+///
+/// NaN and infinite values are always rejected.
+#[derive(Debug, Clone, Default)]
+pub struct ValueChecks {
+    /// Also reject finite values whose magnitude exceeds this threshold.
+    pub max_abs: Option<f64>,
+}
+
+impl ValueChecks {
+    /// Returns a description of the first offending value, if any.
+    pub fn check(&self, tensor: &Tensor) -> Option<String> {
+        let values: Vec<f64> = match tensor {
+            Tensor::F16(it) => it.iter().map(|&x| x.to_f64()).collect(),
+            Tensor::F32(it) => it.iter().map(|&x| x as f64).collect(),
+            Tensor::F64(it) => it.iter().cloned().collect(),
+            _ => return None,
+        };
+        for (ix, &v) in values.iter().enumerate() {
+            if v.is_nan() {
+                return Some(format!("NaN at index {}", ix));
+            }
+            if v.is_infinite() {
+                return Some(format!("{} at index {}", v, ix));
+            }
+            if let Some(max) = self.max_abs {
+                if v.abs() > max {
+                    return Some(format!("{} exceeds {} at index {}", v, max, ix));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Summarizes a tensor for error reports: type, shape and, for floats,
+/// min, max, mean and the count of NaN and infinite values.
+fn tensor_stats(tensor: &Tensor) -> String {
+    let values: Vec<f64> = match tensor {
+        Tensor::F16(it) => it.iter().map(|&x| x.to_f64()).collect(),
+        Tensor::F32(it) => it.iter().map(|&x| x as f64).collect(),
+        Tensor::F64(it) => it.iter().cloned().collect(),
+        _ => return format!("{:?} {:?}", tensor.datum_type(), tensor.shape()),
+    };
+    let nans = values.iter().filter(|v| v.is_nan()).count();
+    let infs = values.iter().filter(|v| v.is_infinite()).count();
+    let finite: Vec<f64> = values.into_iter().filter(|v| v.is_finite()).collect();
+    let min = finite.iter().cloned().fold(::std::f64::INFINITY, f64::min);
+    let max = finite.iter().cloned().fold(::std::f64::NEG_INFINITY, f64::max);
+    let mean = finite.iter().sum::<f64>() / finite.len() as f64;
+    format!(
+        "{:?} {:?} min:{} max:{} mean:{} NaN:{} Inf:{}",
+        tensor.datum_type(),
+        tensor.shape(),
+        min,
+        max,
+        mean,
+        nans,
+        infs
+    )
+}
+
 #[derive(Clone, Debug)]
 pub struct SimpleState {
     plan: SimplePlan,
@@ -161,6 +243,39 @@ impl SimpleState {
         Ok(())
     }
 
+    /// Computes one node, then checks its float outputs against `checks`.
+    ///
+    /// On failure, the error names the node, its op and gives statistics
+    /// about its inputs.
+    pub fn compute_one_checked(&mut self, node: usize, checks: &ValueChecks) -> TfdResult<()> {
+        self.compute_one(node)?;
+        let node: &Node = &self.plan.model.nodes()[node];
+        let outputs = self.values[node.id].as_ref().unwrap();
+        for (slot, output) in outputs.iter().enumerate() {
+            if let Some(problem) = checks.check(output) {
+                let inputs = node
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, i)| {
+                        let value = &self.values[i.node].as_ref().unwrap()[i.slot];
+                        format!("  input #{}: {}", ix, tensor_stats(value))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                bail!(
+                    "Node {} ({}) output #{}: {}\n{}",
+                    node.name,
+                    node.op_name,
+                    slot,
+                    problem,
+                    inputs
+                );
+            }
+        }
+        Ok(())
+    }
+
     pub fn compute_recursively(&mut self, node: usize) -> TfdResult<()> {
         let precs: Vec<usize> = self.plan.model.nodes()[node]
             .inputs
@@ -196,7 +311,145 @@ impl SimpleState {
             .collect())
     }
 
+    /// Writes computed values to `dir`, one text file per outlet.
+    ///
+    /// If `nodes` is given, only these nodes are dumped and they must have
+    /// been computed. Otherwise every computed value is dumped, skipping the
+    /// ones that have no text representation. Files are named by
+    /// `dump_filename` and can be loaded back by `tfdeploy-cli` as `@file`.
+    pub fn dump_values<P: AsRef<Path>>(&self, dir: P, nodes: Option<&[&str]>) -> TfdResult<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let ids: Vec<usize> = match nodes {
+            Some(names) => names
+                .iter()
+                .map(|n| Ok(self.model().node_by_name(n)?.id))
+                .collect::<TfdResult<_>>()?,
+            None => (0..self.values.len()).filter(|&id| self.values[id].is_some()).collect(),
+        };
+        for id in ids {
+            let node = &self.model().nodes()[id];
+            let values = self.values[id]
+                .as_ref()
+                .ok_or_else(|| format!("Value for {} is not computed", node.name))?;
+            for (slot, value) in values.iter().enumerate() {
+                let dt = value.datum_type();
+                if nodes.is_none()
+                    && [::DatumType::String, ::DatumType::ComplexF32, ::DatumType::ComplexF64]
+                        .contains(&dt)
+                {
+                    debug!("Skipping dump of {:?} value {}:{}", dt, node.name, slot);
+                    continue;
+                }
+                // Written in memory first, not to leave a partial file behind.
+                let mut text = vec![];
+                value
+                    .write_text(&mut text)
+                    .map_err(|e| format!("Dumping {}:{}, {}", node.name, slot, e))?;
+                fs::write(dir.join(dump_filename(&node.name, slot)), text)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn model(&self) -> &Model {
         &self.plan.model
     }
 }
+
+/// Name of the file holding outlet `slot` of `node` in a value dump.
+///
+/// Characters that are not safe in file names (like the `/` of scoped
+/// Tensorflow names) are replaced by `_`.
+pub fn dump_filename(node: &str, slot: usize) -> String {
+    let node: String = node
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect();
+    format!("{}.{}.txt", node, slot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use analyser::TensorFact;
+    use model::RawModel;
+    use ops;
+
+    fn ln_model() -> Model {
+        let nodes = vec![
+            Node {
+                id: 0,
+                name: "x".to_string(),
+                op_name: "Source".to_string(),
+                inputs: vec![],
+                control_inputs: vec![],
+                op: Box::new(ops::source::Source::new(TensorFact::default())),
+            },
+            Node {
+                id: 1,
+                name: "ln".to_string(),
+                op_name: "Ln".to_string(),
+                inputs: vec![::model::OutletId::new(0, 0)],
+                control_inputs: vec![],
+                op: Box::new(ops::math::Ln::default()),
+            },
+        ];
+        let names = hashmap!("x".to_string() => 0, "ln".to_string() => 1);
+        Model(Arc::new(RawModel::new(nodes, names)))
+    }
+
+    #[test]
+    fn dump_values_as_text() {
+        let plan = SimplePlan::new(&ln_model(), &["x"], &["ln"]).unwrap();
+        let mut state = plan.state().unwrap();
+        state.set_input(0, Tensor::f32s(&[2], &[1.0, 4.0]).unwrap()).unwrap();
+        state.compute_one(1).unwrap();
+        let dir = ::std::env::temp_dir().join(format!("tfd-dump-{}", ::std::process::id()));
+        state.dump_values(&dir, None).unwrap();
+        let x = fs::read_to_string(dir.join(dump_filename("x", 0))).unwrap();
+        let ln = fs::read_to_string(dir.join(dump_filename("ln", 0))).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(x, "2xf32\n1 4\n");
+        assert_eq!(ln, format!("2xf32\n0 {}\n", 4f32.ln()));
+    }
+
+    #[test]
+    fn dump_values_without_text_form() {
+        let plan = SimplePlan::new(&ln_model(), &["x"], &["ln"]).unwrap();
+        let mut state = plan.state().unwrap();
+        let complex = Tensor::complex_f32s(&[1], &[::num::Complex::new(0.0, 1.0)]).unwrap();
+        state.set_input(0, complex).unwrap();
+        let dir = ::std::env::temp_dir().join(format!("tfd-dump-complex-{}", ::std::process::id()));
+        state.dump_values(&dir, None).unwrap();
+        assert!(state.dump_values(&dir, Some(&["x"])).is_err());
+        let files = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files, 0);
+    }
+
+    #[test]
+    fn checked_run_passes() {
+        let plan = SimplePlan::new(&ln_model(), &["x"], &["ln"]).unwrap();
+        let input = Tensor::f32s(&[2], &[1.0, 2.0]).unwrap();
+        let checks = ValueChecks::default();
+        assert!(plan.run_checked(tvec![input], &checks).is_ok());
+    }
+
+    #[test]
+    fn checked_run_catches_nan() {
+        let plan = SimplePlan::new(&ln_model(), &["x"], &["ln"]).unwrap();
+        let input = Tensor::f32s(&[2], &[1.0, -2.0]).unwrap();
+        let checks = ValueChecks::default();
+        let err = plan.run_checked(tvec![input], &checks).unwrap_err();
+        assert!(err.to_string().starts_with("Node ln (Ln) output #0: NaN at index 1"));
+    }
+
+    #[test]
+    fn checked_run_catches_threshold() {
+        let plan = SimplePlan::new(&ln_model(), &["x"], &["ln"]).unwrap();
+        let input = Tensor::f32s(&[2], &[1.0, 1e6]).unwrap();
+        let checks = ValueChecks { max_abs: Some(10.0) };
+        assert!(plan.run_checked(tvec![input], &checks).is_err());
+    }
+}
@@ -1,9 +1,12 @@
 //! `Tensor` is the equivalent of Tensorflow Tensor.
 use dim::TDim;
 use ndarray::prelude::*;
-use std::fmt;
+use std::{fmt, io};
 use TfdResult;
 
+pub use half::f16;
+pub use num::Complex;
+
 #[cfg(feature = "serialize")]
 use serde::ser::{Serialize, Serializer};
 
@@ -13,12 +16,17 @@ pub enum DatumType {
     Bool,
     U8,
     U16,
+    U32,
+    U64,
     I8,
     I16,
     I32,
     I64,
+    F16,
     F32,
     F64,
+    ComplexF32,
+    ComplexF64,
     TDim,
     String,
 }
@@ -27,15 +35,35 @@ impl DatumType {
     pub fn super_types(&self) -> &'static [DatumType] {
         match self {
             DatumType::Bool => &[DatumType::Bool],
-            DatumType::U8 => &[DatumType::U8, DatumType::I16, DatumType::I32, DatumType::I64,
-DatumType::TDim],
-            DatumType::U16 => &[DatumType::U16, DatumType::I32, DatumType::I64, DatumType::TDim],
+            DatumType::U8 => &[
+                DatumType::U8,
+                DatumType::U16,
+                DatumType::I16,
+                DatumType::U32,
+                DatumType::I32,
+                DatumType::U64,
+                DatumType::I64,
+                DatumType::TDim,
+            ],
+            DatumType::U16 => &[
+                DatumType::U16,
+                DatumType::U32,
+                DatumType::I32,
+                DatumType::U64,
+                DatumType::I64,
+                DatumType::TDim,
+            ],
+            DatumType::U32 => &[DatumType::U32, DatumType::U64, DatumType::I64, DatumType::TDim],
+            DatumType::U64 => &[DatumType::U64],
             DatumType::I8 => &[DatumType::I8, DatumType::I16, DatumType::I32, DatumType::I64, DatumType::TDim],
             DatumType::I16 => &[DatumType::I16, DatumType::I32, DatumType::I64, DatumType::TDim],
             DatumType::I32 => &[DatumType::I32, DatumType::I64, DatumType::TDim],
             DatumType::I64 => &[DatumType::I64, DatumType::TDim],
+            DatumType::F16 => &[DatumType::F16, DatumType::F32, DatumType::F64],
             DatumType::F32 => &[DatumType::F32, DatumType::F64],
             DatumType::F64 => &[DatumType::F64],
+            DatumType::ComplexF32 => &[DatumType::ComplexF32],
+            DatumType::ComplexF64 => &[DatumType::ComplexF64],
             DatumType::String => &[DatumType::String],
             DatumType::TDim => &[DatumType::TDim],
         }
@@ -92,8 +120,7 @@ impl<'a, T: Clone + 'a> MaybeOwnedArray<'a, T> {
 }
 
 pub trait Datum:
-    Copy
-    + Clone
+    Clone
     + Send
     + Sync
     + fmt::Debug
@@ -120,8 +147,13 @@ pub enum Tensor {
     I64(ArrayD<i64>),
     U8(ArrayD<u8>),
     U16(ArrayD<u16>),
+    U32(ArrayD<u32>),
+    U64(ArrayD<u64>),
+    F16(ArrayD<f16>),
+    ComplexF32(ArrayD<Complex<f32>>),
+    ComplexF64(ArrayD<Complex<f64>>),
     TDim(ArrayD<TDim>),
-    String(ArrayD<i8>),
+    String(ArrayD<Vec<u8>>),
 }
 
 impl Tensor {
@@ -140,6 +172,11 @@ impl Tensor {
             &Tensor::Bool(ref it) => it.shape(),
             &Tensor::U8(ref it) => it.shape(),
             &Tensor::U16(ref it) => it.shape(),
+            &Tensor::U32(ref it) => it.shape(),
+            &Tensor::U64(ref it) => it.shape(),
+            &Tensor::F16(ref it) => it.shape(),
+            &Tensor::ComplexF32(ref it) => it.shape(),
+            &Tensor::ComplexF64(ref it) => it.shape(),
             &Tensor::I8(ref it) => it.shape(),
             &Tensor::I16(ref it) => it.shape(),
             &Tensor::I32(ref it) => it.shape(),
@@ -168,6 +205,11 @@ impl Tensor {
             &Tensor::Bool(_) => DatumType::Bool,
             &Tensor::U8(_) => DatumType::U8,
             &Tensor::U16(_) => DatumType::U16,
+            &Tensor::U32(_) => DatumType::U32,
+            &Tensor::U64(_) => DatumType::U64,
+            &Tensor::F16(_) => DatumType::F16,
+            &Tensor::ComplexF32(_) => DatumType::ComplexF32,
+            &Tensor::ComplexF64(_) => DatumType::ComplexF64,
             &Tensor::I8(_) => DatumType::I8,
             &Tensor::I16(_) => DatumType::I16,
             &Tensor::I32(_) => DatumType::I32,
@@ -184,6 +226,11 @@ impl Tensor {
             &Tensor::Bool(_) => self.axis_chunks_t::<bool>(axis, size),
             &Tensor::U8(_) => self.axis_chunks_t::<u8>(axis, size),
             &Tensor::U16(_) => self.axis_chunks_t::<u16>(axis, size),
+            &Tensor::U32(_) => self.axis_chunks_t::<u32>(axis, size),
+            &Tensor::U64(_) => self.axis_chunks_t::<u64>(axis, size),
+            &Tensor::F16(_) => self.axis_chunks_t::<f16>(axis, size),
+            &Tensor::ComplexF32(_) => self.axis_chunks_t::<Complex<f32>>(axis, size),
+            &Tensor::ComplexF64(_) => self.axis_chunks_t::<Complex<f64>>(axis, size),
             &Tensor::I8(_) => self.axis_chunks_t::<i8>(axis, size),
             &Tensor::I16(_) => self.axis_chunks_t::<i16>(axis, size),
             &Tensor::I32(_) => self.axis_chunks_t::<i32>(axis, size),
@@ -191,7 +238,7 @@ impl Tensor {
             &Tensor::F32(_) => self.axis_chunks_t::<f32>(axis, size),
             &Tensor::F64(_) => self.axis_chunks_t::<f64>(axis, size),
             &Tensor::TDim(_) => self.axis_chunks_t::<TDim>(axis, size),
-            &Tensor::String(_) => bail!("String is not a datum")
+            &Tensor::String(_) => self.axis_chunks_t::<Vec<u8>>(axis, size),
         }
     }
 
@@ -225,18 +272,27 @@ impl Tensor {
         macro_rules! fmt_small {
             ($a:ident) => { format!("{:?} {:?}", self.datum_type(), $a).replace("\n", " ") }
         }
+        let lossy = |a: &ArrayD<Vec<u8>>| a.map(|s| String::from_utf8_lossy(s).into_owned());
         if self.shape().len() == 0 {
             Ok(match self {
                 &Tensor::Bool(ref a) => fmt_scalar!(a),
                 &Tensor::U8(ref a) => fmt_scalar!(a),
                 &Tensor::U16(ref a) => fmt_scalar!(a),
+                &Tensor::U32(ref a) => fmt_scalar!(a),
+                &Tensor::U64(ref a) => fmt_scalar!(a),
+                &Tensor::F16(ref a) => fmt_scalar!(a),
+                &Tensor::ComplexF32(ref a) => fmt_scalar!(a),
+                &Tensor::ComplexF64(ref a) => fmt_scalar!(a),
                 &Tensor::I8(ref a) => fmt_scalar!(a),
                 &Tensor::I16(ref a) => fmt_scalar!(a),
                 &Tensor::I32(ref a) => fmt_scalar!(a),
                 &Tensor::I64(ref a) => fmt_scalar!(a),
                 &Tensor::F32(ref a) => fmt_scalar!(a),
                 &Tensor::F64(ref a) => fmt_scalar!(a),
-                &Tensor::String(ref a) => fmt_scalar!(a),
+                &Tensor::String(ref a) => {
+                    let a = lossy(a);
+                    fmt_scalar!(a)
+                }
                 &Tensor::TDim(ref a) => fmt_scalar!(a),
             })
         } else if self.shape().iter().product::<usize>() > 8 {
@@ -245,13 +301,21 @@ impl Tensor {
                 &Tensor::Bool(ref a) => fmt_trunc!(a),
                 &Tensor::U8(ref a) => fmt_trunc!(a),
                 &Tensor::U16(ref a) => fmt_trunc!(a),
+                &Tensor::U32(ref a) => fmt_trunc!(a),
+                &Tensor::U64(ref a) => fmt_trunc!(a),
+                &Tensor::F16(ref a) => fmt_trunc!(a),
+                &Tensor::ComplexF32(ref a) => fmt_trunc!(a),
+                &Tensor::ComplexF64(ref a) => fmt_trunc!(a),
                 &Tensor::I8(ref a) => fmt_trunc!(a),
                 &Tensor::I16(ref a) => fmt_trunc!(a),
                 &Tensor::I32(ref a) => fmt_trunc!(a),
                 &Tensor::I64(ref a) => fmt_trunc!(a),
                 &Tensor::F32(ref a) => fmt_trunc!(a),
                 &Tensor::F64(ref a) => fmt_trunc!(a),
-                &Tensor::String(ref a) => fmt_trunc!(a),
+                &Tensor::String(ref a) => {
+                    let a = lossy(a);
+                    fmt_trunc!(a)
+                }
                 &Tensor::TDim(ref a) => fmt_trunc!(a),
             })
         } else {
@@ -259,23 +323,83 @@ impl Tensor {
                 &Tensor::Bool(ref a) => fmt_small!(a),
                 &Tensor::U8(ref a) => fmt_small!(a),
                 &Tensor::U16(ref a) => fmt_small!(a),
+                &Tensor::U32(ref a) => fmt_small!(a),
+                &Tensor::U64(ref a) => fmt_small!(a),
+                &Tensor::F16(ref a) => fmt_small!(a),
+                &Tensor::ComplexF32(ref a) => fmt_small!(a),
+                &Tensor::ComplexF64(ref a) => fmt_small!(a),
                 &Tensor::I8(ref a) => fmt_small!(a),
                 &Tensor::I16(ref a) => fmt_small!(a),
                 &Tensor::I32(ref a) => fmt_small!(a),
                 &Tensor::I64(ref a) => fmt_small!(a),
                 &Tensor::F32(ref a) => fmt_small!(a),
                 &Tensor::F64(ref a) => fmt_small!(a),
-                &Tensor::String(ref a) => fmt_small!(a),
+                &Tensor::String(ref a) => {
+                    let a = lossy(a);
+                    fmt_small!(a)
+                }
                 &Tensor::TDim(ref a) => fmt_small!(a),
             })
         }
     }
 
+    /// Writes the tensor in the text format read by `tfdeploy-cli`.
+    ///
+    /// The first line is the shape and type (`2x3xf32`, or just `f32` for a
+    /// scalar), followed by whitespace separated values, one line per
+    /// innermost row. TDim values must be concrete and are written as i64.
+    pub fn write_text<W: io::Write>(&self, w: &mut W) -> TfdResult<()> {
+        macro_rules! write_rows {
+            ($t:expr, $a:expr) => {{
+                let mut header: Vec<String> = self.shape().iter().map(|d| d.to_string()).collect();
+                header.push($t.to_string());
+                writeln!(w, "{}", header.join("x"))?;
+                let row = self.shape().last().cloned().unwrap_or(1).max(1);
+                for chunk in $a.iter().collect::<Vec<_>>().chunks(row) {
+                    let line: Vec<String> = chunk.iter().map(|v| v.to_string()).collect();
+                    writeln!(w, "{}", line.join(" "))?;
+                }
+            }};
+        }
+        match self {
+            &Tensor::Bool(ref a) => write_rows!("bool", a),
+            &Tensor::U8(ref a) => write_rows!("u8", a),
+            &Tensor::U16(ref a) => write_rows!("u16", a),
+            &Tensor::U32(ref a) => write_rows!("u32", a),
+            &Tensor::U64(ref a) => write_rows!("u64", a),
+            &Tensor::F16(ref a) => write_rows!("f16", a),
+            &Tensor::I8(ref a) => write_rows!("i8", a),
+            &Tensor::I16(ref a) => write_rows!("i16", a),
+            &Tensor::I32(ref a) => write_rows!("i32", a),
+            &Tensor::I64(ref a) => write_rows!("i64", a),
+            &Tensor::F32(ref a) => write_rows!("f32", a),
+            &Tensor::F64(ref a) => write_rows!("f64", a),
+            &Tensor::TDim(ref a) => {
+                let a = a
+                    .iter()
+                    .map(|d| d.to_integer())
+                    .collect::<TfdResult<Vec<i64>>>()?;
+                write_rows!("i64", a)
+            }
+            &Tensor::ComplexF32(_) | &Tensor::ComplexF64(_) | &Tensor::String(_) => {
+                bail!("{:?} tensors can not be written as text", self.datum_type())
+            }
+        }
+        Ok(())
+    }
+
     fn approx(&self) -> ArrayD<f32> {
         match self {
             &Tensor::Bool(ref data) => data.map(|&a| a as u32 as f32),
             &Tensor::U8(ref data) => data.map(|&a| a as f32),
             &Tensor::U16(ref data) => data.map(|&a| a as f32),
+            &Tensor::U32(ref data) => data.map(|&a| a as f32),
+            &Tensor::U64(ref data) => data.map(|&a| a as f32),
+            &Tensor::F16(ref data) => data.map(|&a| a.to_f32()),
+            &Tensor::ComplexF32(ref data) => Self::approx_complex(data, |c| (c.re, c.im)),
+            &Tensor::ComplexF64(ref data) => {
+                Self::approx_complex(data, |c| (c.re as f32, c.im as f32))
+            }
             &Tensor::I8(ref data) => data.map(|&a| a as f32),
             &Tensor::I16(ref data) => data.map(|&a| a as f32),
             &Tensor::I32(ref data) => data.map(|&a| a as f32),
@@ -287,7 +411,24 @@ impl Tensor {
         }
     }
 
+    /// Flattens complex values to (real, imaginary) pairs along a new
+    /// innermost axis.
+    fn approx_complex<C, F: Fn(&C) -> (f32, f32)>(data: &ArrayD<C>, f: F) -> ArrayD<f32> {
+        let mut shape = data.shape().to_vec();
+        shape.push(2);
+        let values = data
+            .iter()
+            .flat_map(|c| {
+                let (re, im) = f(c);
+                vec![re, im]
+            }).collect();
+        ArrayD::from_shape_vec(shape, values).unwrap()
+    }
+
     pub fn close_enough(&self, other: &Self, approx: bool) -> bool {
+        if self.datum_type() == DatumType::String || other.datum_type() == DatumType::String {
+            return self == other;
+        }
         let ma = self.approx();
         let mb = other.approx();
         let avg = ma.iter().map(|&a| a.abs()).sum::<f32>() / ma.len() as f32;
@@ -350,6 +491,11 @@ impl Serialize for Tensor {
             Bool(m) => serialize_inner!(bool, m),
             U8(m) => serialize_inner!(u8, m),
             U16(m) => serialize_inner!(u16, m),
+            U32(m) => serialize_inner!(u32, m),
+            U64(m) => serialize_inner!(u64, m),
+            F16(m) => serialize_inner!(f16, m),
+            ComplexF32(m) => serialize_inner!(ComplexF32, m),
+            ComplexF64(m) => serialize_inner!(ComplexF64, m),
             I8(m) => serialize_inner!(i8, m),
             I16(m) => serialize_inner!(i16, m),
             I32(m) => serialize_inner!(i32, m),
@@ -369,7 +515,7 @@ impl<D: ::ndarray::Dimension, T: Datum> From<Array<T, D>> for Tensor {
 }
 
 macro_rules! tensor {
-    ($t:ident, $v:ident, $as_one:ident, $as:ident, $take:ident, $make:ident, [$(($cast:ident, $as_cast:ident)),*]) => {
+    ($t:ty, $v:ident, $as_one:ident, $as:ident, $take:ident, $make:ident, [$(($cast:ident, $as_cast:ident)),*]) => {
         impl From<$t> for Tensor {
             fn from(it: $t) -> Tensor {
                 Tensor::$v(arr0(it).into_dyn())
@@ -380,7 +526,7 @@ macro_rules! tensor {
             pub fn $as_one(&self) -> Option<$t> {
                 if let &Tensor::$v(ref it) = self {
                     if it.shape().len() == 0 {
-                        Some(*it.iter().next().unwrap())
+                        Some(it.iter().next().unwrap().clone())
                     } else {
                         None
                     }
@@ -436,16 +582,16 @@ macro_rules! tensor {
 
             fn tensor_cast_to_array(m: &Tensor) -> TfdResult<MaybeOwnedArray<$t>> {
                 match m.datum_type() {
-                    DatumType::$v => Ok(MaybeOwnedArray::View($t::tensor_to_view(m)?)),
+                    DatumType::$v => Ok(MaybeOwnedArray::View(<$t>::tensor_to_view(m)?)),
                     $(DatumType::$cast => {
                         let src = m.$as_cast().ok_or("Wrong type")?;
                         let vec:Vec<$t> = src.iter()
-                            .map(|x| TryInto::<$t>::try_into(*x))
+                            .map(|x| TryInto::<$t>::try_into(x.clone()))
                             .collect::<TfdResult<Vec<_>>>()?;
                         let dst:ArrayD<$t> = ArrayD::from_shape_vec(src.shape(), vec)?;
                         Ok(MaybeOwnedArray::Owned(dst))
                     })*
-                    _ => bail!("Can not cast tensor from {:?} to {:?}", m.datum_type(), $t::datum_type())
+                    _ => bail!("Can not cast tensor from {:?} to {:?}", m.datum_type(), <$t>::datum_type())
                 }
             }
         }
@@ -475,8 +621,17 @@ impl TryInto<i64> for TDim {
 }
 
 tensor!(bool, Bool, as_bool, as_bools, take_bools, bools, []);
-tensor!(f64, F64, as_f64, as_f64s, take_f64s, f64s, []);
-tensor!(f32, F32, as_f32, as_f32s, take_f32s, f32s, []);
+tensor!(
+    f64,
+    F64,
+    as_f64,
+    as_f64s,
+    take_f64s,
+    f64s,
+    [(F16, as_f16s), (F32, as_f32s)]
+);
+tensor!(f32, F32, as_f32, as_f32s, take_f32s, f32s, [(F16, as_f16s)]);
+tensor!(f16, F16, as_f16, as_f16s, take_f16s, f16s, []);
 tensor!(i8, I8, as_i8, as_i8s, take_i8s, i8s, []);
 tensor!(i16, I16, as_i16, as_i16s, take_i16s, i16s, []);
 tensor!(
@@ -495,10 +650,46 @@ tensor!(
     as_i64s,
     take_i64s,
     i64s,
-    [(TDim, as_dims)]
+    [(TDim, as_dims), (U32, as_u32s)]
 );
 tensor!(u8, U8, as_u8, as_u8s, take_u8s, u8s, []);
-tensor!(u16, U16, as_u16, as_u16s, take_u16s, u16s, []);
+tensor!(u16, U16, as_u16, as_u16s, take_u16s, u16s, [(U8, as_u8s)]);
+tensor!(
+    u32,
+    U32,
+    as_u32,
+    as_u32s,
+    take_u32s,
+    u32s,
+    [(U8, as_u8s), (U16, as_u16s)]
+);
+tensor!(
+    u64,
+    U64,
+    as_u64,
+    as_u64s,
+    take_u64s,
+    u64s,
+    [(U8, as_u8s), (U16, as_u16s), (U32, as_u32s)]
+);
+tensor!(
+    Complex<f32>,
+    ComplexF32,
+    as_complex_f32,
+    as_complex_f32s,
+    take_complex_f32s,
+    complex_f32s,
+    []
+);
+tensor!(
+    Complex<f64>,
+    ComplexF64,
+    as_complex_f64,
+    as_complex_f64s,
+    take_complex_f64s,
+    complex_f64s,
+    []
+);
 tensor!(
     TDim,
     TDim,
@@ -508,6 +699,21 @@ tensor!(
     dims,
     [(I32, as_i32s)]
 );
+tensor!(
+    Vec<u8>,
+    String,
+    as_string,
+    as_strings,
+    take_strings,
+    strings,
+    []
+);
+
+impl<'a> From<&'a str> for Tensor {
+    fn from(it: &'a str) -> Tensor {
+        Tensor::from(it.as_bytes().to_vec())
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -525,4 +731,16 @@ mod tests {
         let t_i32: Tensor = arr1(&[0i32, 0]).into();
         let _dims: MaybeOwnedArray<TDim> = TDim::tensor_cast_to_array(&t_i32).unwrap();
     }
+
+    #[test]
+    fn test_strings() {
+        let t = Tensor::strings(&[2, 2], &[b"a".to_vec(), b"b".to_vec(), b"c".to_vec(), vec![0xff]]).unwrap();
+        assert_eq!(t.datum_type(), DatumType::String);
+        assert_eq!(Tensor::from("a").as_string(), Some(b"a".to_vec()));
+        let chunks = t.axis_chunks(0, 1).unwrap();
+        assert_eq!(chunks[1], Tensor::strings(&[1, 2], &[b"c".to_vec(), vec![0xff]]).unwrap());
+        assert!(t.close_enough(&t.clone(), true));
+        assert!(!chunks[0].close_enough(&chunks[1], true));
+        assert_eq!(format!("{:?}", Tensor::from("foo")), "Tensor Scalar String \"foo\"");
+    }
 }
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
 use std::sync::Arc;
 use std::{fs, path};
 
@@ -6,20 +7,29 @@ use tfdeploy::model::{Model, Node, OutletId, RawModel};
 use tfdeploy::*;
 
 use pb;
+use tensor::tensor_fact;
 
 /// Load a ONNX protobul model from a file.
 pub fn for_path<P: AsRef<path::Path>>(p: P) -> TfdResult<Model> {
-    for_reader(fs::File::open(p)?)
+    model_proto_for_path(p)?.to_tfd()
 }
 
 /// Load a ONNX model from a reader.
+///
+/// Models with tensors stored in external files must be loaded with
+/// `for_path`.
 pub fn for_reader<R: ::std::io::Read>(r: R) -> TfdResult<Model> {
     model_proto_for_reader(r)?.to_tfd()
 }
 
-/// Load a ONNX protobuf graph def from a path
+/// Load a ONNX protobuf graph def from a path, along with the tensors it
+/// stores in external files.
 pub fn model_proto_for_path<P: AsRef<path::Path>>(p: P) -> TfdResult<pb::ModelProto> {
-    model_proto_for_reader(fs::File::open(p)?)
+    let p = p.as_ref();
+    let mut proto = model_proto_for_reader(fs::File::open(p)?)?;
+    let dir = p.parent().unwrap_or_else(|| path::Path::new(""));
+    load_external_data(&mut proto, dir)?;
+    Ok(proto)
 }
 
 /// Load a ONNX protobuf graph def from a reader.
@@ -27,28 +37,194 @@ pub fn model_proto_for_reader<R: ::std::io::Read>(mut r: R) -> TfdResult<pb::Mod
     Ok(::protobuf::parse_from_reader(&mut r).map_err(|e| format!("{:?}", e))?)
 }
 
-impl TfdFrom<pb::ModelProto> for Model {
-    fn tfd_from(proto: &pb::ModelProto) -> TfdResult<Model> {
+/// Reads the tensors the model stores in external files, relative to `dir`,
+/// and moves their content into their raw data.
+pub fn load_external_data(proto: &mut pb::ModelProto, dir: &path::Path) -> TfdResult<()> {
+    load_external_graph(proto.mut_graph(), dir)
+}
+
+/// Same as `load_external_data`, for a graph and the graphs nested in its
+/// nodes attributes.
+fn load_external_graph(graph: &mut pb::GraphProto, dir: &path::Path) -> TfdResult<()> {
+    for tensor in graph.mut_initializer().iter_mut() {
+        load_external_tensor(tensor, dir)?;
+    }
+    for node in graph.mut_node().iter_mut() {
+        for attr in node.mut_attribute().iter_mut() {
+            if attr.has_t() {
+                load_external_tensor(attr.mut_t(), dir)?;
+            }
+            for tensor in attr.mut_tensors().iter_mut() {
+                load_external_tensor(tensor, dir)?;
+            }
+            if attr.has_g() {
+                load_external_graph(attr.mut_g(), dir)?;
+            }
+            for graph in attr.mut_graphs().iter_mut() {
+                load_external_graph(graph, dir)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolves an external data location in the model directory: it must be
+/// relative, and not climb out of the directory through "..".
+fn external_location(dir: &path::Path, location: &str) -> Option<path::PathBuf> {
+    let mut relative = path::PathBuf::new();
+    for component in path::Path::new(location).components() {
+        match component {
+            path::Component::Normal(c) => relative.push(c),
+            path::Component::CurDir => (),
+            path::Component::ParentDir if relative.pop() => (),
+            _ => return None,
+        }
+    }
+    Some(dir.join(relative))
+}
+
+fn load_external_tensor(tensor: &mut pb::TensorProto, dir: &path::Path) -> TfdResult<()> {
+    if tensor.get_data_location() != pb::TensorProto_DataLocation::EXTERNAL {
+        return Ok(());
+    }
+    let mut location = None;
+    let mut offset = 0;
+    let mut length = None;
+    for entry in tensor.get_external_data() {
+        let value = entry.get_value();
+        let invalid = || format!("Tensor {}: invalid external {} {:?}", tensor.get_name(), entry.get_key(), value);
+        match entry.get_key() {
+            "location" => location = Some(external_location(dir, value).ok_or_else(invalid)?),
+            "offset" => offset = value.parse::<u64>().map_err(|_| invalid())?,
+            "length" => length = Some(value.parse::<u64>().map_err(|_| invalid())?),
+            _ => (),
+        }
+    }
+    let location = location
+        .ok_or_else(|| format!("Tensor {}: external data without location", tensor.get_name()))?;
+    let mut file = fs::File::open(&location)
+        .map_err(|e| format!("Tensor {}: opening {:?}: {}", tensor.get_name(), location, e))?;
+    let size = file.metadata()?.len();
+    let length = length.unwrap_or(size.saturating_sub(offset));
+    if offset.checked_add(length).map(|end| end > size).unwrap_or(true) {
+        bail!(
+            "Tensor {}: {} bytes at offset {} do not fit in {:?} ({} bytes)",
+            tensor.get_name(),
+            length,
+            offset,
+            location,
+            size
+        )
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    let mut data = vec![];
+    file.take(length).read_to_end(&mut data)?;
+    if data.len() as u64 != length {
+        bail!(
+            "Tensor {}: expected {} bytes in {:?}, read {}",
+            tensor.get_name(),
+            length,
+            location,
+            data.len()
+        )
+    }
+    tensor.set_raw_data(data);
+    tensor.clear_external_data();
+    tensor.clear_data_location();
+    Ok(())
+}
+
+/// The opset version the model imports for each domain.
+///
+/// Models that import no opset for the default domain predate opset imports,
+/// and are read with the first opset.
+fn opset_versions(proto: &pb::ModelProto) -> HashMap<&str, i64> {
+    let mut opsets: HashMap<&str, i64> = proto
+        .get_opset_import()
+        .iter()
+        .map(|o| (super::ops::domain(o.get_domain()), o.get_version()))
+        .collect();
+    opsets.entry(super::ops::DEFAULT_DOMAIN).or_insert(1);
+    opsets
+}
+
+/// A graph turned into a model, as it fits in the graph owning it.
+pub struct ParseResult {
+    pub model: Model,
+    /// The names of the runtime inputs of the graph.
+    pub inputs: Vec<String>,
+    /// The values the graph uses from outer scopes, turned into extra
+    /// inputs of the model.
+    pub captured: Vec<String>,
+    /// The outlets of the graph outputs.
+    pub outputs: Vec<OutletId>,
+}
+
+/// What it takes to turn a graph, and the graphs nested in its nodes, into
+/// models.
+pub struct ParsingContext<'a> {
+    pub op_builder: &'a super::ops::OpBuilder,
+    pub opsets: HashMap<&'a str, i64>,
+    pub symbols: SymbolTable,
+}
+
+impl<'a> ParsingContext<'a> {
+    /// Turns a graph into a model.
+    ///
+    /// Names used in the graph but not defined there are values from an
+    /// outer scope: they become `Source` nodes, listed as captured.
+    pub fn parse_graph(&mut self, graph: &pb::GraphProto) -> TfdResult<ParseResult> {
         let mut nodes = vec![];
-        let mut model_inputs = vec![];
+        let mut inputs = vec![];
         let mut outlets_index: HashMap<String, OutletId> = HashMap::new();
         let mut nodes_by_name: HashMap<String, usize> = HashMap::new();
-        let op_builder = super::ops::OpBuilder::new();
-        let graph = proto.get_graph();
-        for input in graph.get_input().iter() {
+        // Initializers are often listed among the inputs too, but they are
+        // constants, not something to feed at runtime.
+        let initializers: HashMap<&str, &pb::TensorProto> = graph
+            .get_initializer()
+            .iter()
+            .map(|t| (t.get_name(), t))
+            .collect();
+        for input in graph
+            .get_input()
+            .iter()
+            .filter(|i| !initializers.contains_key(i.get_name()))
+        {
             outlets_index.insert(input.get_name().to_owned(), OutletId::new(nodes.len(), 0));
-            let fact = input.get_field_type().get_tensor_type().to_tfd()?;
+            let fact = tensor_fact(
+                input.get_field_type().get_tensor_type(),
+                input.get_name(),
+                &mut self.symbols,
+            )?;
             let source = Node {
                 id: nodes.len(),
                 name: input.get_name().to_owned(),
                 op: Box::new(::tfdeploy::ops::source::Source::new(fact)),
                 op_name: "Source".to_string(),
                 inputs: vec![],
+                control_inputs: vec![],
             };
             nodes_by_name.insert(input.get_name().to_owned(), nodes.len());
-            model_inputs.push(nodes.len());
+            inputs.push(input.get_name().to_owned());
             nodes.push(source);
         }
+        for tensor in graph.get_initializer().iter() {
+            outlets_index.insert(tensor.get_name().to_owned(), OutletId::new(nodes.len(), 0));
+            let value = tensor
+                .to_tfd()
+                .map_err(|e| format!("Loading initializer {}: {}", tensor.get_name(), e))?;
+            let konst = Node {
+                id: nodes.len(),
+                name: tensor.get_name().to_owned(),
+                op: Box::new(::tfdeploy::ops::konst::Const::for_tensor(value)),
+                op_name: "Const".to_string(),
+                inputs: vec![],
+                control_inputs: vec![],
+            };
+            nodes_by_name.insert(tensor.get_name().to_owned(), nodes.len());
+            nodes.push(konst);
+        }
+        let mut node_inputs = vec![];
         for pbnode in graph.get_node().iter() {
             let name = if pbnode.get_name() != "" {
                 pbnode.get_name().to_string()
@@ -61,43 +237,248 @@ impl TfdFrom<pb::ModelProto> for Model {
                 outlets_index.insert(output.to_string(), OutletId::new(nodes.len(), ix));
             }
             let op_name = pbnode.get_op_type().to_owned();
+            let domain = super::ops::domain(pbnode.get_domain());
+            let version = *self.opsets.get(domain).ok_or_else(|| {
+                format!("Node {} uses domain {:?} which the model does not import", name, domain)
+            })?;
+            let (op, op_inputs) = if super::ops::control_flow::is_control_flow(pbnode) {
+                super::ops::control_flow::build(self, pbnode, version)
+            } else {
+                self.op_builder
+                    .build(pbnode, version)
+                    .map(|op| (op, pbnode.get_input().to_vec()))
+            }.map_err(|e| format!("Building node {} ({}): {}", name, op_name, e))?;
             let node = Node {
                 id: nodes.len(),
                 name: name.clone(),
-                op: op_builder.build(pbnode)?,
+                op,
                 op_name,
                 inputs: vec![],
+                control_inputs: vec![],
             };
             nodes_by_name.insert(name, nodes.len());
+            node_inputs.push((nodes.len(), op_inputs));
             nodes.push(node)
         }
-        for (pbnode, mut node) in graph
-            .get_node()
-            .iter()
-            .zip(&mut nodes.iter_mut().skip(graph.get_input().len()))
+        let mut captured = vec![];
+        let mut outputs = vec![];
+        let mut sinks = vec![];
         {
-            for pbinput in pbnode.get_input() {
-                node.inputs.push(
-                    outlets_index
-                        .get(pbinput)
-                        .ok_or_else(|| format!("Can not find matching outlet for {}", pbinput))?
-                        .clone(),
-                )
+            let mut outlet = |name: &str, nodes: &mut Vec<Node>| {
+                if let Some(outlet) = outlets_index.get(name) {
+                    return *outlet;
+                }
+                let outlet = OutletId::new(nodes.len(), 0);
+                nodes.push(Node {
+                    id: nodes.len(),
+                    name: name.to_owned(),
+                    op: Box::new(::tfdeploy::ops::source::Source::new(TensorFact::default())),
+                    op_name: "Source".to_string(),
+                    inputs: vec![],
+                    control_inputs: vec![],
+                });
+                nodes_by_name.insert(name.to_owned(), outlet.node);
+                outlets_index.insert(name.to_owned(), outlet);
+                captured.push(name.to_owned());
+                outlet
+            };
+            for (id, names) in node_inputs {
+                // Optional inputs left out are named "".
+                for name in names.iter().filter(|name| !name.is_empty()) {
+                    let input = outlet(name, &mut nodes);
+                    nodes[id].inputs.push(input);
+                }
+            }
+            for output in graph.get_output().iter() {
+                let fact = tensor_fact(
+                    output.get_field_type().get_tensor_type(),
+                    output.get_name(),
+                    &mut self.symbols,
+                )?;
+                let input = outlet(output.get_name(), &mut nodes);
+                let sink = Node {
+                    id: nodes.len(),
+                    name: format!("Sink-{}", output.get_name()),
+                    op: Box::new(::tfdeploy::ops::sink::Sink::new(fact)),
+                    op_name: "Sink".to_string(),
+                    inputs: vec![input],
+                    control_inputs: vec![],
+                };
+                outputs.push(input);
+                sinks.push((format!("Output-{}", output.get_name()), nodes.len()));
+                nodes.push(sink);
             }
         }
-        for output in graph.get_output().iter() {
-            let fact = output.get_field_type().get_tensor_type().to_tfd()?;
-            let outlet = outlets_index[output.get_name()];
-            let source = Node {
-                id: nodes.len(),
-                name: format!("Sink-{}", output.get_name()),
-                op: Box::new(::tfdeploy::ops::sink::Sink::new(fact)),
-                op_name: "Sink".to_string(),
-                inputs: vec![outlet],
-            };
-            nodes_by_name.insert(format!("Output-{}", output.get_name()), nodes.len());
-            nodes.push(source);
+        nodes_by_name.extend(sinks);
+        Ok(ParseResult {
+            model: Model(Arc::new(RawModel::new(nodes, nodes_by_name))),
+            inputs,
+            captured,
+            outputs,
+        })
+    }
+}
+
+impl TfdFrom<pb::ModelProto> for Model {
+    fn tfd_from(proto: &pb::ModelProto) -> TfdResult<Model> {
+        let op_builder = super::ops::OpBuilder::new();
+        let mut context = ParsingContext {
+            op_builder: &op_builder,
+            opsets: opset_versions(proto),
+            symbols: SymbolTable::default(),
+        };
+        let result = context.parse_graph(proto.get_graph())?;
+        if let Some(name) = result.captured.first() {
+            bail!("Can not find matching outlet for {}", name)
         }
-        Ok(Model(Arc::new(RawModel::new(nodes, nodes_by_name))))
+        Ok(result.model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protobuf::Message;
+
+    fn float_input(name: &str) -> pb::ValueInfoProto {
+        let mut input = pb::ValueInfoProto::new();
+        input.set_name(name.to_string());
+        input
+            .mut_field_type()
+            .mut_tensor_type()
+            .set_elem_type(pb::TensorProto_DataType::FLOAT);
+        input
+    }
+
+    fn with_dims(mut input: pb::ValueInfoProto, dims: &[&str]) -> pb::ValueInfoProto {
+        for d in dims {
+            let mut dim = pb::TensorShapeProto_Dimension::new();
+            match d.parse::<i64>() {
+                Ok(v) => dim.set_dim_value(v),
+                Err(_) if *d == "?" => (),
+                Err(_) => dim.set_dim_param(d.to_string()),
+            }
+            input
+                .mut_field_type()
+                .mut_tensor_type()
+                .mut_shape()
+                .mut_dim()
+                .push(dim);
+        }
+        input
+    }
+
+    /// `y = x + w`, with `w` an initializer also listed among the inputs and
+    /// stored in `w.bin`.
+    fn add_model() -> pb::ModelProto {
+        let mut w = pb::TensorProto::new();
+        w.set_name("w".to_string());
+        w.set_data_type(pb::TensorProto_DataType::FLOAT);
+        w.set_dims(vec![2]);
+        w.set_data_location(pb::TensorProto_DataLocation::EXTERNAL);
+        for &(k, v) in &[("location", "w.bin"), ("offset", "4"), ("length", "8")] {
+            let mut entry = pb::StringStringEntryProto::new();
+            entry.set_key(k.to_string());
+            entry.set_value(v.to_string());
+            w.mut_external_data().push(entry);
+        }
+        let mut add = pb::NodeProto::new();
+        add.set_op_type("Add".to_string());
+        add.mut_input().push("x".to_string());
+        add.mut_input().push("w".to_string());
+        add.mut_output().push("y".to_string());
+        let mut proto = pb::ModelProto::new();
+        {
+            let graph = proto.mut_graph();
+            graph.mut_input().push(float_input("x"));
+            graph.mut_input().push(float_input("w"));
+            graph.mut_initializer().push(w);
+            graph.mut_node().push(add);
+            graph.mut_output().push(float_input("y"));
+        }
+        proto
+    }
+
+    #[test]
+    fn initializers_and_external_data() {
+        let dir = ::std::env::temp_dir().join(format!("tfdeploy-onnx-{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut data = vec![0u8; 4];
+        for v in &[1.0f32, 2.0] {
+            data.extend(v.to_bits().to_le_bytes().iter());
+        }
+        fs::write(dir.join("w.bin"), data).unwrap();
+        fs::write(dir.join("model.onnx"), add_model().write_to_bytes().unwrap()).unwrap();
+
+        assert!(Model::tfd_from(&add_model()).is_err());
+        let model = for_path(dir.join("model.onnx")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let inputs: Vec<&str> = model.guess_inputs().iter().map(|n| &*n.name).collect();
+        assert_eq!(inputs, vec!["x"]);
+        assert_eq!(model.node_by_name("w").unwrap().op_name, "Const");
+
+        let plan = ::tfdeploy::SimplePlan::new(&model, &["x"], &["y"]).unwrap();
+        let x = Tensor::f32s(&[2], &[10.0, 20.0]).unwrap();
+        let y = plan.run(tvec![x]).unwrap().remove(0).remove(0);
+        assert_eq!(y, Tensor::f32s(&[2], &[11.0, 22.0]).unwrap());
+    }
+
+    #[test]
+    fn external_data_stays_in_dir() {
+        let dir = ::std::env::temp_dir().join(format!("tfdeploy-onnx-ext-{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("w.bin"), vec![0u8; 8]).unwrap();
+        let load = |location: &str, length: &str| {
+            let mut tensor = pb::TensorProto::new();
+            tensor.set_data_location(pb::TensorProto_DataLocation::EXTERNAL);
+            for &(k, v) in &[("location", location), ("length", length)] {
+                let mut entry = pb::StringStringEntryProto::new();
+                entry.set_key(k.to_string());
+                entry.set_value(v.to_string());
+                tensor.mut_external_data().push(entry);
+            }
+            load_external_tensor(&mut tensor, &dir).map(|_| tensor.get_raw_data().len())
+        };
+        assert_eq!(load("./sub/../w.bin", "8").unwrap(), 8);
+        assert!(load("../w.bin", "8").is_err());
+        assert!(load(&dir.join("w.bin").to_string_lossy(), "8").is_err());
+        assert!(load("w.bin", "9").is_err());
+        assert!(load("w.bin", "18446744073709551615").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn symbolic_dims() {
+        let mut add = pb::NodeProto::new();
+        add.set_op_type("Add".to_string());
+        add.mut_input().push("a".to_string());
+        add.mut_input().push("b".to_string());
+        add.mut_output().push("y".to_string());
+        let mut proto = pb::ModelProto::new();
+        {
+            let graph = proto.mut_graph();
+            graph.mut_input().push(with_dims(float_input("a"), &["batch", "3"]));
+            graph.mut_input().push(with_dims(float_input("b"), &["batch", "?"]));
+            graph.mut_node().push(add);
+            graph.mut_output().push(float_input("y"));
+        }
+        let model = Model::tfd_from(&proto).unwrap();
+        let source = |dims: Vec<TDim>| {
+            format!(
+                "{:?}",
+                ::tfdeploy::ops::source::Source::new(
+                    TensorFact::dt(DatumType::F32).with_shape(dims)
+                )
+            )
+        };
+        let batch = TDim::sym('b');
+        assert_eq!(
+            format!("{:?}", model.node_by_name("a").unwrap().op),
+            source(vec![batch, 3.into()])
+        );
+        assert_eq!(
+            format!("{:?}", model.node_by_name("b").unwrap().op),
+            source(vec![batch, TDim::sym('a')])
+        );
     }
 }
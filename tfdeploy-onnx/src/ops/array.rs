@@ -0,0 +1,113 @@
+use tfdeploy::ops as tfdops;
+
+use tfdeploy::ops::array::PadMode;
+use tfdeploy::{DatumType, TfdResult};
+
+use ops::OpRegister;
+use pb::NodeProto;
+
+pub fn register_all_ops(reg: &mut OpRegister) {
+    reg.insert("Concat", |node| concat(node, Some(1)));
+    reg.insert_since("Concat", 4, |node| concat(node, None));
+    reg.insert("Flatten", flatten);
+    reg.insert("Gather", gather);
+    reg.insert("Pad", |node| pad(node, "paddings"));
+    reg.insert_since("Pad", 2, |node| pad(node, "pads"));
+    reg.unsupported_since("Pad", 11);
+    reg.insert_since("Reshape", 5, |_| Ok(Box::new(tfdops::array::Reshape::default())));
+    reg.insert("Shape", |_| Ok(Box::new(tfdops::array::Shape::new(DatumType::I64))));
+    reg.insert("Slice", slice);
+    reg.unsupported_since("Slice", 10);
+    reg.insert("Split", split);
+    reg.unsupported_since("Split", 13);
+    reg.insert("Squeeze", squeeze);
+    reg.unsupported_since("Squeeze", 13);
+    reg.insert("Transpose", transpose);
+    reg.insert("Unsqueeze", unsqueeze);
+    reg.unsupported_since("Unsqueeze", 13);
+}
+
+fn required_ints(node: &NodeProto, name: &str) -> TfdResult<Vec<i64>> {
+    Ok(node.get_attr_opt_ints(name)?.ok_or_else(|| {
+        format!("Node {} ({}) expected ints attribute '{}'", node.get_name(), node.get_op_type(), name)
+    })?)
+}
+
+/// Before opset 4, the axis was optional.
+fn concat(node: &NodeProto, default_axis: Option<i64>) -> TfdResult<Box<tfdops::Op>> {
+    let axis = node
+        .get_attr_opt_int("axis")?
+        .or(default_axis)
+        .ok_or("Concat expects an axis attribute")?;
+    Ok(Box::new(tfdops::array::Concat::new(axis)))
+}
+
+fn flatten(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let axis = node.get_attr_opt_int("axis")?.unwrap_or(1);
+    if axis < 0 {
+        bail!("Flatten expects a positive axis, got {}", axis)
+    }
+    Ok(Box::new(tfdops::array::Flatten::new(axis as usize)))
+}
+
+fn gather(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let axis = node.get_attr_opt_int("axis")?.unwrap_or(0);
+    Ok(Box::new(tfdops::array::Gather::new(axis)))
+}
+
+/// The pads attribute was named `paddings` in the first opset.
+fn pad(node: &NodeProto, pads_attr: &str) -> TfdResult<Box<tfdops::Op>> {
+    let value = node.get_attr_opt_float("value")?.unwrap_or(0.0);
+    let mode = match node.get_attr_opt_str("mode")?.unwrap_or("constant") {
+        "constant" => PadMode::Constant(value),
+        "reflect" => PadMode::Reflect,
+        "edge" => PadMode::Edge,
+        mode => bail!("Unsupported Pad mode {}", mode),
+    };
+    let pads = required_ints(node, pads_attr)?;
+    if pads.len() % 2 != 0 || pads.iter().any(|&p| p < 0) {
+        bail!("Unsupported pads {:?}", pads)
+    }
+    let rank = pads.len() / 2;
+    let pads = (0..rank)
+        .map(|ax| (pads[ax] as usize, pads[ax + rank] as usize))
+        .collect();
+    Ok(Box::new(tfdops::array::Pad::new(pads, mode)))
+}
+
+fn slice(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let axes = node.get_attr_opt_ints("axes")?;
+    let starts = required_ints(node, "starts")?;
+    let ends = required_ints(node, "ends")?;
+    Ok(Box::new(tfdops::array::Slice::new(axes, starts, ends)))
+}
+
+fn split(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let axis = node.get_attr_opt_int("axis")?.unwrap_or(0);
+    let split = match node.get_attr_opt_ints("split")? {
+        Some(split) if split.iter().any(|&s| s < 0) => bail!("Invalid split {:?}", split),
+        Some(split) => Some(split.into_iter().map(|s| s as usize).collect()),
+        None => None,
+    };
+    let outputs = node.get_output().len();
+    Ok(Box::new(tfdops::array::Split::new(axis, outputs, split)))
+}
+
+fn squeeze(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let axes = node.get_attr_opt_ints("axes")?;
+    Ok(Box::new(tfdops::array::Squeeze::new(axes)))
+}
+
+fn transpose(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let perm = match node.get_attr_opt_ints("perm")? {
+        Some(perm) if perm.iter().any(|&p| p < 0) => bail!("Invalid perm {:?}", perm),
+        Some(perm) => Some(perm.into_iter().map(|p| p as usize).collect()),
+        None => None,
+    };
+    Ok(Box::new(tfdops::array::Transpose::new(perm)))
+}
+
+fn unsqueeze(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let axes = required_ints(node, "axes")?;
+    Ok(Box::new(tfdops::array::Unsqueeze::new(axes)))
+}
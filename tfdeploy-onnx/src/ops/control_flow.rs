@@ -0,0 +1,312 @@
+use tfdeploy::ops as tfdops;
+use tfdeploy::ops::control_flow::SubGraph;
+use tfdeploy::TfdResult;
+
+use model::ParsingContext;
+use pb::NodeProto;
+
+/// Whether the node runs nested graphs.
+///
+/// Building these ops takes the parsing context, and they may get values of
+/// the outer scope as extra inputs.
+pub fn is_control_flow(node: &NodeProto) -> bool {
+    super::domain(node.get_domain()) == super::DEFAULT_DOMAIN
+        && ["If", "Loop", "Scan"].contains(&node.get_op_type())
+}
+
+/// Builds a control-flow op, along with the names of the values to wire to
+/// its inputs.
+pub fn build(
+    ctx: &mut ParsingContext,
+    node: &NodeProto,
+    version: i64,
+) -> TfdResult<(Box<tfdops::Op>, Vec<String>)> {
+    match node.get_op_type() {
+        "If" => if_then_else(ctx, node),
+        "Loop" => looping(ctx, node),
+        // Before opset 9, Scan had a batch axis and sequence lengths.
+        "Scan" if version >= 9 => scan(ctx, node),
+        op => bail!("Operator {} is not supported in opset {}", op, version),
+    }
+}
+
+/// Parses the graph of an attribute, adding the values it captures from
+/// the outer scope to the op inputs.
+fn subgraph(
+    ctx: &mut ParsingContext,
+    node: &NodeProto,
+    attr: &str,
+    inputs: &mut Vec<String>,
+) -> TfdResult<SubGraph> {
+    let result = ctx.parse_graph(node.get_attr_graph(attr)?)?;
+    let captured = result
+        .captured
+        .iter()
+        .map(|name| match inputs.iter().position(|i| i == name) {
+            Some(ix) => ix,
+            None => {
+                inputs.push(name.clone());
+                inputs.len() - 1
+            }
+        })
+        .collect();
+    let mut plan_inputs = result.inputs.clone();
+    plan_inputs.extend(result.captured.iter().cloned());
+    SubGraph::new(&result.model, &plan_inputs, result.outputs, captured)
+}
+
+fn if_then_else(
+    ctx: &mut ParsingContext,
+    node: &NodeProto,
+) -> TfdResult<(Box<tfdops::Op>, Vec<String>)> {
+    let mut inputs = node.get_input().to_vec();
+    if inputs.len() != 1 {
+        bail!("If expects a single input, got {:?}", inputs)
+    }
+    let then_branch = subgraph(ctx, node, "then_branch", &mut inputs)?;
+    let else_branch = subgraph(ctx, node, "else_branch", &mut inputs)?;
+    let op = tfdops::control_flow::If::new(then_branch, else_branch);
+    Ok((Box::new(op), inputs))
+}
+
+/// The trip count and the condition are optional, left empty when absent.
+fn looping(
+    ctx: &mut ParsingContext,
+    node: &NodeProto,
+) -> TfdResult<(Box<tfdops::Op>, Vec<String>)> {
+    let declared = node.get_input();
+    if declared.len() < 2 {
+        bail!("Loop expects a trip count and a condition, got {:?}", declared)
+    }
+    let has_trip_count = declared[0] != "";
+    let has_condition = declared[1] != "";
+    let mut inputs: Vec<String> = declared
+        .iter()
+        .enumerate()
+        .filter(|&(ix, name)| ix >= 2 || name != "")
+        .map(|(_, name)| name.clone())
+        .collect();
+    let body = subgraph(ctx, node, "body", &mut inputs)?;
+    let op = tfdops::control_flow::Loop::new(body, has_trip_count, has_condition, declared.len() - 2);
+    Ok((Box::new(op), inputs))
+}
+
+/// Reads the axes and directions of the scanned inputs or outputs.
+fn scan_axes(
+    node: &NodeProto,
+    axes: &str,
+    directions: &str,
+    count: usize,
+) -> TfdResult<Vec<(i64, bool)>> {
+    let axes = node.get_attr_opt_ints(axes)?.unwrap_or_else(|| vec![0; count]);
+    let directions = node
+        .get_attr_opt_ints(directions)?
+        .unwrap_or_else(|| vec![0; count]);
+    if axes.len() != count || directions.len() != count {
+        bail!("Scan expects {} axes and directions, got {:?} and {:?}", count, axes, directions)
+    }
+    Ok(axes
+        .into_iter()
+        .zip(directions.into_iter())
+        .map(|(axis, direction)| (axis, direction == 1))
+        .collect())
+}
+
+fn scan(ctx: &mut ParsingContext, node: &NodeProto) -> TfdResult<(Box<tfdops::Op>, Vec<String>)> {
+    let mut inputs = node.get_input().to_vec();
+    let scanned = node
+        .get_attr_opt_int("num_scan_inputs")?
+        .ok_or("Scan expects a num_scan_inputs attribute")? as usize;
+    if scanned > inputs.len() {
+        bail!("Scan has {} inputs, less than its {} scanned ones", inputs.len(), scanned)
+    }
+    let state = inputs.len() - scanned;
+    if node.get_output().len() < state {
+        bail!("Scan has {} outputs, less than its {} state values", node.get_output().len(), state)
+    }
+    let input_axes = scan_axes(node, "scan_input_axes", "scan_input_directions", scanned)?;
+    let output_axes = scan_axes(
+        node,
+        "scan_output_axes",
+        "scan_output_directions",
+        node.get_output().len() - state,
+    )?;
+    let body = subgraph(ctx, node, "body", &mut inputs)?;
+    let op = tfdops::control_flow::Scan::new(body, state, input_axes, output_axes);
+    Ok((Box::new(op), inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use pb::*;
+    use tfdeploy::*;
+
+    fn node(op: &str, inputs: &[&str], outputs: &[&str]) -> NodeProto {
+        let mut node = NodeProto::new();
+        node.set_op_type(op.to_string());
+        node.set_input(inputs.iter().map(|s| s.to_string()).collect());
+        node.set_output(outputs.iter().map(|s| s.to_string()).collect());
+        node
+    }
+
+    fn with_graph(mut node: NodeProto, name: &str, graph: GraphProto) -> NodeProto {
+        let mut attr = AttributeProto::new();
+        attr.set_name(name.to_string());
+        attr.set_field_type(AttributeProto_AttributeType::GRAPH);
+        attr.set_g(graph);
+        node.mut_attribute().push(attr);
+        node
+    }
+
+    fn with_int(mut node: NodeProto, name: &str, value: i64) -> NodeProto {
+        let mut attr = AttributeProto::new();
+        attr.set_name(name.to_string());
+        attr.set_field_type(AttributeProto_AttributeType::INT);
+        attr.set_i(value);
+        node.mut_attribute().push(attr);
+        node
+    }
+
+    fn graph(inputs: &[&str], nodes: Vec<NodeProto>, outputs: &[&str]) -> GraphProto {
+        let value = |name: &&str| {
+            let mut value = ValueInfoProto::new();
+            value.set_name(name.to_string());
+            value
+        };
+        let mut graph = GraphProto::new();
+        graph.set_input(inputs.iter().map(value).collect());
+        graph.set_node(nodes.into());
+        graph.set_output(outputs.iter().map(value).collect());
+        graph
+    }
+
+    /// Runs a graph made of a single node, returning all its outputs.
+    fn run(graph: GraphProto, inputs: TVec<Tensor>) -> TVec<Tensor> {
+        let node = graph.get_node()[0].get_output()[0].to_string();
+        let inputs_names: Vec<String> = graph
+            .get_input()
+            .iter()
+            .map(|i| i.get_name().to_string())
+            .collect();
+        let mut proto = ModelProto::new();
+        proto.mut_opset_import().push({
+            let mut opset = OperatorSetIdProto::new();
+            opset.set_version(11);
+            opset
+        });
+        proto.set_graph(graph);
+        let model = Model::tfd_from(&proto).unwrap();
+        let plan = SimplePlan::new(&model, &inputs_names, &[node]).unwrap();
+        plan.run(inputs).unwrap().remove(0)
+    }
+
+    #[test]
+    fn if_captures_outer_values() {
+        let then_branch = graph(&[], vec![node("Add", &["x", "x"], &["y"])], &["y"]);
+        let else_branch = graph(&[], vec![node("Mul", &["x", "x"], &["y"])], &["y"]);
+        let iff = node("If", &["cond"], &["out"]);
+        let iff = with_graph(with_graph(iff, "then_branch", then_branch), "else_branch", else_branch);
+        let model = graph(&["cond", "x"], vec![iff], &["out"]);
+        let x = Tensor::f32s(&[1], &[3.0]).unwrap();
+        assert_eq!(
+            run(model.clone(), tvec![true.into(), x.clone()])[0],
+            Tensor::f32s(&[1], &[6.0]).unwrap()
+        );
+        assert_eq!(
+            run(model, tvec![false.into(), x])[0],
+            Tensor::f32s(&[1], &[9.0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn loop_with_trip_count() {
+        let body = graph(
+            &["i", "cond", "acc"],
+            vec![node("Add", &["acc", "x"], &["acc_out"])],
+            &["cond", "acc_out", "acc_out"],
+        );
+        let looping = with_graph(node("Loop", &["n", "", "acc"], &["final", "partials"]), "body", body);
+        let model = graph(&["n", "acc", "x"], vec![looping], &["final", "partials"]);
+        let outputs = run(
+            model,
+            tvec![
+                3i64.into(),
+                Tensor::f32s(&[2], &[0.0, 0.0]).unwrap(),
+                Tensor::f32s(&[2], &[1.0, 2.0]).unwrap(),
+            ],
+        );
+        assert_eq!(outputs[0], Tensor::f32s(&[2], &[3.0, 6.0]).unwrap());
+        assert_eq!(
+            outputs[1],
+            Tensor::f32s(&[3, 2], &[1.0, 2.0, 2.0, 4.0, 3.0, 6.0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn loop_without_iterations() {
+        let body = graph(
+            &["i", "cond", "acc"],
+            vec![node("Add", &["acc", "x"], &["acc_out"])],
+            &["cond", "acc_out", "acc_out"],
+        );
+        let looping = with_graph(node("Loop", &["n", "c", "acc"], &["final", "partials"]), "body", body);
+        let model = graph(&["n", "c", "acc", "x"], vec![looping], &["final", "partials"]);
+        for &(n, cond) in &[(0i64, true), (3, false)] {
+            let outputs = run(
+                model.clone(),
+                tvec![
+                    n.into(),
+                    cond.into(),
+                    Tensor::f32s(&[2], &[5.0, 5.0]).unwrap(),
+                    Tensor::f32s(&[2], &[1.0, 2.0]).unwrap(),
+                ],
+            );
+            assert_eq!(outputs[0], Tensor::f32s(&[2], &[5.0, 5.0]).unwrap());
+            assert_eq!(outputs[1], Tensor::f32s(&[0, 2], &[]).unwrap());
+        }
+    }
+
+    #[test]
+    fn scan_cumulative_sum() {
+        let body = graph(
+            &["sum", "x"],
+            vec![node("Add", &["sum", "x"], &["sum_out"])],
+            &["sum_out", "sum_out"],
+        );
+        let scan = with_graph(node("Scan", &["init", "xs"], &["sum", "sums"]), "body", body);
+        let scan = with_int(scan, "num_scan_inputs", 1);
+        let model = graph(&["init", "xs"], vec![scan], &["sum", "sums"]);
+        let outputs = run(
+            model,
+            tvec![
+                Tensor::f32s(&[2], &[0.0, 0.0]).unwrap(),
+                Tensor::f32s(&[3, 2], &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap(),
+            ],
+        );
+        assert_eq!(outputs[0], Tensor::f32s(&[2], &[9.0, 12.0]).unwrap());
+        assert_eq!(
+            outputs[1],
+            Tensor::f32s(&[3, 2], &[1.0, 2.0, 4.0, 6.0, 9.0, 12.0]).unwrap()
+        );
+    }
+    #[test]
+    fn scan_empty_axis() {
+        let body = graph(
+            &["sum", "x"],
+            vec![node("Add", &["sum", "x"], &["sum_out"])],
+            &["sum_out", "sum_out"],
+        );
+        let scan = with_graph(node("Scan", &["init", "xs"], &["sum", "sums"]), "body", body);
+        let scan = with_int(scan, "num_scan_inputs", 1);
+        let model = graph(&["init", "xs"], vec![scan], &["sum", "sums"]);
+        let outputs = run(
+            model,
+            tvec![
+                Tensor::f32s(&[2], &[0.0, 0.0]).unwrap(),
+                Tensor::f32s(&[0, 2], &[]).unwrap(),
+            ],
+        );
+        assert_eq!(outputs[0], Tensor::f32s(&[2], &[0.0, 0.0]).unwrap());
+        assert_eq!(outputs[1], Tensor::f32s(&[0, 2], &[]).unwrap());
+    }
+}
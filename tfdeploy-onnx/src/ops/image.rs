@@ -0,0 +1,83 @@
+use tfdeploy::ops::image::*;
+use tfdeploy::ops::Op;
+use tfdeploy::TfdResult;
+
+use ops::OpRegister;
+use pb::NodeProto;
+
+pub fn register_all_ops(reg: &mut OpRegister) {
+    reg.insert_since("Upsample", 7, upsample_7);
+    reg.insert_since("Upsample", 9, |node| resize_10(node, "Upsample"));
+    reg.unsupported_since("Upsample", 10);
+    reg.insert_since("Resize", 10, |node| resize_10(node, "Resize"));
+    reg.insert_since("Resize", 11, resize_11);
+    // Opset 18 adds antialias, axes and keep_aspect_ratio_policy.
+    reg.unsupported_since("Resize", 18);
+}
+
+/// Nearest neighbor or linear interpolation, with the legacy asymmetric
+/// coordinates where nearest neighbor floors.
+fn legacy_interpolation(node: &NodeProto, op: &str) -> TfdResult<Interpolation> {
+    match node.get_attr_opt_str("mode")?.unwrap_or("nearest") {
+        "nearest" => Ok(Interpolation::Nearest(Rounding::Floor)),
+        "linear" => Ok(Interpolation::Linear),
+        mode => bail!("{} mode {} is not supported", op, mode),
+    }
+}
+
+fn upsample_7(node: &NodeProto) -> TfdResult<Box<Op>> {
+    let scales = node
+        .get_attr_opt_floats("scales")?
+        .ok_or("Upsample expects a scales attribute")?;
+    Ok(Box::new(Resize::new(
+        legacy_interpolation(node, "Upsample")?,
+        CoordinateTransform::Asymmetric,
+        ResizeSize::ConstScales(scales),
+    )))
+}
+
+fn resize_10(node: &NodeProto, op: &str) -> TfdResult<Box<Op>> {
+    Ok(Box::new(Resize::new(
+        legacy_interpolation(node, op)?,
+        CoordinateTransform::Asymmetric,
+        ResizeSize::Scales { input: 1 },
+    )))
+}
+
+fn resize_11(node: &NodeProto) -> TfdResult<Box<Op>> {
+    let transform = match node
+        .get_attr_opt_str("coordinate_transformation_mode")?
+        .unwrap_or("half_pixel")
+    {
+        "half_pixel" => CoordinateTransform::HalfPixel,
+        "asymmetric" => CoordinateTransform::Asymmetric,
+        "align_corners" => CoordinateTransform::AlignCorners,
+        mode => bail!("Resize coordinate transformation mode {} is not supported", mode),
+    };
+    let interpolation = match node.get_attr_opt_str("mode")?.unwrap_or("nearest") {
+        "nearest" => Interpolation::Nearest(
+            match node.get_attr_opt_str("nearest_mode")?.unwrap_or("round_prefer_floor") {
+                "round_prefer_floor" => Rounding::RoundPreferFloor,
+                "round_prefer_ceil" => Rounding::RoundPreferCeil,
+                "floor" => Rounding::Floor,
+                "ceil" => Rounding::Ceil,
+                mode => bail!("Resize nearest mode {} is not supported", mode),
+            },
+        ),
+        "linear" => Interpolation::Linear,
+        mode => bail!("Resize mode {} is not supported", mode),
+    };
+    // The inputs are X, roi, scales and sizes, the optional ones being
+    // left out with empty names.
+    let inputs = node.get_input();
+    let position = |ix: usize| inputs[..ix].iter().filter(|i| !i.is_empty()).count();
+    let size = match inputs.get(3) {
+        Some(sizes) if !sizes.is_empty() => ResizeSize::Sizes {
+            input: position(3),
+            axes: None,
+        },
+        _ if inputs.get(2).map_or(false, |s| !s.is_empty()) => ResizeSize::Scales { input: position(2) },
+        _ => bail!("Resize expects either scales or sizes"),
+    };
+    Ok(Box::new(Resize::new(interpolation, transform, size)))
+}
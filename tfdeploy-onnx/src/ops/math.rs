@@ -1,6 +1,10 @@
 use tfdeploy::ops as tfdops;
 
+use tfdeploy::ops::math::Reducer;
+use tfdeploy::TfdResult;
+
 use ops::OpRegister;
+use pb::NodeProto;
 
 pub fn register_all_ops(reg: &mut OpRegister) {
     reg.insert("Add", |_| Ok(Box::new(tfdops::math::Add::default())));
@@ -13,6 +17,22 @@ pub fn register_all_ops(reg: &mut OpRegister) {
     reg.insert("Min", |_| Ok(Box::new(tfdops::math::MinN::default())));
     reg.insert("Mean", |_| Ok(Box::new(tfdops::math::MeanN::default())));
 
+    reg.insert("MatMul", |_| Ok(Box::new(tfdops::math::MatMul::default())));
+    reg.insert("Gemm", gemm);
+
+    reg.insert("ReduceSum", |node| reduce(node, Reducer::Sum));
+    reg.insert("ReduceMean", |node| reduce(node, Reducer::Mean));
+    reg.insert("ReduceMax", |node| reduce(node, Reducer::Max));
+    reg.insert("ReduceMin", |node| reduce(node, Reducer::Min));
+    reg.insert("ReduceProd", |node| reduce(node, Reducer::Prod));
+    // Reductions take their axes as an input from these opsets on.
+    reg.unsupported_since("ReduceSum", 13);
+    for op in &["ReduceMean", "ReduceMax", "ReduceMin", "ReduceProd"] {
+        reg.unsupported_since(op, 18);
+    }
+    reg.insert("ArgMax", |node| arg_max_min(node, true));
+    reg.insert("ArgMin", |node| arg_max_min(node, false));
+
     reg.insert("Abs", |_| Ok(Box::new(tfdops::math::Abs::default())));
     reg.insert("Ceil", |_| Ok(Box::new(tfdops::math::Ceil::default())));
     reg.insert("Floor", |_| Ok(Box::new(tfdops::math::Floor::default())));
@@ -37,3 +57,27 @@ pub fn register_all_ops(reg: &mut OpRegister) {
     reg.insert("Tanh", |_| Ok(Box::new(tfdops::math::Tanh::default())));
 }
 
+
+fn reduce(node: &NodeProto, reducer: Reducer) -> TfdResult<Box<tfdops::Op>> {
+    let axes = node.get_attr_opt_ints("axes")?;
+    let keep_dims = node.get_attr_opt_int("keepdims")?.unwrap_or(1) == 1;
+    Ok(Box::new(tfdops::math::Reduce::new(reducer, axes, keep_dims)))
+}
+
+fn arg_max_min(node: &NodeProto, max: bool) -> TfdResult<Box<tfdops::Op>> {
+    let axis = node.get_attr_opt_int("axis")?.unwrap_or(0);
+    let keep_dims = node.get_attr_opt_int("keepdims")?.unwrap_or(1) == 1;
+    // From opset 12 on, ties may resolve to the last index.
+    if node.get_attr_opt_int("select_last_index")?.unwrap_or(0) != 0 {
+        bail!("{} with select_last_index is not supported", node.get_op_type())
+    }
+    Ok(Box::new(tfdops::math::ArgMaxMin::new(max, axis, keep_dims)))
+}
+
+fn gemm(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let alpha = node.get_attr_opt_float("alpha")?.unwrap_or(1.0);
+    let beta = node.get_attr_opt_float("beta")?.unwrap_or(1.0);
+    let trans_a = node.get_attr_opt_int("transA")?.unwrap_or(0) == 1;
+    let trans_b = node.get_attr_opt_int("transB")?.unwrap_or(0) == 1;
+    Ok(Box::new(tfdops::math::Gemm::new(alpha, beta, trans_a, trans_b)))
+}
@@ -1,35 +1,129 @@
 use tfdeploy::ops::prelude::*;
 use pb::NodeProto;
 
+mod array;
+pub mod control_flow;
+#[cfg(feature = "image_ops")]
+mod image;
 mod logic;
 mod math;
 mod nn;
 
-pub type OpRegister = HashMap<&'static str, fn(&NodeProto) -> TfdResult<Box<Op>>>;
+pub type OpBuilderFn = fn(&NodeProto) -> TfdResult<Box<Op>>;
+
+/// The default ONNX domain, which goes by two names.
+pub const DEFAULT_DOMAIN: &str = "";
+
+/// Normalizes a domain name, mapping `ai.onnx` to the default domain.
+pub fn domain(name: &str) -> &str {
+    if name == "ai.onnx" {
+        DEFAULT_DOMAIN
+    } else {
+        name
+    }
+}
+
+/// The operator builders, by domain and operator name.
+///
+/// Each operator has a list of builders, each one valid from the opset
+/// version it was registered with until the next one. A missing builder
+/// marks the versions from which the operator is not supported.
+#[derive(Default)]
+pub struct OpRegister(HashMap<&'static str, HashMap<&'static str, Vec<(i64, Option<OpBuilderFn>)>>>);
+
+impl OpRegister {
+    /// Registers a builder for an operator of the default domain, valid
+    /// from the first opset.
+    pub fn insert(&mut self, op: &'static str, builder: OpBuilderFn) {
+        self.insert_since(op, 1, builder)
+    }
+
+    /// Registers a builder for an operator of the default domain, valid
+    /// from the given opset version.
+    pub fn insert_since(&mut self, op: &'static str, version: i64, builder: OpBuilderFn) {
+        self.register(DEFAULT_DOMAIN, op, version, Some(builder))
+    }
+
+    /// Marks an operator of the default domain as unsupported from the
+    /// given opset version.
+    pub fn unsupported_since(&mut self, op: &'static str, version: i64) {
+        self.register(DEFAULT_DOMAIN, op, version, None)
+    }
+
+    pub fn register(
+        &mut self,
+        domain: &'static str,
+        op: &'static str,
+        version: i64,
+        builder: Option<OpBuilderFn>,
+    ) {
+        let builders = self
+            .0
+            .entry(domain)
+            .or_insert_with(HashMap::new)
+            .entry(op)
+            .or_insert_with(Vec::new);
+        builders.retain(|b| b.0 != version);
+        builders.push((version, builder));
+        builders.sort_by_key(|b| b.0);
+    }
+
+    /// Whether some version of the operator is known.
+    pub fn contains(&self, domain: &str, op: &str) -> bool {
+        self.0.get(domain).map(|ops| ops.contains_key(op)).unwrap_or(false)
+    }
+
+    /// Finds the builder of an operator for an opset version.
+    pub fn get(&self, domain: &str, op: &str, version: i64) -> TfdResult<OpBuilderFn> {
+        let builders = self
+            .0
+            .get(domain)
+            .and_then(|ops| ops.get(op))
+            .ok_or_else(|| format!("Unknown operator {} in domain {:?}", op, domain))?;
+        let supported: Vec<i64> = builders
+            .iter()
+            .filter(|b| b.1.is_some())
+            .map(|b| b.0)
+            .collect();
+        match builders.iter().rev().find(|b| b.0 <= version) {
+            Some(&(_, Some(builder))) => Ok(builder),
+            _ => bail!(
+                "Operator {} is not supported in opset {} (supported from opsets {:?})",
+                op,
+                version,
+                supported
+            ),
+        }
+    }
+}
 
 pub struct OpBuilder(OpRegister);
 
 impl OpBuilder {
     pub fn new() -> OpBuilder {
-        let mut reg = OpRegister::new();
+        let mut reg = OpRegister::default();
         reg.insert("Const", konst);
+        array::register_all_ops(&mut reg);
+        #[cfg(feature = "image_ops")]
+        image::register_all_ops(&mut reg);
         logic::register_all_ops(&mut reg);
         math::register_all_ops(&mut reg);
         nn::register_all_ops(&mut reg);
-        /*
-        array::register_all_ops(&mut reg);
-        reg.insert("Placeholder", ::ops::source::Source::build);
-        */
         OpBuilder(reg)
     }
 
-    pub fn build(&self, pb: &NodeProto) -> TfdResult<Box<Op>> {
-        match self.0.get(pb.get_op_type()) {
-            Some(builder) => builder(pb),
-            None => Ok(Box::new(::tfdeploy::ops::unimpl::UnimplementedOp(
+    /// Builds the op for a node, given the opset version the model imports
+    /// for the node's domain.
+    pub fn build(&self, pb: &NodeProto, version: i64) -> TfdResult<Box<Op>> {
+        let domain = domain(pb.get_domain());
+        if self.0.contains(domain, pb.get_op_type()) {
+            let builder = self.0.get(domain, pb.get_op_type(), version)?;
+            builder(pb)
+        } else {
+            Ok(Box::new(::tfdeploy::ops::unimpl::UnimplementedOp(
                 pb.get_op_type().to_string(),
                 format!("{:?}", pb)
-            ))),
+            )))
         }
     }
 }
@@ -38,3 +132,41 @@ fn konst(node: &NodeProto) -> TfdResult<Box<Op>> {
     let v = node.get_attr_tensor("value")?;
     Ok(Box::new(::tfdeploy::ops::konst::Const::for_tensor(v)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(op: &str) -> NodeProto {
+        let mut node = NodeProto::new();
+        node.set_op_type(op.to_string());
+        node
+    }
+
+    #[test]
+    fn dispatch_on_opset() {
+        let builder = OpBuilder::new();
+        assert!(builder.build(&node("Reshape"), 4).is_err());
+        assert!(builder.build(&node("Reshape"), 5).is_ok());
+        assert!(builder.build(&node("Squeeze"), 13).is_err());
+        let softmax = builder.build(&node("Softmax"), 13).unwrap();
+        assert_eq!(
+            format!("{:?}", softmax),
+            format!("{:?}", ::tfdeploy::ops::nn::Softmax::new(-1, false))
+        );
+    }
+
+    #[test]
+    fn arg_max_select_last_index() {
+        use pb::{AttributeProto, AttributeProto_AttributeType};
+        let builder = OpBuilder::new();
+        let mut arg_max = node("ArgMax");
+        assert!(builder.build(&arg_max, 12).is_ok());
+        let mut attr = AttributeProto::new();
+        attr.set_name("select_last_index".to_string());
+        attr.set_field_type(AttributeProto_AttributeType::INT);
+        attr.set_i(1);
+        arg_max.mut_attribute().push(attr);
+        assert!(builder.build(&arg_max, 12).is_err());
+    }
+}
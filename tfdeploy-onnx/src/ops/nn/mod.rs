@@ -1,9 +1,153 @@
 use tfdeploy::ops as tfdops;
+use tfdeploy::ops::nn::{DataFormat, KernelFormat, PaddingSpec, PoolSpec};
+use tfdeploy::TfdResult;
 
 use ops::OpRegister;
+use pb::NodeProto;
 
 pub fn register_all_ops(reg: &mut OpRegister) {
+    reg.insert("AveragePool", average_pool);
+    reg.insert("BatchNormalization", |node| {
+        let epsilon = node.get_attr_opt_float("epsilon")?.unwrap_or(1e-5);
+        Ok(Box::new(tfdops::nn::BatchNorm::new(epsilon, 1)))
+    });
+    reg.insert("Conv", conv);
+    reg.insert("ConvTranspose", conv_transpose);
+    reg.insert("GlobalAveragePool", |_| {
+        Ok(Box::new(tfdops::nn::GlobalAvgPool::new(DataFormat::NCHW)))
+    });
+    reg.insert("GlobalMaxPool", |_| {
+        Ok(Box::new(tfdops::nn::GlobalMaxPool::new(DataFormat::NCHW)))
+    });
+    reg.insert("InstanceNormalization", |node| {
+        let epsilon = node.get_attr_opt_float("epsilon")?.unwrap_or(1e-5);
+        Ok(Box::new(tfdops::nn::InstanceNorm::new(epsilon)))
+    });
+    reg.insert("LRN", lrn);
+    reg.insert("MaxPool", max_pool);
     reg.insert("Relu", |_| Ok(Box::new(tfdops::nn::Relu::default())));
     reg.insert("Sigmoid", |_| Ok(Box::new(tfdops::nn::Sigmoid::default())));
+    reg.insert("Softmax", |node| {
+        Ok(Box::new(tfdops::nn::Softmax::new(softmax_axis(node, 1)?, true)))
+    });
+    reg.insert_since("Softmax", 13, |node| {
+        Ok(Box::new(tfdops::nn::Softmax::new(softmax_axis(node, -1)?, false)))
+    });
+    reg.insert("LogSoftmax", |node| {
+        Ok(Box::new(tfdops::nn::LogSoftmax::new(softmax_axis(node, 1)?, true)))
+    });
+    reg.insert_since("LogSoftmax", 13, |node| {
+        Ok(Box::new(tfdops::nn::LogSoftmax::new(softmax_axis(node, -1)?, false)))
+    });
 }
 
+/// Up to opset 12, ONNX softmaxes normalize over `axis` and the following
+/// ones. From opset 13 on, they normalize over `axis` only, which defaults
+/// to the last one.
+fn softmax_axis(node: &NodeProto, default: i64) -> TfdResult<i64> {
+    Ok(node.get_attr_opt_int("axis")?.unwrap_or(default))
+}
+
+fn lrn(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let size = node
+        .get_attr_opt_int("size")?
+        .ok_or("LRN expects a size attribute")?;
+    if size < 1 {
+        bail!("LRN expects a positive size, got {}", size)
+    }
+    let alpha = node.get_attr_opt_float("alpha")?.unwrap_or(0.0001);
+    let beta = node.get_attr_opt_float("beta")?.unwrap_or(0.75);
+    let bias = node.get_attr_opt_float("bias")?.unwrap_or(1.0);
+    Ok(Box::new(tfdops::nn::Lrn::new(alpha / size as f32, beta, bias, size as usize, 1)))
+}
+
+fn usizes(node: &NodeProto, name: &str) -> TfdResult<Option<Vec<usize>>> {
+    match node.get_attr_opt_ints(name)? {
+        Some(ints) => {
+            if ints.iter().any(|&i| i < 0) {
+                bail!("{} expects non negative {}, got {:?}", node.get_op_type(), name, ints)
+            }
+            Ok(Some(ints.into_iter().map(|i| i as usize).collect()))
+        }
+        None => Ok(None),
+    }
+}
+
+fn padding(node: &NodeProto) -> TfdResult<PaddingSpec> {
+    Ok(match node.get_attr_opt_str("auto_pad")?.unwrap_or("NOTSET") {
+        "NOTSET" => match usizes(node, "pads")? {
+            // pads are all the beginnings, then all the ends.
+            Some(ref pads) if pads.len() % 2 == 0 => {
+                let (before, after) = pads.split_at(pads.len() / 2);
+                PaddingSpec::Explicit(before.to_vec(), after.to_vec())
+            }
+            Some(pads) => bail!("Invalid pads {:?}", pads),
+            None => PaddingSpec::Valid,
+        },
+        "VALID" => PaddingSpec::Valid,
+        "SAME_UPPER" => PaddingSpec::SameUpper,
+        "SAME_LOWER" => PaddingSpec::SameLower,
+        pad => bail!("Unsupported auto_pad {}", pad),
+    })
+}
+
+fn group(node: &NodeProto) -> TfdResult<usize> {
+    match node.get_attr_opt_int("group")?.unwrap_or(1) {
+        g if g > 0 => Ok(g as usize),
+        g => bail!("Invalid group {}", g),
+    }
+}
+
+fn conv(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    Ok(Box::new(tfdops::nn::Conv::new(
+        DataFormat::NCHW,
+        KernelFormat::OIHW,
+        padding(node)?,
+        usizes(node, "dilations")?,
+        usizes(node, "strides")?,
+        group(node)?,
+    )))
+}
+
+fn conv_transpose(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    Ok(Box::new(tfdops::nn::ConvTranspose::new(
+        DataFormat::NCHW,
+        padding(node)?,
+        usizes(node, "dilations")?,
+        usizes(node, "strides")?,
+        usizes(node, "output_padding")?,
+        usizes(node, "output_shape")?,
+        group(node)?,
+    )))
+}
+
+fn pool_spec(node: &NodeProto) -> TfdResult<PoolSpec> {
+    let kernel_shape = usizes(node, "kernel_shape")?
+        .ok_or_else(|| format!("{} expects a kernel_shape", node.get_op_type()))?;
+    if node.get_attr_opt_int("ceil_mode")?.unwrap_or(0) != 0 {
+        bail!("{} with ceil_mode is not supported", node.get_op_type())
+    }
+    if let Some(dilations) = usizes(node, "dilations")? {
+        if dilations.iter().any(|&d| d != 1) {
+            bail!("{} with dilations is not supported", node.get_op_type())
+        }
+    }
+    Ok(PoolSpec::new(
+        DataFormat::NCHW,
+        kernel_shape,
+        padding(node)?,
+        usizes(node, "strides")?,
+    ))
+}
+
+fn average_pool(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    let count_include_pad = node.get_attr_opt_int("count_include_pad")?.unwrap_or(0) != 0;
+    Ok(Box::new(tfdops::nn::AvgPool::new(pool_spec(node)?, count_include_pad)))
+}
+
+fn max_pool(node: &NodeProto) -> TfdResult<Box<tfdops::Op>> {
+    if node.get_output().len() > 1 {
+        bail!("MaxPool indices output is not supported")
+    }
+    Ok(Box::new(tfdops::nn::MaxPool::new(pool_spec(node)?)))
+}
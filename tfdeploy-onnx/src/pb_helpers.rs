@@ -44,4 +44,54 @@ impl NodeProto {
             )
         })?)
     }
+
+    pub fn get_attr_opt_graph(&self, name: &str) -> TfdResult<Option<&GraphProto>> {
+        Ok(self
+            .get_attr_opt_with_type(name, AttributeProto_AttributeType::GRAPH)?
+            .map(|attr| attr.get_g()))
+    }
+
+    pub fn get_attr_graph(&self, name: &str) -> TfdResult<&GraphProto> {
+        Ok(self.get_attr_opt_graph(name)?.ok_or_else(|| {
+            format!(
+                "Node {} ({}) expected graph attribute '{}'",
+                self.get_name(),
+                self.get_op_type(),
+                name
+            )
+        })?)
+    }
+
+    pub fn get_attr_opt_int(&self, name: &str) -> TfdResult<Option<i64>> {
+        Ok(self
+            .get_attr_opt_with_type(name, AttributeProto_AttributeType::INT)?
+            .map(|attr| attr.get_i()))
+    }
+
+    pub fn get_attr_opt_ints(&self, name: &str) -> TfdResult<Option<Vec<i64>>> {
+        Ok(self
+            .get_attr_opt_with_type(name, AttributeProto_AttributeType::INTS)?
+            .map(|attr| attr.get_ints().to_vec()))
+    }
+
+    pub fn get_attr_opt_float(&self, name: &str) -> TfdResult<Option<f32>> {
+        Ok(self
+            .get_attr_opt_with_type(name, AttributeProto_AttributeType::FLOAT)?
+            .map(|attr| attr.get_f()))
+    }
+
+    pub fn get_attr_opt_floats(&self, name: &str) -> TfdResult<Option<Vec<f32>>> {
+        Ok(self
+            .get_attr_opt_with_type(name, AttributeProto_AttributeType::FLOATS)?
+            .map(|attr| attr.get_floats().to_vec()))
+    }
+
+    pub fn get_attr_opt_str(&self, name: &str) -> TfdResult<Option<&str>> {
+        match self.get_attr_opt_with_type(name, AttributeProto_AttributeType::STRING)? {
+            Some(attr) => Ok(Some(::std::str::from_utf8(attr.get_s()).map_err(|e| {
+                format!("Node {} attribute {}: {}", self.get_name(), name, e)
+            })?)),
+            None => Ok(None),
+        }
+    }
 }
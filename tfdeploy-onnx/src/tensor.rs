@@ -1,4 +1,6 @@
 use tfdeploy::*;
+use tfdeploy::analyser::types::{GenericFact, ShapeFact};
+use tfdeploy::tensor::{f16, Complex};
 use pb::*;
 
 impl TfdFrom<TensorProto_DataType> for DatumType {
@@ -8,12 +10,17 @@ impl TfdFrom<TensorProto_DataType> for DatumType {
             &BOOL => Ok(DatumType::Bool),
             &UINT8 => Ok(DatumType::U8),
             &UINT16 => Ok(DatumType::U16),
+            &UINT32 => Ok(DatumType::U32),
+            &UINT64 => Ok(DatumType::U64),
             &INT8 => Ok(DatumType::I8),
             &INT16 => Ok(DatumType::I16),
             &INT32 => Ok(DatumType::I32),
             &INT64 => Ok(DatumType::I64),
+            &FLOAT16 => Ok(DatumType::F16),
             &FLOAT => Ok(DatumType::F32),
             &DOUBLE => Ok(DatumType::F64),
+            &COMPLEX64 => Ok(DatumType::ComplexF32),
+            &COMPLEX128 => Ok(DatumType::ComplexF64),
             &STRING => Ok(DatumType::String),
             _ => Err(format!("Unknown DatumType {:?}", t))?,
         }
@@ -37,25 +44,55 @@ impl TfdFrom<TensorProto_DataType> for DatumType {
 
 impl TfdFrom<TypeProto_Tensor> for TensorFact {
     fn tfd_from(t: &TypeProto_Tensor) -> TfdResult<TensorFact> {
-        let mut fact = TensorFact::default();
-        if t.has_elem_type() {
-            fact = fact.with_datum_type(t.get_elem_type().to_tfd()?);
-        }
-        if t.has_shape() {
-            let shape = t.get_shape();
-            let shape: Vec<usize> = shape
-                .get_dim()
-                .iter()
-                .map(|d| d.get_dim_value() as usize)
-                .collect();
-            fact = fact.with_shape(shape)
-        }
-        Ok(fact)
+        tensor_fact(t, "", &mut SymbolTable::default())
     }
 }
 
+/// Translates the type of the named tensor, giving its named and unknown
+/// dimensions symbols from the table.
+///
+/// Unknown dimensions are named after the tensor and their axis, so they
+/// never get merged with another one. Dimensions the table has no symbol
+/// left for stay unknown.
+pub fn tensor_fact(
+    t: &TypeProto_Tensor,
+    name: &str,
+    symbols: &mut SymbolTable,
+) -> TfdResult<TensorFact> {
+    let mut fact = TensorFact::default();
+    if t.has_elem_type() {
+        fact = fact.with_datum_type(t.get_elem_type().to_tfd()?);
+    }
+    if t.has_shape() {
+        let shape = t
+            .get_shape()
+            .get_dim()
+            .iter()
+            .enumerate()
+            .map(|(axis, d)| {
+                let dim = if d.has_dim_value() {
+                    Some(TDim::from(d.get_dim_value()))
+                } else if d.has_dim_param() && d.get_dim_param() != "" {
+                    symbols.sym(d.get_dim_param())
+                } else {
+                    symbols.sym(&format!("{}:{}", name, axis))
+                };
+                dim.map(GenericFact::Only).unwrap_or(GenericFact::Any)
+            })
+            .collect();
+        fact = fact.with_shape(ShapeFact::closed(shape))
+    }
+    Ok(fact)
+}
+
 impl TfdFrom<TensorProto> for Tensor {
     fn tfd_from(t: &TensorProto) -> TfdResult<Tensor> {
+        if t.get_data_location() == TensorProto_DataLocation::EXTERNAL {
+            bail!(
+                "Tensor {} stores its data in an external file, load the model from its path",
+                t.get_name()
+            )
+        }
         let dt = t.get_data_type().to_tfd()?;
         let shape: Vec<usize> = t.get_dims().iter().map(|&i| i as usize).collect();
         if t.has_raw_data() {
@@ -63,12 +100,21 @@ impl TfdFrom<TensorProto> for Tensor {
                 match dt {
                     DatumType::U8 => Tensor::from_raw::<u8>(&*shape, t.get_raw_data()),
                     DatumType::U16 => Tensor::from_raw::<u16>(&*shape, t.get_raw_data()),
+                    DatumType::U32 => Tensor::from_raw::<u32>(&*shape, t.get_raw_data()),
+                    DatumType::U64 => Tensor::from_raw::<u64>(&*shape, t.get_raw_data()),
                     DatumType::I8 => Tensor::from_raw::<i8>(&*shape, t.get_raw_data()),
                     DatumType::I16 => Tensor::from_raw::<i16>(&*shape, t.get_raw_data()),
                     DatumType::I32 => Tensor::from_raw::<i32>(&*shape, t.get_raw_data()),
                     DatumType::I64 => Tensor::from_raw::<i64>(&*shape, t.get_raw_data()),
+                    DatumType::F16 => Tensor::from_raw::<f16>(&*shape, t.get_raw_data()),
                     DatumType::F32 => Tensor::from_raw::<f32>(&*shape, t.get_raw_data()),
                     DatumType::F64 => Tensor::from_raw::<f64>(&*shape, t.get_raw_data()),
+                    DatumType::ComplexF32 => {
+                        Tensor::from_raw::<Complex<f32>>(&*shape, t.get_raw_data())
+                    }
+                    DatumType::ComplexF64 => {
+                        Tensor::from_raw::<Complex<f64>>(&*shape, t.get_raw_data())
+                    }
                     DatumType::Bool => {
                         Ok(Tensor::from_raw::<u8>(&*shape, t.get_raw_data())?.into_array::<u8>()?.mapv(|x| x != 0).into())
                     }
@@ -86,6 +132,43 @@ impl TfdFrom<TensorProto> for Tensor {
                 DatumType::I64 => Tensor::i64s(&*shape, t.get_int64_data()),
                 DatumType::F32 => Tensor::f32s(&*shape, t.get_float_data()),
                 DatumType::F64 => Tensor::f64s(&*shape, t.get_double_data()),
+                DatumType::U32 => Tensor::u32s(
+                    &*shape,
+                    &*t.get_uint64_data().iter().map(|&x| x as u32).collect::<Vec<_>>(),
+                ),
+                DatumType::U64 => Tensor::u64s(&*shape, t.get_uint64_data()),
+                DatumType::F16 => Tensor::f16s(
+                    &*shape,
+                    &*t.get_int32_data()
+                        .iter()
+                        .map(|&x| f16::from_bits(x as u16))
+                        .collect::<Vec<_>>(),
+                ),
+                DatumType::ComplexF32 => {
+                    if t.get_float_data().len() % 2 != 0 {
+                        bail!("Complex tensor with an odd number of float values")
+                    }
+                    Tensor::complex_f32s(
+                        &*shape,
+                        &*t.get_float_data()
+                            .chunks(2)
+                            .map(|c| Complex::new(c[0], c[1]))
+                            .collect::<Vec<_>>(),
+                    )
+                }
+                DatumType::ComplexF64 => {
+                    if t.get_double_data().len() % 2 != 0 {
+                        bail!("Complex tensor with an odd number of double values")
+                    }
+                    Tensor::complex_f64s(
+                        &*shape,
+                        &*t.get_double_data()
+                            .chunks(2)
+                            .map(|c| Complex::new(c[0], c[1]))
+                            .collect::<Vec<_>>(),
+                    )
+                }
+                DatumType::String => Tensor::strings(&*shape, t.get_string_data()),
                 _ => unimplemented!("FIXME, tensor loading"),
             }
         }
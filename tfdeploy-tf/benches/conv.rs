@@ -6,8 +6,7 @@ extern crate tfdeploy;
 
 use criterion::Criterion;
 
-use tfdeploy::tf::ops::nn::conv2d::*;
-use tfdeploy::tf::ops::nn::local_patch::*;
+use tfdeploy::ops::nn::{Conv, DataFormat, KernelFormat, PaddingSpec};
 use tfdeploy::*;
 
 use tfdeploy::ops::Op;
@@ -21,10 +20,17 @@ fn mk(sizes: &[usize]) -> Tensor {
 
 fn conv(bencher: &mut Criterion) {
     let stride = 1;
-    let conv = Conv2D::<f32>::new(LocalPatch::valid(stride, stride));
+    let conv = Conv::new(
+        DataFormat::NHWC,
+        KernelFormat::HWIO,
+        PaddingSpec::Valid,
+        None,
+        Some(vec![stride, stride]),
+        1,
+    );
     let inputs = tvec![mk(&[1, 82, 1, 40]).into(), mk(&[41, 1, 40, 128]).into()];
     conv.eval(inputs.clone()).unwrap();
-    bencher.bench_function("Conv2D<f32>(1x82x1x40 41x1x40x128)", move |b| {
+    bencher.bench_function("Conv(1x82x1x40 41x1x40x128)", move |b| {
         b.iter(|| conv.eval(inputs.clone()).unwrap())
     });
 }
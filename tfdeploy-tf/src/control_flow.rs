@@ -0,0 +1,784 @@
+//! Lowering of Tensorflow v1 control flow to structured ops.
+//!
+//! `tf.while_loop` produces frames of `Enter`, `Merge`, `Switch`,
+//! `NextIteration` and `Exit` nodes, making the graph cyclic, and `tf.cond`
+//! produces `Switch` and `Merge` nodes relying on dead tensors so that only
+//! one branch runs. Before the model is built, each frame is replaced by a
+//! `While` node and each conditional by an `If` node, their sub-models being
+//! built from the nodes inside the construct. Constructs are lowered from the
+//! outermost in, the nested ones being lowered when their enclosing sub-model
+//! is built.
+use std::collections::{HashMap, HashSet};
+
+use tfdeploy::model::{Model, OutletId};
+use tfdeploy::ops::control_flow::{If, SubGraph, While};
+use tfdeploy::ops::Op;
+use tfdeploy::{TfdResult, ToTfd};
+
+use tfpb::graph::GraphDef;
+use tfpb::node_def::NodeDef;
+use tfpb::types::DataType;
+
+/// Prebuilt ops, by node name.
+pub type LoweredOps = HashMap<String, Box<Op>>;
+
+/// For each node, its consumers and the slot they use.
+type Consumers = Vec<Vec<(usize, Option<usize>)>>;
+
+/// A loop frame name, with its `Enter` and `Merge` nodes.
+type FrameNodes = (String, Vec<usize>, Vec<usize>);
+
+/// Replaces the control flow constructs of a graph, returning the graph and
+/// the ops of the nodes standing for the constructs, by node name.
+pub fn lower(graph: &GraphDef) -> TfdResult<(GraphDef, LoweredOps)> {
+    let mut lowered = graph.clone();
+    let mut lowering = Lowering {
+        nodes: lowered.take_node().into_vec(),
+        ops: HashMap::new(),
+    };
+    while let Some(construct) = lowering.outermost()? {
+        match construct {
+            Construct::Frame(frame) => lowering.lower_while(frame)?,
+            Construct::Cond(cond) => lowering.lower_cond(cond)?,
+        }
+    }
+    lowered.set_node(lowering.nodes.into());
+    Ok((lowered, lowering.ops))
+}
+
+/// Splits a node input into the producer name, its output slot, and whether
+/// it is a control dependency.
+fn parse_input(input: &str) -> TfdResult<(&str, usize, bool)> {
+    if input.starts_with('^') {
+        return Ok((&input[1..], 0, true));
+    }
+    let mut splits = input.splitn(2, ':');
+    let name = splits.next().unwrap();
+    let slot = match splits.next() {
+        Some(slot) => slot.parse::<usize>()?,
+        None => 0,
+    };
+    Ok((name, slot, false))
+}
+
+fn output_name(node: &str, slot: usize) -> String {
+    if slot == 0 {
+        node.to_string()
+    } else {
+        format!("{}:{}", node, slot)
+    }
+}
+
+/// A `tf.while_loop` frame, its loop variables in the order of their `Merge`.
+struct Frame {
+    name: String,
+    enters: Vec<usize>,
+    merges: Vec<usize>,
+    switches: Vec<usize>,
+    next_iterations: Vec<usize>,
+    exits: Vec<Option<usize>>,
+    loop_cond: usize,
+    members: HashSet<usize>,
+}
+
+/// A `tf.cond`: the switches on the same predicate, the nodes of each branch,
+/// and the merges joining them.
+struct Cond {
+    pred: String,
+    switches: Vec<usize>,
+    then_nodes: HashSet<usize>,
+    else_nodes: HashSet<usize>,
+    merges: Vec<usize>,
+}
+
+enum Construct {
+    Frame(Frame),
+    Cond(Cond),
+}
+
+impl Construct {
+    /// The nodes the construct starts from.
+    fn anchors(&self) -> &[usize] {
+        match self {
+            Construct::Frame(frame) => &frame.enters,
+            Construct::Cond(cond) => &cond.switches,
+        }
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        match self {
+            Construct::Frame(frame) => frame.members.contains(&node),
+            Construct::Cond(cond) => {
+                cond.then_nodes.contains(&node) || cond.else_nodes.contains(&node)
+            }
+        }
+    }
+}
+
+struct Lowering {
+    nodes: Vec<NodeDef>,
+    ops: LoweredOps,
+}
+
+impl Lowering {
+    fn index(&self) -> HashMap<&str, usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(ix, n)| (n.get_name(), ix))
+            .collect()
+    }
+
+    fn producer(&self, index: &HashMap<&str, usize>, input: &str) -> TfdResult<(usize, usize)> {
+        let (name, slot, _) = parse_input(input)?;
+        let node = index
+            .get(name)
+            .ok_or_else(|| format!("No node {} found", name))?;
+        Ok((*node, slot))
+    }
+
+    /// The `ix`-th data input of a node, control dependencies left aside.
+    fn data_input(&self, node: usize, ix: usize) -> TfdResult<&str> {
+        let pb = &self.nodes[node];
+        Ok(pb
+            .get_input()
+            .iter()
+            .filter(|i| !i.starts_with('^'))
+            .nth(ix)
+            .ok_or_else(|| {
+                format!("Node {} ({}) expects at least {} inputs", pb.get_name(), pb.get_op(), ix + 1)
+            })?)
+    }
+
+    /// The type of an output, from the attributes of its producer, or from
+    /// its first input for the ops passing a value through untyped.
+    fn output_type(
+        &self,
+        index: &HashMap<&str, usize>,
+        node: usize,
+        slot: usize,
+    ) -> TfdResult<DataType> {
+        let (mut node, mut slot) = (node, slot);
+        let mut visited = HashSet::new();
+        while visited.insert(node) {
+            let pb = &self.nodes[node];
+            let attr = |name: &str| pb.get_attr().get(name).map(|a| a.get_field_type());
+            let dt = match pb.get_op() {
+                "Merge" if slot == 1 => Some(DataType::DT_INT32),
+                "Cast" => attr("DstT"),
+                "Shape" | "Size" => attr("out_type").or(Some(DataType::DT_INT32)),
+                "Rank" => Some(DataType::DT_INT32),
+                "ArgMax" | "ArgMin" => attr("output_type").or(Some(DataType::DT_INT64)),
+                "Gather" | "GatherV2" | "GatherNd" => attr("Tparams"),
+                "Equal" | "NotEqual" | "Less" | "LessEqual" | "Greater" | "GreaterEqual"
+                | "LogicalAnd" | "LogicalOr" | "LogicalNot" | "LoopCond" | "IsNan" | "IsInf"
+                | "IsFinite" => Some(DataType::DT_BOOL),
+                _ => attr("dtype").or_else(|| attr("T")),
+            };
+            if let Some(dt) = dt {
+                return Ok(dt);
+            }
+            let producer = self.producer(index, self.data_input(node, 0)?)?;
+            node = producer.0;
+            slot = producer.1;
+        }
+        bail!("Can not infer the type of {}", output_name(self.nodes[node].get_name(), slot))
+    }
+
+    /// For each node, its consumers and the slot they use, `None` for control
+    /// dependencies.
+    fn consumers(&self) -> TfdResult<Consumers> {
+        let index = self.index();
+        let mut consumers = vec![vec![]; self.nodes.len()];
+        for (ix, node) in self.nodes.iter().enumerate() {
+            for input in node.get_input() {
+                let (producer, slot) = self.producer(&index, input)?;
+                let control = parse_input(input)?.2;
+                consumers[producer].push((ix, if control { None } else { Some(slot) }));
+            }
+        }
+        Ok(consumers)
+    }
+
+    /// The nodes depending, directly or not, on the given outputs (any output
+    /// and control for `None`), not going through the `stop` nodes.
+    fn descendants(
+        &self,
+        consumers: &Consumers,
+        from: &[(usize, Option<usize>)],
+        stop: &HashSet<usize>,
+    ) -> HashSet<usize> {
+        let mut todo: Vec<usize> = from
+            .iter()
+            .flat_map(|&(node, slot)| {
+                consumers[node]
+                    .iter()
+                    .filter(move |c| slot.is_none() || c.1 == slot)
+                    .map(|c| c.0)
+            })
+            .collect();
+        let mut reached = HashSet::new();
+        while let Some(node) = todo.pop() {
+            if !stop.contains(&node) && reached.insert(node) {
+                todo.extend(consumers[node].iter().map(|c| c.0));
+            }
+        }
+        reached
+    }
+
+    /// Follows `Identity` nodes up to the actual producer of a value.
+    fn resolve(&self, index: &HashMap<&str, usize>, input: &str) -> TfdResult<String> {
+        let (mut node, mut slot) = self.producer(index, input)?;
+        while self.nodes[node].get_op() == "Identity" && !self.nodes[node].get_input().is_empty() {
+            let producer = self.producer(index, &self.nodes[node].get_input()[0])?;
+            node = producer.0;
+            slot = producer.1;
+        }
+        Ok(output_name(self.nodes[node].get_name(), slot))
+    }
+
+    /// The construct not nested in any other.
+    fn outermost(&self) -> TfdResult<Option<Construct>> {
+        let index = self.index();
+        let consumers = self.consumers()?;
+        let mut constructs = vec![];
+        for (name, enters, merges) in self.frames(&index)? {
+            constructs.push(Construct::Frame(self.frame(&index, &consumers, name, enters, merges)?));
+        }
+        constructs.extend(self.conds(&index, &consumers)?.into_iter().map(Construct::Cond));
+        let outermost = (0..constructs.len()).find(|&c| {
+            constructs.iter().enumerate().all(|(other, construct)| {
+                other == c || constructs[c].anchors().iter().all(|&a| !construct.contains(a))
+            })
+        });
+        Ok(outermost.map(|c| constructs.swap_remove(c)))
+    }
+
+    /// The nodes of each loop frame, found in one pass over the graph.
+    fn frames(&self, index: &HashMap<&str, usize>) -> TfdResult<Vec<FrameNodes>> {
+        let mut frames: Vec<FrameNodes> = vec![];
+        let mut frame_by_name = HashMap::new();
+        let mut frame_by_enter = HashMap::new();
+        for (ix, node) in self.nodes.iter().enumerate() {
+            if node.get_op() == "Enter" {
+                let name = node.get_attr_str("frame_name")?;
+                let frame = *frame_by_name.entry(name.clone()).or_insert(frames.len());
+                if frame == frames.len() {
+                    frames.push((name, vec![], vec![]));
+                }
+                frames[frame].1.push(ix);
+                frame_by_enter.insert(ix, frame);
+            }
+        }
+        for (ix, node) in self.nodes.iter().enumerate() {
+            if node.get_op() != "Merge" {
+                continue;
+            }
+            for input in node.get_input().iter().filter(|i| !i.starts_with('^')) {
+                if let Some(&frame) = frame_by_enter.get(&self.producer(index, input)?.0) {
+                    frames[frame].2.push(ix);
+                    break;
+                }
+            }
+        }
+        Ok(frames)
+    }
+
+    fn frame(
+        &self,
+        index: &HashMap<&str, usize>,
+        consumers: &Consumers,
+        name: String,
+        enters: Vec<usize>,
+        merges: Vec<usize>,
+    ) -> TfdResult<Frame> {
+        let mut next_iterations = vec![];
+        for &merge in &merges {
+            let node = &self.nodes[merge];
+            let inputs = node
+                .get_input()
+                .iter()
+                .filter(|i| !i.starts_with('^'))
+                .map(|i| Ok(self.producer(index, i)?.0))
+                .collect::<TfdResult<Vec<usize>>>()?;
+            let next = inputs
+                .iter()
+                .find(|i| !enters.contains(i))
+                .ok_or_else(|| format!("Merge {} of frame {} has no back edge", node.get_name(), name))?;
+            if self.nodes[*next].get_op() != "NextIteration" {
+                bail!("Merge {} of frame {} is expected to consume a NextIteration", node.get_name(), name)
+            }
+            next_iterations.push(*next);
+        }
+        let mut switches = vec![];
+        let mut exits = vec![];
+        for &merge in &merges {
+            let switch = consumers[merge]
+                .iter()
+                .find(|c| c.1 == Some(0) && self.nodes[c.0].get_op() == "Switch")
+                .ok_or_else(|| format!("Merge {} of frame {} has no Switch", self.nodes[merge].get_name(), name))?
+                .0;
+            switches.push(switch);
+            exits.push(
+                consumers[switch]
+                    .iter()
+                    .find(|c| c.1 == Some(0) && self.nodes[c.0].get_op() == "Exit")
+                    .map(|c| c.0),
+            );
+        }
+        if switches.is_empty() {
+            bail!("Frame {} has no loop variable", name)
+        }
+        let loop_cond = self.producer(index, self.data_input(switches[0], 1)?)?.0;
+        if self.nodes[loop_cond].get_op() != "LoopCond" {
+            bail!("Switches of frame {} are expected to use a LoopCond", name)
+        }
+        let stop = exits.iter().filter_map(|e| *e).collect();
+        let from: Vec<_> = enters.iter().map(|&e| (e, None)).collect();
+        let mut members = self.descendants(consumers, &from, &stop);
+        members.extend(enters.iter().cloned());
+        Ok(Frame {
+            name,
+            enters,
+            merges,
+            switches,
+            next_iterations,
+            exits,
+            loop_cond,
+            members,
+        })
+    }
+
+    /// The conditionals, made of the switches not in a loop frame, grouped by
+    /// predicate.
+    fn conds(&self, index: &HashMap<&str, usize>, consumers: &Consumers) -> TfdResult<Vec<Cond>> {
+        let mut groups: Vec<(String, Vec<usize>)> = vec![];
+        for (ix, node) in self.nodes.iter().enumerate() {
+            if node.get_op() != "Switch" {
+                continue;
+            }
+            let pred = self.data_input(ix, 1)?;
+            if self.nodes[self.producer(index, pred)?.0].get_op() == "LoopCond" {
+                continue;
+            }
+            let pred = self.resolve(index, pred)?;
+            match groups.iter().position(|g| g.0 == pred) {
+                Some(g) => groups[g].1.push(ix),
+                None => groups.push((pred, vec![ix])),
+            }
+        }
+        let mut conds = vec![];
+        for (pred, switches) in groups {
+            let no_stop = HashSet::new();
+            let from = |slot| switches.iter().map(|&s| (s, Some(slot))).collect::<Vec<_>>();
+            let mut then_nodes = self.descendants(consumers, &from(1), &no_stop);
+            let mut else_nodes = self.descendants(consumers, &from(0), &no_stop);
+            let both: HashSet<usize> = then_nodes.intersection(&else_nodes).cloned().collect();
+            then_nodes.retain(|n| !both.contains(n));
+            else_nodes.retain(|n| !both.contains(n));
+            let side = |input: &str| -> TfdResult<Option<bool>> {
+                let (node, slot) = self.producer(index, input)?;
+                let switch = switches.contains(&node);
+                Ok(if then_nodes.contains(&node) || (switch && slot == 1) {
+                    Some(true)
+                } else if else_nodes.contains(&node) || (switch && slot == 0) {
+                    Some(false)
+                } else {
+                    None
+                })
+            };
+            let mut merges = vec![];
+            for (ix, node) in self.nodes.iter().enumerate() {
+                if node.get_op() == "Merge" && both.contains(&ix) {
+                    let sides = node
+                        .get_input()
+                        .iter()
+                        .map(|i| side(i))
+                        .collect::<TfdResult<Vec<_>>>()?;
+                    if sides.contains(&Some(true)) && sides.contains(&Some(false)) {
+                        merges.push(ix);
+                    }
+                }
+            }
+            conds.push(Cond {
+                pred,
+                switches,
+                then_nodes,
+                else_nodes,
+                merges,
+            });
+        }
+        Ok(conds)
+    }
+
+    fn lower_while(&mut self, frame: Frame) -> TfdResult<()> {
+        let index = self.index();
+        let loop_enters = frame
+            .merges
+            .iter()
+            .map(|&m| {
+                let inputs = self.nodes[m].get_input();
+                let producers = inputs
+                    .iter()
+                    .map(|i| Ok(self.producer(&index, i)?.0))
+                    .collect::<TfdResult<Vec<_>>>()?;
+                Ok(*producers.iter().find(|p| frame.enters.contains(p)).unwrap())
+            })
+            .collect::<TfdResult<Vec<usize>>>()?;
+        // Loop invariants are read from the outer graph.
+        let substitutes: HashMap<(usize, usize), String> = frame
+            .enters
+            .iter()
+            .filter(|e| !loop_enters.contains(e))
+            .map(|&e| Ok(((e, 0), self.data_input(e, 0)?.to_string())))
+            .collect::<TfdResult<_>>()?;
+        let mut inputs: Vec<String> = loop_enters
+            .iter()
+            .map(|&e| Ok(self.data_input(e, 0)?.to_string()))
+            .collect::<TfdResult<_>>()?;
+
+        let explicit = frame.merges.iter().map(|&m| (m, 0)).collect();
+        let mut cond = Extraction::new(self, &index, &frame.members, explicit, substitutes.clone());
+        let pred = cond.map(self.data_input(frame.loop_cond, 0)?)?;
+        let cond = cond.finish(vec![pred], &mut inputs)?;
+
+        let explicit = frame.switches.iter().map(|&s| (s, 1)).collect();
+        let mut body = Extraction::new(self, &index, &frame.members, explicit, substitutes);
+        let next = frame
+            .next_iterations
+            .iter()
+            .map(|&n| body.map(self.data_input(n, 0)?))
+            .collect::<TfdResult<Vec<_>>>()?;
+        let body = body.finish(next, &mut inputs)?;
+
+        let op = While::new(cond, body, frame.merges.len());
+        let mut removed = frame.members;
+        removed.extend(frame.exits.iter().filter_map(|e| *e));
+        let outputs = frame
+            .exits
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, e)| e.map(|e| ((e, 0), ix)))
+            .collect();
+        self.replace(frame.name, "While", Box::new(op), inputs, removed, outputs)
+    }
+
+    fn lower_cond(&mut self, cond: Cond) -> TfdResult<()> {
+        let merge = *cond
+            .merges
+            .first()
+            .ok_or_else(|| format!("Conditional on {} has no Merge", cond.pred))?;
+        let index = self.index();
+        let mut inputs = vec![cond.pred.clone()];
+        let mut branches = vec![];
+        for &(slot, nodes) in &[(1, &cond.then_nodes), (0, &cond.else_nodes)] {
+            let substitutes = cond
+                .switches
+                .iter()
+                .map(|&s| Ok(((s, slot), self.data_input(s, 0)?.to_string())))
+                .collect::<TfdResult<_>>()?;
+            let mut branch = Extraction::new(self, &index, nodes, vec![], substitutes);
+            let mut outputs = vec![];
+            for &merge in &cond.merges {
+                for input in self.nodes[merge].get_input() {
+                    let (node, input_slot) = self.producer(&index, input)?;
+                    let in_branch = nodes.contains(&node)
+                        || (cond.switches.contains(&node) && input_slot == slot);
+                    if in_branch && !input.starts_with('^') {
+                        outputs.push(branch.map(input)?);
+                        break;
+                    }
+                }
+            }
+            branches.push(branch.finish(outputs, &mut inputs)?);
+        }
+        let else_branch = branches.pop().unwrap();
+        let then_branch = branches.pop().unwrap();
+        let op = If::new(then_branch, else_branch);
+
+        let name = format!("{}/If", self.nodes[merge].get_name());
+        let mut removed: HashSet<usize> = cond.then_nodes;
+        removed.extend(cond.else_nodes);
+        removed.extend(cond.switches);
+        removed.extend(cond.merges.iter().cloned());
+        let outputs = cond
+            .merges
+            .iter()
+            .enumerate()
+            .map(|(ix, &m)| ((m, 0), ix))
+            .collect();
+        self.replace(name, "If", Box::new(op), inputs, removed, outputs)
+    }
+
+    /// Replaces the `removed` nodes by a node running `op`, put where the
+    /// first of them was. The references to `outputs` keys are rewired to the
+    /// corresponding output of the new node.
+    fn replace(
+        &mut self,
+        name: String,
+        op_name: &str,
+        op: Box<Op>,
+        inputs: Vec<String>,
+        removed: HashSet<usize>,
+        outputs: HashMap<(usize, usize), usize>,
+    ) -> TfdResult<()> {
+        let position = outputs
+            .keys()
+            .map(|o| o.0)
+            .min()
+            .or_else(|| removed.iter().cloned().min());
+        let mut node = NodeDef::new();
+        node.set_name(name.clone());
+        node.set_op(op_name.to_string());
+        node.set_input(inputs.into());
+        let mut node = Some(node);
+        let mut nodes = vec![];
+        {
+            let index = self.index();
+            for (ix, pb) in self.nodes.iter().enumerate() {
+                if Some(ix) == position {
+                    nodes.extend(node.take());
+                }
+                if removed.contains(&ix) {
+                    continue;
+                }
+                let mut pb = pb.clone();
+                let pb_name = pb.get_name().to_string();
+                for input in pb.mut_input().iter_mut() {
+                    let (producer, slot) = self.producer(&index, input)?;
+                    if !removed.contains(&producer) {
+                        continue;
+                    }
+                    let output = outputs
+                        .iter()
+                        .find(|(o, _)| o.0 == producer && (o.1 == slot || input.starts_with('^')))
+                        .map(|(_, &output)| output);
+                    *input = match output {
+                        Some(_) if input.starts_with('^') => format!("^{}", name),
+                        Some(output) => output_name(&name, output),
+                        None => bail!(
+                            "Node {} uses {} from inside {}",
+                            pb_name,
+                            input,
+                            name
+                        ),
+                    };
+                }
+                nodes.push(pb);
+            }
+        }
+        nodes.extend(node);
+        self.nodes = nodes;
+        self.ops.insert(name, op);
+        Ok(())
+    }
+}
+
+/// A sub graph being extracted from the nodes of a construct.
+///
+/// References to the `explicit` outputs become the sub graph inputs, other
+/// references to outputs from outside the member nodes become captured
+/// inputs, read from the outer graph (or its `substitutes` entry).
+struct Extraction<'a> {
+    lowering: &'a Lowering,
+    index: &'a HashMap<&'a str, usize>,
+    members: &'a HashSet<usize>,
+    explicit: Vec<(usize, usize)>,
+    substitutes: HashMap<(usize, usize), String>,
+    captured: Vec<(NodeDef, String)>,
+    extracted: HashMap<usize, NodeDef>,
+}
+
+impl<'a> Extraction<'a> {
+    fn new(
+        lowering: &'a Lowering,
+        index: &'a HashMap<&'a str, usize>,
+        members: &'a HashSet<usize>,
+        explicit: Vec<(usize, usize)>,
+        substitutes: HashMap<(usize, usize), String>,
+    ) -> Extraction<'a> {
+        Extraction {
+            lowering,
+            index,
+            members,
+            explicit,
+            substitutes,
+            captured: vec![],
+            extracted: HashMap::new(),
+        }
+    }
+
+    /// A placeholder standing for an output of the outer graph.
+    fn placeholder(&self, name: String, node: usize, slot: usize) -> TfdResult<NodeDef> {
+        let mut placeholder = NodeDef::new();
+        placeholder.set_name(name);
+        placeholder.set_op("Placeholder".to_string());
+        let dtype = self.lowering.output_type(self.index, node, slot)?;
+        placeholder.mut_attr().insert("dtype".to_string(), dtype.into());
+        Ok(placeholder)
+    }
+
+    /// Extracts what is needed to compute an output of the outer graph,
+    /// returning the name of this value in the sub graph.
+    fn map(&mut self, input: &str) -> TfdResult<String> {
+        let (node, slot) = self.lowering.producer(self.index, input)?;
+        let nodes = &self.lowering.nodes;
+        if self.explicit.contains(&(node, slot)) {
+            return Ok(nodes[node].get_name().to_string());
+        }
+        if self.members.contains(&node) && !self.substitutes.contains_key(&(node, slot)) {
+            if !self.extracted.contains_key(&node) {
+                let mut pb = nodes[node].clone();
+                self.extracted.insert(node, NodeDef::new());
+                // Control dependencies do not matter in a sub graph.
+                let inputs = pb
+                    .get_input()
+                    .iter()
+                    .filter(|i| !i.starts_with('^'))
+                    .map(|i| self.map(i))
+                    .collect::<TfdResult<Vec<_>>>()?;
+                pb.set_input(inputs.into());
+                self.extracted.insert(node, pb);
+            }
+            return Ok(input.to_string());
+        }
+        let outer = self
+            .substitutes
+            .get(&(node, slot))
+            .cloned()
+            .unwrap_or_else(|| input.to_string());
+        let name = outer.replace(':', "/output_");
+        if !self.captured.iter().any(|c| c.1 == outer) {
+            let (producer, slot) = self.lowering.producer(self.index, &outer)?;
+            let placeholder = self.placeholder(name.clone(), producer, slot)?;
+            self.captured.push((placeholder, outer));
+        }
+        Ok(name)
+    }
+
+    /// Builds the sub graph computing `outputs`, adding the outer values it
+    /// captures to the op `inputs`.
+    fn finish(self, outputs: Vec<String>, inputs: &mut Vec<String>) -> TfdResult<SubGraph> {
+        let mut graph = GraphDef::new();
+        let mut input_names = vec![];
+        for &(node, slot) in &self.explicit {
+            let name = self.lowering.nodes[node].get_name().to_string();
+            graph.mut_node().push(self.placeholder(name.clone(), node, slot)?);
+            input_names.push(name);
+        }
+        let mut captured = vec![];
+        for (placeholder, outer) in self.captured {
+            input_names.push(placeholder.get_name().to_string());
+            graph.mut_node().push(placeholder);
+            let position = match inputs.iter().position(|i| *i == outer) {
+                Some(position) => position,
+                None => {
+                    inputs.push(outer);
+                    inputs.len() - 1
+                }
+            };
+            captured.push(position);
+        }
+        let mut extracted: Vec<_> = self.extracted.into_iter().collect();
+        extracted.sort_by_key(|e| e.0);
+        graph.mut_node().extend(extracted.into_iter().map(|e| e.1));
+        let model: Model = graph.to_tfd()?;
+        let outputs = outputs
+            .iter()
+            .map(|o| {
+                let (name, slot, _) = parse_input(o)?;
+                Ok(OutletId::new(model.node_by_name(name)?.id, slot))
+            })
+            .collect::<TfdResult<Vec<_>>>()?;
+        SubGraph::new(&model, &input_names, outputs, captured)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tfdeploy::model::Model;
+    use tfdeploy::{SimplePlan, Tensor, TfdResult, ToTfd};
+    use tfpb;
+    use tfpb::node_def::NodeDef;
+    use tfpb::types::DataType::{DT_BOOL, DT_FLOAT};
+
+    fn node(name: &str, op: &str, inputs: &[&str]) -> NodeDef {
+        inputs.iter().fold(tfpb::node().name(name).op(op), |n, i| n.input(i))
+    }
+
+    fn konst(name: &str, value: f32) -> NodeDef {
+        node(name, "Const", &[])
+            .attr("dtype", DT_FLOAT)
+            .attr("value", tfpb::tensor_f32(vec![], vec![value]))
+    }
+
+    fn enter(name: &str, input: &str) -> NodeDef {
+        node(name, "Enter", &[input])
+            .attr("T", DT_FLOAT)
+            .attr("frame_name", "while/while_context")
+    }
+
+    #[test]
+    fn while_loop() {
+        // x = 0; while x < n: x = x + step
+        let graph = tfpb::graph()
+            .node(node("n", "Placeholder", &[]).attr("dtype", DT_FLOAT))
+            .node(konst("zero", 0.0))
+            .node(konst("step", 2.0))
+            .node(enter("while/Enter", "zero"))
+            .node(enter("while/Enter_1", "n").attr("is_constant", true))
+            .node(enter("while/Enter_2", "step").attr("is_constant", true))
+            .node(node("while/Merge", "Merge", &["while/Enter", "while/NextIteration"]).attr("T", DT_FLOAT))
+            .node(node("while/Less", "Less", &["while/Merge", "while/Enter_1"]).attr("T", DT_FLOAT))
+            .node(node("while/LoopCond", "LoopCond", &["while/Less"]))
+            .node(node("while/Switch", "Switch", &["while/Merge", "while/LoopCond"]).attr("T", DT_FLOAT))
+            .node(node("while/Identity", "Identity", &["while/Switch:1"]))
+            .node(node("while/add", "Add", &["while/Identity", "while/Enter_2"]).attr("T", DT_FLOAT))
+            .node(node("while/NextIteration", "NextIteration", &["while/add"]))
+            .node(node("while/Exit", "Exit", &["while/Switch"]))
+            .node(node("output", "Identity", &["while/Exit"]));
+        let model: Model = graph.to_tfd().unwrap();
+        assert_eq!(model.node_by_name("while/while_context").unwrap().op_name, "While");
+        let plan = SimplePlan::new(&model, &["n"], &["output"]).unwrap();
+        let result = plan.run(tvec![Tensor::from(5.0f32)]).unwrap();
+        assert_eq!(result[0][0], Tensor::from(6.0f32));
+    }
+
+    #[test]
+    fn cond() {
+        // p ? x * 2 : -x
+        let graph = tfpb::graph()
+            .node(node("x", "Placeholder", &[]).attr("dtype", DT_FLOAT))
+            .node(node("p", "Placeholder", &[]).attr("dtype", DT_BOOL))
+            .node(node("cond/Switch", "Switch", &["p", "p"]).attr("T", DT_BOOL))
+            .node(node("cond/switch_t", "Identity", &["cond/Switch:1"]))
+            .node(node("cond/switch_f", "Identity", &["cond/Switch"]))
+            .node(node("cond/pred_id", "Identity", &["p"]))
+            .node(node("cond/Switch_1", "Switch", &["x", "cond/pred_id"]).attr("T", DT_FLOAT))
+            .node(konst("cond/mul/y", 2.0).input("^cond/switch_t"))
+            .node(node("cond/mul", "Mul", &["cond/Switch_1:1", "cond/mul/y"]).attr("T", DT_FLOAT))
+            .node(node("cond/Switch_2", "Switch", &["x", "cond/pred_id"]).attr("T", DT_FLOAT))
+            .node(node("cond/Neg", "Neg", &["cond/Switch_2"]).attr("T", DT_FLOAT))
+            .node(node("cond/Merge", "Merge", &["cond/Neg", "cond/mul"]).attr("T", DT_FLOAT))
+            .node(node("output", "Identity", &["cond/Merge"]));
+        let model: Model = graph.to_tfd().unwrap();
+        assert!(model.node_by_name("cond/Switch_1").is_err());
+        let plan = SimplePlan::new(&model, &["x", "p"], &["output"]).unwrap();
+        for &(p, expected) in &[(true, 6.0f32), (false, -3.0)] {
+            let result = plan
+                .run(tvec![Tensor::from(3.0f32), Tensor::from(p)])
+                .unwrap();
+            assert_eq!(result[0][0], Tensor::from(expected));
+        }
+    }
+
+    #[test]
+    fn switch_without_predicate() {
+        let graph = tfpb::graph()
+            .node(node("x", "Placeholder", &[]).attr("dtype", DT_FLOAT))
+            .node(node("Switch", "Switch", &["x"]).attr("T", DT_FLOAT))
+            .node(node("Merge", "Merge", &["Switch", "Switch:1"]).attr("T", DT_FLOAT));
+        let model: TfdResult<Model> = graph.to_tfd();
+        assert!(model.is_err());
+    }
+}
@@ -0,0 +1,301 @@
+//! Inlining of the functions of a graph library.
+//!
+//! A node calls a function when its op is the name of a function of the
+//! library, or when it is a `PartitionedCall` or `StatefulPartitionedCall`
+//! naming the function in its `f` attribute. The call is replaced by the
+//! function nodes, prefixed by the call name, followed by an `IdentityN` node
+//! with the call name forwarding the function results, output i of the call
+//! being result i.
+use std::collections::HashMap;
+
+use tfdeploy::TfdResult;
+
+use tfpb::attr_value::AttrValue;
+use tfpb::function::FunctionDef;
+use tfpb::graph::GraphDef;
+use tfpb::node_def::NodeDef;
+
+/// Output arguments of multi-output ops, for the "node:arg:index" references
+/// of function bodies. Other ops are assumed to have a single output argument.
+const OUTPUT_ARGS: &[(&str, &[&str])] = &[
+    ("FusedBatchNorm", &["y", "batch_mean", "batch_variance", "reserve_space_1", "reserve_space_2"]),
+    ("FusedBatchNormV3", &["y", "batch_mean", "batch_variance", "reserve_space_1", "reserve_space_2", "reserve_space_3"]),
+    ("Merge", &["output", "value_index"]),
+    ("Switch", &["output_false", "output_true"]),
+    ("TopKV2", &["values", "indices"]),
+    ("Unique", &["y", "idx"]),
+];
+
+/// Replaces the calls to library functions by the function bodies.
+pub fn inline(graph: &GraphDef) -> TfdResult<GraphDef> {
+    let functions: HashMap<&str, &FunctionDef> = graph
+        .get_library()
+        .get_function()
+        .iter()
+        .map(|f| (f.get_signature().get_name(), f))
+        .collect();
+    let mut inlined = graph.clone();
+    if functions.is_empty() {
+        return Ok(inlined);
+    }
+    let mut nodes = vec![];
+    for node in graph.get_node() {
+        inline_node(&functions, node, &mut vec![], &mut nodes)?;
+    }
+    inlined.set_node(nodes.into());
+    inlined.clear_library();
+    Ok(inlined)
+}
+
+/// The function called by a node, and the attributes of the call.
+fn called<'a, 'n>(
+    functions: &HashMap<&str, &'a FunctionDef>,
+    node: &'n NodeDef,
+) -> TfdResult<Option<(&'a FunctionDef, &'n HashMap<String, AttrValue>)>> {
+    let (name, attrs) = match node.get_op() {
+        "PartitionedCall" | "StatefulPartitionedCall" => {
+            let f = node
+                .get_attr()
+                .get("f")
+                .ok_or_else(|| format!("Node {} expected attribute 'f'", node.get_name()))?
+                .get_func();
+            (f.get_name(), f.get_attr())
+        }
+        op => (op, node.get_attr()),
+    };
+    match functions.get(name) {
+        Some(f) => Ok(Some((*f, attrs))),
+        None if node.get_op() != name => bail!("Function {} not found in graph library", name),
+        None => Ok(None),
+    }
+}
+
+fn inline_node<'a>(
+    functions: &HashMap<&str, &'a FunctionDef>,
+    node: &NodeDef,
+    stack: &mut Vec<&'a str>,
+    nodes: &mut Vec<NodeDef>,
+) -> TfdResult<()> {
+    let (function, attrs) = match called(functions, node)? {
+        Some(call) => call,
+        None => {
+            nodes.push(node.clone());
+            return Ok(());
+        }
+    };
+    let signature = function.get_signature();
+    if stack.contains(&signature.get_name()) {
+        bail!("Function {} is recursive", signature.get_name())
+    }
+    let prefix = node.get_name();
+    let (data_inputs, control_inputs): (Vec<&String>, Vec<&String>) =
+        node.get_input().iter().partition(|i| !i.starts_with('^'));
+    if signature.get_input_arg().len() != data_inputs.len() {
+        bail!(
+            "Node {} calls {} with {} inputs, expected {}",
+            prefix,
+            signature.get_name(),
+            data_inputs.len(),
+            signature.get_input_arg().len()
+        )
+    }
+    let mut args = HashMap::new();
+    for (arg, input) in signature.get_input_arg().iter().zip(data_inputs) {
+        if arg.get_number_attr() != "" || arg.get_type_list_attr() != "" {
+            bail!("Function {} has a list argument {}, not supported", signature.get_name(), arg.get_name())
+        }
+        args.insert(arg.get_name(), input.to_string());
+    }
+    let ops: HashMap<&str, &str> = function
+        .get_node_def()
+        .iter()
+        .map(|n| (n.get_name(), n.get_op()))
+        .collect();
+    let reference = |input: &str| -> TfdResult<String> {
+        if input.starts_with('^') {
+            return Ok(format!("^{}/{}", prefix, &input[1..]));
+        }
+        let splits: Vec<&str> = input.split(':').collect();
+        if splits.len() == 1 {
+            return Ok(args
+                .get(input)
+                .cloned()
+                .ok_or_else(|| format!("Unknown argument {} in function {}", input, signature.get_name()))?);
+        }
+        if splits.len() != 3 {
+            bail!("Can not parse reference {} in function {}", input, signature.get_name())
+        }
+        let index = splits[2].parse::<usize>()?;
+        let op = ops
+            .get(splits[0])
+            .ok_or_else(|| format!("No node {} in function {}", splits[0], signature.get_name()))?;
+        let position = match functions.get(op) {
+            Some(f) => f.get_signature().get_output_arg().iter().position(|a| a.get_name() == splits[1]),
+            None => OUTPUT_ARGS
+                .iter()
+                .find(|o| o.0 == *op)
+                .map_or(Some(0), |o| o.1.iter().position(|a| *a == splits[1])),
+        };
+        let slot = position
+            .ok_or_else(|| format!("Op {} has no output {}", op, splits[1]))?
+            + index;
+        Ok(if slot == 0 {
+            format!("{}/{}", prefix, splits[0])
+        } else {
+            format!("{}/{}:{}", prefix, splits[0], slot)
+        })
+    };
+
+    stack.push(signature.get_name());
+    for body_node in function.get_node_def() {
+        let mut body_node = body_node.clone();
+        body_node.set_name(format!("{}/{}", prefix, body_node.get_name()));
+        let inputs = body_node
+            .get_input()
+            .iter()
+            .map(|i| reference(i))
+            .collect::<TfdResult<Vec<_>>>()?;
+        body_node.set_input(inputs.into());
+        for value in body_node.mut_attr().values_mut() {
+            if value.has_placeholder() {
+                *value = attrs
+                    .get(value.get_placeholder())
+                    .cloned()
+                    .ok_or_else(|| format!("Node {} misses attribute {}", prefix, value.get_placeholder()))?;
+            }
+        }
+        inline_node(functions, &body_node, stack, nodes)?;
+    }
+    stack.pop();
+
+    let mut outputs = NodeDef::new();
+    outputs.set_name(prefix.to_string());
+    outputs.set_op("IdentityN".to_string());
+    for arg in signature.get_output_arg() {
+        let ret = function
+            .get_ret()
+            .get(arg.get_name())
+            .ok_or_else(|| format!("Function {} does not return {}", signature.get_name(), arg.get_name()))?;
+        outputs.mut_input().push(reference(ret)?);
+    }
+    outputs.mut_input().extend(control_inputs.into_iter().cloned());
+    nodes.push(outputs);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tfdeploy::model::Model;
+    use tfdeploy::{SimplePlan, Tensor, ToTfd};
+    use tfpb;
+    use tfpb::attr_value::NameAttrList;
+    use tfpb::op_def::{OpDef, OpDef_ArgDef};
+    use tfpb::types::DataType::DT_FLOAT;
+
+    fn arg(name: &str) -> OpDef_ArgDef {
+        let mut arg = OpDef_ArgDef::new();
+        arg.set_name(name.to_string());
+        arg
+    }
+
+    // double(x) = x * 2, its type being a placeholder attribute
+    fn double() -> FunctionDef {
+        let mut signature = OpDef::new();
+        signature.set_name("double".to_string());
+        signature.mut_input_arg().push(arg("x"));
+        signature.mut_output_arg().push(arg("y"));
+        let mut t = AttrValue::new();
+        t.set_placeholder("T".to_string());
+        let mut function = FunctionDef::new();
+        function.set_signature(signature);
+        function.mut_node_def().push(
+            tfpb::node()
+                .name("two")
+                .op("Const")
+                .attr("dtype", DT_FLOAT)
+                .attr("value", tfpb::tensor_f32(vec![], vec![2.0])),
+        );
+        function.mut_node_def().push(
+            tfpb::node()
+                .name("mul")
+                .op("Mul")
+                .input("x")
+                .input("two:output:0")
+                .attr("T", t),
+        );
+        function.mut_ret().insert("y".to_string(), "mul:z:0".to_string());
+        function
+    }
+
+    // halves(x) = (x / 2, -x / 2), its second result built from its first
+    fn halves() -> FunctionDef {
+        let mut signature = OpDef::new();
+        signature.set_name("halves".to_string());
+        signature.mut_input_arg().push(arg("x"));
+        signature.mut_output_arg().push(arg("half"));
+        signature.mut_output_arg().push(arg("neg"));
+        let mut function = FunctionDef::new();
+        function.set_signature(signature);
+        function.mut_node_def().push(
+            tfpb::node()
+                .name("two")
+                .op("Const")
+                .attr("dtype", DT_FLOAT)
+                .attr("value", tfpb::tensor_f32(vec![], vec![2.0])),
+        );
+        function.mut_node_def().push(
+            tfpb::node()
+                .name("div")
+                .op("RealDiv")
+                .input("x")
+                .input("two:output:0")
+                .attr("T", DT_FLOAT),
+        );
+        function.mut_node_def().push(
+            tfpb::node()
+                .name("neg")
+                .op("Neg")
+                .input("div:z:0")
+                .attr("T", DT_FLOAT),
+        );
+        function.mut_ret().insert("half".to_string(), "div:z:0".to_string());
+        function.mut_ret().insert("neg".to_string(), "neg:y:0".to_string());
+        function
+    }
+
+    #[test]
+    fn inline_calls() {
+        let mut f = NameAttrList::new();
+        f.set_name("double".to_string());
+        f.mut_attr().insert("T".to_string(), DT_FLOAT.into());
+        let mut func = AttrValue::new();
+        func.set_func(f);
+        let mut graph = tfpb::graph()
+            .node(tfpb::node().name("input").op("Placeholder").attr("dtype", DT_FLOAT))
+            .node(tfpb::node().name("twice").op("double").input("input").attr("T", DT_FLOAT))
+            .node(tfpb::node().name("output").op("PartitionedCall").input("twice").attr("f", func));
+        graph.mut_library().mut_function().push(double());
+        let model: Model = graph.to_tfd().unwrap();
+        assert_eq!(model.node_by_name("output/mul").unwrap().op_name, "Mul");
+        let plan = SimplePlan::new(&model, &["input"], &["output"]).unwrap();
+        let result = plan.run(tvec![Tensor::from(3.0f32)]).unwrap();
+        assert_eq!(result[0][0], Tensor::from(12.0f32));
+    }
+
+    #[test]
+    fn inline_multiple_outputs() {
+        let mut graph = tfpb::graph()
+            .node(tfpb::node().name("input").op("Placeholder").attr("dtype", DT_FLOAT))
+            .node(tfpb::node().name("call").op("halves").input("input"))
+            .node(tfpb::node().name("half").op("Identity").input("call"))
+            .node(tfpb::node().name("neg").op("Identity").input("call:1"));
+        graph.mut_library().mut_function().push(halves());
+        let model: Model = graph.to_tfd().unwrap();
+        assert_eq!(model.node_by_name("call").unwrap().op_name, "IdentityN");
+        let plan = SimplePlan::new(&model, &["input"], &["half", "neg"]).unwrap();
+        let result = plan.run(tvec![Tensor::from(3.0f32)]).unwrap();
+        assert_eq!(result[0][0], Tensor::from(1.5f32));
+        assert_eq!(result[1][0], Tensor::from(-1.5f32));
+    }
+}
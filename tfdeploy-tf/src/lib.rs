@@ -46,12 +46,16 @@ extern crate protobuf;
 extern crate tfdeploy;
 
 pub mod tfpb;
+pub mod control_flow;
+pub mod function;
 pub mod model;
 pub mod tensor;
 pub mod ops;
+pub mod saved_model;
 
 pub use self::model::for_path;
 pub use self::model::for_reader;
+pub use self::saved_model::{for_saved_model, for_saved_model_with_tags};
 
 pub trait ToTensorflow<Tf>: Sized {
     fn to_tf(&self) -> tfdeploy::TfdResult<Tf>;
@@ -5,7 +5,7 @@ use std::{fs, path};
 
 use tfdeploy::model::{Model, Node, OutletId, RawModel};
 use tfpb::graph::GraphDef;
-use tfdeploy::{TfdFrom, ToTfd, TfdResult };
+use tfdeploy::{SymbolTable, TfdFrom, ToTfd, TfdResult };
 
 /// Load a Tensorflow protobul model from a file.
 pub fn for_path<P: AsRef<path::Path>>(p: P) -> TfdResult<Model> {
@@ -29,66 +29,222 @@ pub fn graphdef_for_path<P: AsRef<path::Path>>(p: P) -> TfdResult<GraphDef> {
     graphdef_for_reader(fs::File::open(p)?)
 }
 
+/// Load a Tfdeploy model from a graph def, dropping its control dependencies.
+///
+/// Control dependencies mostly order side effects, like variable updates or
+/// assertions, which do not matter when only running inference.
+pub fn for_graphdef_inference_only(graph: &GraphDef) -> TfdResult<Model> {
+    graphdef_to_model(graph, false)
+}
+
 impl TfdFrom<GraphDef> for Model {
     fn tfd_from(graph: &GraphDef) -> TfdResult<Model> {
+        graphdef_to_model(graph, true)
+    }
+}
+
+fn graphdef_to_model(graph: &GraphDef, control_inputs: bool) -> TfdResult<Model> {
+    let graph = ::function::inline(graph)?;
+    // Control flow constructs are replaced by nodes with prebuilt ops.
+    let (graph, mut lowered) = ::control_flow::lower(&graph)?;
+    let op_builder = ::ops::OpBuilder::new();
+    let mut symbols = SymbolTable::default();
+
+    // Nodes may be listed before their inputs: build all of them first, then
+    // wire them.
     let mut nodes = vec![];
     let mut nodes_by_name: HashMap<String, usize> = HashMap::new();
-    let mut model_inputs = vec!();
-    let op_builder = ::ops::OpBuilder::new();
     for pbnode in graph.get_node().iter() {
         let name = pbnode.get_name().to_string();
+        if nodes_by_name.contains_key(&name) {
+            bail!("Graph has two nodes named {}", name)
+        }
+        let node = Node {
+            id: nodes.len(),
+            name: name.to_string(),
+            op_name: pbnode.get_op().to_string(),
+            inputs: vec![],
+            control_inputs: vec![],
+            op: if let Some(op) = lowered.remove(&name) {
+                Ok(op)
+            } else if pbnode.get_op() == "Placeholder" {
+                // Placeholders share the symbols of their unknown dimensions.
+                ::ops::placeholder_with_symbols(&pbnode, &mut symbols)
+            } else {
+                op_builder.build(&pbnode)
+            }.map_err(|e| format!("While building node {}, {}", name, e.description()))?,
+        };
+        nodes_by_name.insert(name, nodes.len());
+        nodes.push(node)
+    }
 
+    for (pbnode, node) in graph.get_node().iter().zip(nodes.iter_mut()) {
         // From the node_def.proto documentation:
         // Each input is "node:src_output" with "node" being a string name and
         // "src_output" indicating which output tensor to use from "node". If
         // "src_output" is 0 the ":0" suffix can be omitted. Regular inputs may
         // optionally be followed by control inputs that have the format "^node".
-        let inputs: Vec<OutletId> = pbnode
-            .get_input()
+        let node_id = |input: &str| -> TfdResult<usize> {
+            Ok(*nodes_by_name
+                .get(input)
+                .ok_or(format!("No node {} found", input))?)
+        };
+        let (data_inputs, control): (Vec<&String>, Vec<&String>) =
+            pbnode.get_input().iter().partition(|i| !i.starts_with("^"));
+        node.inputs = data_inputs
             .iter()
             .map(|i| {
-                let input: (usize, usize) = if i.starts_with("^") {
-                    (
-                        nodes_by_name
-                            .get(&*i.replace("^", ""))
-                            .ok_or(format!("No node {} found", i))?
-                            .clone(),
-                        0,
-                    )
+                let splits: Vec<_> = i.splitn(2, ':').collect();
+                let slot = if splits.len() > 1 {
+                    splits[1].parse::<usize>()?
                 } else {
-                    let splits: Vec<_> = i.splitn(2, ':').collect();
-                    (
-                        nodes_by_name
-                            .get(splits[0])
-                            .ok_or(format!("No node {} found", i))?
-                            .clone(),
-                        if splits.len() > 1 {
-                            splits[1].parse::<usize>()?
-                        } else {
-                            0
-                        },
-                    )
+                    0
                 };
-                Ok(OutletId::new(input.0, input.1))
+                Ok(OutletId::new(node_id(splits[0])?, slot))
             })
             .collect::<TfdResult<Vec<_>>>()
-            .map_err(|e| format!("While building node {}, {}", name, e.description()))?;
-        let node = Node {
-            id: nodes.len(),
-            name: name.to_string(),
-            op_name: pbnode.get_op().to_string(),
-            inputs: inputs,
-            op: op_builder
-                .build(&pbnode)
-                .map_err(|e| format!("While building node {}, {}", name, e.description()))?,
-        };
-        if node.op_name == "Placeholder" {
-            model_inputs.push(nodes.len());
-        }
-        nodes_by_name.insert(name, nodes.len());
-        nodes.push(node)
+            .map_err(|e| format!("While wiring node {}, {}", node.name, e.description()))?;
+        node.control_inputs = control
+            .iter()
+            .filter(|_| control_inputs)
+            .map(|i| node_id(&i[1..]))
+            .collect::<TfdResult<Vec<_>>>()
+            .map_err(|e| format!("While wiring node {}, {}", node.name, e.description()))?;
     }
+    check_acyclic(&nodes)?;
 
     Ok(Model(Arc::new(RawModel::new(nodes, nodes_by_name))))
 }
+
+/// Fails with the nodes of a cycle if the graph has one.
+fn check_acyclic(nodes: &[Node]) -> TfdResult<()> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        Visiting,
+        Done,
+    }
+    let predecessors = |n: usize| -> Vec<usize> {
+        let node = &nodes[n];
+        node.inputs.iter().map(|i| i.node).chain(node.control_inputs.iter().cloned()).collect()
+    };
+    let mut marks = vec![Mark::New; nodes.len()];
+    for root in 0..nodes.len() {
+        if marks[root] != Mark::New {
+            continue;
+        }
+        // Depth first search, keeping the path from the root.
+        let mut path: Vec<(usize, Vec<usize>)> = vec![(root, predecessors(root))];
+        marks[root] = Mark::Visiting;
+        while let Some(&mut (node, ref mut todo)) = path.last_mut() {
+            match todo.pop() {
+                Some(next) if marks[next] == Mark::Visiting => {
+                    let start = path.iter().position(|p| p.0 == next).unwrap();
+                    let mut cycle: Vec<&str> = path[start..].iter().map(|p| &*nodes[p.0].name).collect();
+                    cycle.reverse();
+                    cycle.push(&nodes[node].name);
+                    bail!("Graph has a cycle: {}", cycle.join(" -> "))
+                }
+                Some(next) if marks[next] == Mark::New => {
+                    marks[next] = Mark::Visiting;
+                    path.push((next, predecessors(next)));
+                }
+                Some(_) => (),
+                None => {
+                    marks[node] = Mark::Done;
+                    path.pop();
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tfdeploy::analyser::types::TensorFact;
+    use tfdeploy::{DatumType, TDim};
+    use tfpb;
+    use tfpb::tensor_shape::{TensorShapeProto, TensorShapeProto_Dim};
+    use tfpb::types::DataType::DT_FLOAT;
+
+    fn placeholder(name: &str, dims: &[i64]) -> tfpb::node_def::NodeDef {
+        let mut shape = TensorShapeProto::new();
+        for &d in dims {
+            let mut dim = TensorShapeProto_Dim::new();
+            dim.set_size(d);
+            shape.mut_dim().push(dim);
+        }
+        tfpb::node()
+            .name(name)
+            .op("Placeholder")
+            .attr("dtype", DT_FLOAT)
+            .attr("shape", shape)
+    }
+
+    #[test]
+    fn control_inputs() {
+        let graph = tfpb::graph()
+            .node(placeholder("input", &[3]))
+            .node(placeholder("other", &[3]))
+            .node(tfpb::node().name("output").op("Identity").input("input").input("^other"));
+        let model: Model = graph.to_tfd().unwrap();
+        let output = model.node_by_name("output").unwrap();
+        assert_eq!(output.inputs, vec![OutletId::new(0, 0)]);
+        assert_eq!(output.control_inputs, vec![1]);
+        let order = ::tfdeploy::model::eval_order_for_nodes(model.nodes(), &[output.id]).unwrap();
+        assert_eq!(order, vec![0, 1, 2]);
+
+        let model = for_graphdef_inference_only(&graph).unwrap();
+        let output = model.node_by_name("output").unwrap();
+        assert!(output.control_inputs.is_empty());
+        let order = ::tfdeploy::model::eval_order_for_nodes(model.nodes(), &[output.id]).unwrap();
+        assert_eq!(order, vec![0, 2]);
+    }
+
+    #[test]
+    fn out_of_order_nodes() {
+        let graph = tfpb::graph()
+            .node(tfpb::node().name("output").op("Identity").input("input"))
+            .node(placeholder("input", &[3]));
+        let model: Model = graph.to_tfd().unwrap();
+        let plan = ::tfdeploy::SimplePlan::new(&model, &["input"], &["output"]).unwrap();
+        let input = ::tfdeploy::Tensor::from(::ndarray::arr1(&[1.0f32, 2.0, 3.0]));
+        assert_eq!(plan.run(tvec![input.clone()]).unwrap()[0][0], input);
+    }
+
+    #[test]
+    fn cycle() {
+        let graph = tfpb::graph()
+            .node(placeholder("input", &[3]))
+            .node(tfpb::node().name("a").op("Add").input("input").input("b").attr("T", DT_FLOAT))
+            .node(tfpb::node().name("b").op("Identity").input("a"));
+        let error = Model::tfd_from(&graph).unwrap_err();
+        assert_eq!(error.description(), "Graph has a cycle: b -> a -> b");
+    }
+
+    #[test]
+    fn placeholder_shapes() {
+        let graph = tfpb::graph()
+            .node(placeholder("input", &[-1, 3]))
+            .node(placeholder("index", &[-1]));
+        let model: Model = graph.to_tfd().unwrap();
+        let source = |dims: Vec<TDim>| {
+            format!(
+                "{:?}",
+                ::tfdeploy::ops::source::Source::new(
+                    TensorFact::dt(DatumType::F32).with_shape(dims)
+                )
+            )
+        };
+        assert_eq!(
+            format!("{:?}", model.node_by_name("input").unwrap().op),
+            source(vec![TDim::sym('i'), 3.into()])
+        );
+        assert_eq!(
+            format!("{:?}", model.node_by_name("index").unwrap().op),
+            source(vec![TDim::sym('a')])
+        );
+    }
 }
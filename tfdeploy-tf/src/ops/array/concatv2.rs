@@ -1,32 +1,33 @@
 use tfdeploy::analyser::rules::prelude::*;
-use ndarray::prelude::*;
+use tfdeploy::ops::array::{resolve_axis, Concat};
 use tfdeploy::ops::prelude::*;
 
 pub fn build(pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
     let n = pb.get_attr_int("N")?;
-    let t = pb.get_attr_datum_type("T")?;
-    let tidx = pb.get_attr_datum_type("Tidx")?;
-    Ok(boxed_new!(ConcatV2(t)(n, tidx)))
+    Ok(Box::new(ConcatV2::new(n)))
 }
 
+/// Tensorflow ConcatV2, taking its axis as a last input.
 #[derive(Debug, Clone, new)]
-pub struct ConcatV2<T: Datum> {
+pub struct ConcatV2 {
     n: usize,
-    tidx: DatumType,
-    t: PhantomData<T>,
 }
 
-impl<T: Datum> Op for ConcatV2<T> {
+/// Reads the axis from an i32 or i64 scalar.
+fn axis(axis: &Tensor) -> TfdResult<i64> {
+    match axis.datum_type() {
+        DatumType::I32 => Ok(axis.as_i32().ok_or("Expected a i32 scalar")? as i64),
+        DatumType::I64 => Ok(axis.as_i64().ok_or("Expected a i64 scalar")?),
+        dt => bail!("Expected a i32 or i64 axis, got {:?}", dt),
+    }
+}
+
+impl Op for ConcatV2 {
     /// Evaluates the operation given the input tensors.
     fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
-        let axis: i32 = inputs
-            .pop()
-            .and_then(|t| t.as_i32())
-            .ok_or("Expected a i32 scalar")?;
-        let mats: TfdResult<Vec<ArrayViewD<T>>> =
-            inputs.iter().map(|mat| mat.to_array_view()).collect();
-        let result = ::ndarray::stack(Axis(axis as usize), &*mats?)?;
-        Ok(tvec![result.into()])
+        let axis_tensor = inputs.pop().ok_or("Expected an axis input")?;
+        let axis = axis(&axis_tensor)?;
+        Concat::new(axis).eval(inputs)
     }
 
     /// Returns a new streaming buffer for the operation.
@@ -55,11 +56,16 @@ impl<T: Datum> Op for ConcatV2<T> {
             .pop()
             .ok_or("Unexpectedly found zero inputs in ConcatV2")?;
         let axis_tensor = n.into_const().ok_or("Axis input should not be streamed.")?;
-        let axis: i32 = axis_tensor.as_i32().ok_or("Expected a i32 scalar")?;
+        let axis = axis(&axis_tensor)?;
+        let rank = inputs.iter().filter_map(|i| i.as_value()).map(|v| v.shape().len()).next();
+        let axis = match rank {
+            Some(rank) => resolve_axis(axis, rank)? as i64,
+            None => axis,
+        };
 
         if inputs
             .iter()
-            .all(|i| i.stream_info().map(|i| i.axis) == Some(axis as usize))
+            .all(|i| i.stream_info().map(|i| i.axis as i64) == Some(axis))
         {
             // All the input tensors are streamed along `axis`.
             let chunk = inputs
@@ -92,7 +98,7 @@ impl<T: Datum> Op for ConcatV2<T> {
     }
 }
 
-impl<T: Datum> InferenceRulesOp for ConcatV2<T> {
+impl InferenceRulesOp for ConcatV2 {
     fn rules<'r, 'p: 'r, 's: 'r>(
         &'s self,
         solver: &mut Solver<'r>,
@@ -103,36 +109,11 @@ impl<T: Datum> InferenceRulesOp for ConcatV2<T> {
         solver
             .equals(&inputs.len, n as i64 + 1)
             .equals(&outputs.len, 1)
-            .equals_all((0..self.n).map(|i| (&inputs[i].datum_type).bex()).collect())
-            .equals(&outputs[0].datum_type, &inputs[0].datum_type)
-            .equals(&inputs[n].datum_type, DatumType::I32)
-            .equals_all((0..self.n).map(|i| (&inputs[i].rank).bex()).collect())
             .equals(&inputs[n].rank, 0)
-            .equals(&outputs[0].rank, &inputs[0].rank)
-            .given(&inputs[n].value, move |solver, axis: Tensor| {
-                let axis = axis.as_i32().unwrap() as usize; // checked
-                trace!("axis for Concatv2: {}", axis);
-                (0..axis).for_each(|d| {
-                    solver.equals_all((0..n).map(|i| (&inputs[i].shape[d]).bex()).collect());
-                });
-                (0..axis).for_each(|d| {
-                    solver.equals(&inputs[0].shape[d], &outputs[0].shape[d]);
-                });
-                solver.given(&inputs[0].rank, move |solver, rank| {
-                    trace!("Given rank {}", rank);
-                    ((axis + 1)..(rank as usize)).for_each(|d| {
-                        solver.equals(&inputs[0].shape[d], &outputs[0].shape[d]);
-                    });
-                    ((axis + 1)..(rank as usize)).for_each(|d| {
-                        solver.equals_all((0..n).map(|i| (&inputs[i].shape[d]).bex()).collect());
-                    });
-                });
-
-                let mut concat_dim = -1 * outputs[0].shape[axis].bex();
-                for i in 0..n {
-                    concat_dim = concat_dim + inputs[i].shape[axis].bex();
+            .given(&inputs[n].value, move |solver, axis_tensor: Tensor| {
+                if let Ok(axis) = axis(&axis_tensor) {
+                    Concat::new(axis).shape_rules(solver, inputs, n, &outputs[0]);
                 }
-                solver.equals_zero(concat_dim);
             });
     }
 }
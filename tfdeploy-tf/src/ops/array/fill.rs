@@ -11,7 +11,7 @@ pub struct Fill<T: Datum> {
 
 pub fn fill(pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
     let dtype = pb.get_attr_datum_type("T")?;
-    Ok(boxed_new!(Fill(dtype)()))
+    Ok(boxed_new_any!(Fill(dtype)()))
 }
 
 impl<T> Op for Fill<T>
@@ -21,8 +21,8 @@ where
     /// Evaluates the operation given the input tensors.
     fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
         let (shape, value) = args_2!(inputs);
-        let value = value.to_array_view()?;
-        let value: T = value[[]];
+        let value = value.to_array_view::<T>()?;
+        let value: T = value[[]].clone();
         let shape = shape.to_array_view::<i32>()?;
         let array = ::ndarray::Array::from_elem(
             shape.iter().map(|i| *i as usize).collect::<Vec<usize>>(),
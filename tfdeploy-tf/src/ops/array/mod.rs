@@ -1,5 +1,5 @@
-use ndarray::prelude::*;
 use tfdeploy::analyser::rules::prelude::*;
+use tfdeploy::ops::array;
 use tfdeploy::ops::prelude::*;
 use ops::OpRegister;
 
@@ -7,21 +7,32 @@ mod concatv2;
 mod fill;
 mod pack;
 mod pad;
-mod reshape;
 mod squeeze;
-mod strided_slice;
 
 pub fn register_all_ops(reg: &mut OpRegister) {
     reg.insert("ConcatV2", concatv2::build);
     reg.insert("ExpandDims", ExpandDims::build);
     reg.insert("Identity", Identity::build);
+    reg.insert("IdentityN", IdentityN::build);
     reg.insert("Fill", fill::fill);
     reg.insert("Pack", pack::pack);
     reg.insert("Pad", pad::pad);
-    reg.insert("Reshape", reshape::reshape);
-    reg.insert("Shape", Shape::build);
+    reg.insert("Reshape", |_| Ok(Box::new(array::Reshape::new(true))));
+    reg.insert("Shape", shape);
     reg.insert("Squeeze", squeeze::squeeze);
-    reg.insert("StridedSlice", strided_slice::build);
+    reg.insert("StridedSlice", strided_slice);
+}
+
+fn shape(pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
+    let dt = pb.get_attr_opt_datum_type("out_type")?.unwrap_or(DatumType::I32);
+    Ok(Box::new(array::Shape::new(dt)))
+}
+
+fn strided_slice(pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
+    let begin_mask = pb.get_attr_opt_int("begin_mask")?.unwrap_or(0);
+    let end_mask = pb.get_attr_opt_int("end_mask")?.unwrap_or(0);
+    let shrink_axis_mask = pb.get_attr_opt_int("shrink_axis_mask")?.unwrap_or(0);
+    Ok(Box::new(array::StridedSlice::new(begin_mask, end_mask, shrink_axis_mask)))
 }
 
 #[derive(Debug, Clone)]
@@ -156,25 +167,26 @@ impl InferenceRulesOp for Identity {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Shape;
+#[derive(Debug, Clone, new)]
+pub struct IdentityN {
+    n: usize, // The number of inputs, and outputs
+}
 
-impl Shape {
-    pub fn build(_pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
-        Ok(Box::new(Shape))
+impl IdentityN {
+    pub fn build(pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
+        let n = pb.get_input().iter().filter(|i| !i.starts_with('^')).count();
+        Ok(Box::new(IdentityN::new(n)))
     }
 }
 
-impl Op for Shape {
+impl Op for IdentityN {
     /// Evaluates the operation given the input tensors.
     fn eval(&self, inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
-        let data = inputs[0].as_f32s().ok_or("Expect input #0 to be f32")?;
-        let shape: Vec<i32> = data.shape().into_iter().map(|s| *s as i32).collect();
-        Ok(tvec![Tensor::from(Array1::from_vec(shape)).into()])
+        Ok(inputs)
     }
 }
 
-impl InferenceRulesOp for Shape {
+impl InferenceRulesOp for IdentityN {
     fn rules<'r, 'p: 'r, 's: 'r>(
         &'s self,
         solver: &mut Solver<'r>,
@@ -182,103 +194,12 @@ impl InferenceRulesOp for Shape {
         outputs: &'p TensorsProxy,
     ) {
         solver
-            .equals(&inputs.len, 1)
-            .equals(&outputs.len, 1)
-            .equals(&outputs[0].datum_type, DatumType::TDim)
-            .equals(&outputs[0].rank, 1)
-            .given(&inputs[0].rank, move |solver, r| {
-                solver.equals(&outputs[0].shape[0], r.to_dim());
-            })
-            .given(&outputs[0].shape[0], move |solver, r| {
-                if let Ok(d) = r.to_integer() {
-                    solver.equals(&inputs[0].rank, d);
-                }
-            })
-            .given(&inputs[0].shape, move |solver, shape: Vec<TDim>| {
-                let array1: Array1<TDim> = Array1::from_vec(shape);
-                let tensor: Tensor = Tensor::from(array1);
-                solver.equals(&outputs[0].value, tensor);
-            })
-            .given(&outputs[0].value, move |solver, shape: Tensor| {
-                let shape = shape.take_dims().unwrap(); // checked
-                solver.equals(
-                    &inputs[0].shape,
-                    shape.into_iter().cloned().collect::<Vec<TDim>>(),
-                );
-            });
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tfpb::node;
-
-    #[test]
-    fn shape_inference_1() {
-        let input = TensorFact {
-            datum_type: typefact!(DatumType::F32),
-            shape: shapefact![1, _, _; ..],
-            value: valuefact!(_),
-        };
-
-        let output = TensorFact {
-            datum_type: typefact!(DatumType::TDim),
-            shape: shapefact![_],
-            value: valuefact!(_),
-        };
-
-        assert_forward!(Shape::build(&node()).unwrap(), input, output);
-    }
-
-    #[test]
-    fn shape_inference_2() {
-        let input = TensorFact {
-            datum_type: typefact!(DatumType::F32),
-            shape: shapefact![1, _, _],
-            value: valuefact!(_),
-        };
-
-        let output = TensorFact {
-            datum_type: typefact!(DatumType::TDim),
-            shape: shapefact![3],
-            value: valuefact!(_),
-        };
-
-        assert_forward!(Shape::build(&node()).unwrap(), input, output);
-    }
-
-    #[test]
-    fn shape_inference_3() {
-        let input = TensorFact {
-            datum_type: typefact!(DatumType::F32),
-            shape: shapefact![1, 2, 3],
-            value: valuefact!(_),
-        };
-
-        let output = TensorFact {
-            datum_type: typefact!(DatumType::TDim),
-            shape: shapefact![3],
-            value: valuefact!(Tensor::dims(&[3], &[1.to_dim(), 2.to_dim(), 3.to_dim()]).unwrap()),
-        };
-
-        assert_forward!(Shape::build(&node()).unwrap(), input, output);
-    }
-
-    #[test]
-    fn shape_inference_4() {
-        let input = TensorFact {
-            datum_type: typefact!(_),
-            shape: shapefact![1, 2, 3],
-            value: valuefact!(_),
-        };
-
-        let output = TensorFact {
-            datum_type: typefact!(DatumType::TDim),
-            shape: shapefact![3],
-            value: valuefact!(Tensor::dims(&[3], &[1.to_dim(), 2.to_dim(), 3.to_dim()]).unwrap()),
-        };
-
-        assert_backward!(Shape::build(&node()).unwrap(), input, output);
+            .equals(&inputs.len, self.n as i64)
+            .equals(&outputs.len, self.n as i64);
+        for i in 0..self.n {
+            solver
+                .equals(&inputs[i].datum_type, &outputs[i].datum_type)
+                .equals(&inputs[i].shape, &outputs[i].shape);
+        }
     }
 }
@@ -29,7 +29,7 @@ impl Pack {
             .iter()
             .map(|v| v.view().insert_axis(Axis(self.axis)))
             .collect();
-        let array = ::ndarray::stack(Axis(self.axis), &*views)?;
+        let array = ::tfdeploy::ops::array::stack(Axis(self.axis), &*views)?;
         Ok(tvec![array.into()])
     }
 }
@@ -43,6 +43,7 @@ impl Op for Pack {
             DatumType::TDim => self.eval_t::<TDim>(inputs),
             DatumType::I32 => self.eval_t::<i32>(inputs),
             DatumType::F32 => self.eval_t::<f32>(inputs),
+            DatumType::String => self.eval_t::<Vec<u8>>(inputs),
             _ => panic!("unsupported type"),
         }
     }
@@ -124,6 +125,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pack_strings() {
+        let inputs = tvec![Tensor::from("a").into(), Tensor::from("b").into()];
+        assert_eq!(
+            Pack::new(DatumType::String, 2, 0)
+                .eval(inputs)
+                .unwrap()
+                .remove(0)
+                .into_tensor(),
+            Tensor::strings(&[2], &[b"a".to_vec(), b"b".to_vec()]).unwrap()
+        );
+    }
+
     #[test]
     fn pack_1() {
         let pack = Pack::new(DatumType::I32, 3, 0);
@@ -1,69 +1,37 @@
-use std::marker::PhantomData;
-
-use ndarray::{Array, ArrayD, ArrayView2, ArrayViewD};
-use num::Zero;
-
 use tfdeploy::analyser::rules::prelude::*;
+use tfdeploy::ops::array::{to_i64s, Pad, PadMode};
 use tfdeploy::ops::prelude::*;
 use tfdeploy::TfdResult;
 
-#[derive(Debug, Clone, Default, new)]
-pub struct Pad<T: Datum + Zero> {
-    _phantom: PhantomData<T>,
-}
+/// Tensorflow Pad, taking its paddings as a second input and padding with
+/// zeroes.
+#[derive(Debug, Clone, Default)]
+pub struct TfPad;
 
-pub fn pad(pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
-    let dtype = pb.get_attr_datum_type("T")?;
-    Ok(boxed_new!(Pad(dtype)()))
+pub fn pad(_pb: &::tfpb::node_def::NodeDef) -> TfdResult<Box<Op>> {
+    Ok(Box::new(TfPad))
 }
 
-impl<T: Datum + Zero> Pad<T> {
-    fn compute(
-        input: &ArrayViewD<T>,
-        paddings: ArrayView2<i32>,
-        stream_dim: Option<usize>,
-    ) -> TfdResult<ArrayD<T>> {
-        let shape: Vec<usize> = input
-            .shape()
-            .iter()
-            .enumerate()
-            .map(|(ix, &dim)| {
-                if Some(ix) != stream_dim {
-                    dim + (paddings[(ix, 0)] + paddings[(ix, 1)]) as usize
-                } else {
-                    dim
-                }
-            })
-            .collect();
-        let mut index_in_input = vec![0; input.ndim()];
-        let result = Array::from_shape_fn(shape, |index| {
-            for i in 0..input.ndim() {
-                if index[i] < paddings[(i, 0)] as usize
-                    || index[i] - paddings[(i, 0)] as usize >= input.shape()[i] as usize
-                {
-                    return T::zero();
-                } else {
-                    index_in_input[i] = index[i] - paddings[(i, 0)] as usize;
-                };
-            }
-            input[&*index_in_input]
-        });
-        Ok(result)
+/// Reads the paddings from a [rank, 2] i32 or i64 tensor.
+fn pads(paddings: &Tensor) -> TfdResult<Vec<(usize, usize)>> {
+    let paddings = to_i64s(paddings)?;
+    if paddings.ndim() != 2 || paddings.shape()[1] != 2 || paddings.iter().any(|&p| p < 0) {
+        bail!("Unsupported paddings {:?}", paddings)
     }
+    Ok(paddings
+        .outer_iter()
+        .map(|p| (p[0] as usize, p[1] as usize))
+        .collect())
 }
 
-impl<T> Op for Pad<T>
-where
-    T: Datum+Zero,
-{
+impl Op for TfPad {
     /// Evaluates the operation given the input tensors.
     fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
         let (input, paddings) = args_2!(inputs);
-        let input = input.to_array_view::<T>()?;
-        let paddings = paddings.to_array_view::<i32>()?.into_dimensionality()?;
-        Ok(tvec![Self::compute(&input, paddings, None)?.into()])
+        Pad::new(pads(&paddings)?, PadMode::Constant(0.0)).eval(tvec![input])
     }
 
+    /// Evaluates one step of the operation on the given input tensors.
     fn step(
         &self,
         mut inputs: TVec<StepValue>,
@@ -71,45 +39,40 @@ where
     ) -> TfdResult<Option<TVec<Value>>> {
         if let (StepValue::Stream(stream), StepValue::Const(paddings)) = args_2!(inputs) {
             if let Some(chunk) = stream.chunk {
-                let chunk = chunk.to_array_view::<T>()?;
-                let paddings = i32::tensor_to_view(&paddings)?.into_dimensionality()?;
-                return Ok(Some(tvec![
-                    Self::compute(&chunk, paddings, Some(stream.info.axis))?.into(),
-                ]));
+                let mut pads = pads(&paddings)?;
+                if let Some(p) = pads.get_mut(stream.info.axis) {
+                    *p = (0, 0);
+                }
+                return Ok(Some(Pad::new(pads, PadMode::Constant(0.0)).eval(tvec![chunk])?));
             }
         }
         Ok(None)
     }
 }
 
-impl<T: Datum+Zero> InferenceRulesOp for Pad<T> {
+impl InferenceRulesOp for TfPad {
     fn rules<'r, 'p: 'r, 's: 'r>(
         &'s self,
         solver: &mut Solver<'r>,
         inputs: &'p TensorsProxy,
         outputs: &'p TensorsProxy,
     ) {
-        let input = &inputs[0];
-        let padding = &inputs[1];
-        let output = &outputs[0];
         solver
             .equals(&inputs.len, 2)
             .equals(&outputs.len, 1)
-            .equals(&output.datum_type, &input.datum_type)
-            .equals(&padding.datum_type, DatumType::TDim)
-            .equals(&input.rank, &output.rank)
-            .equals(&padding.rank, 2)
-            .equals(&padding.shape[0], input.rank.bex().to_dim())
-            .equals(&padding.shape[1], 2.to_dim())
-            .given(&input.rank, move |solver, rank| {
-                (0..rank as usize).for_each(|d| {
-                    solver.equals(
-                        &output.shape[d],
-                        input.shape[d].bex()
-                            + padding.value[d][0].bex().to_dim()
-                            + padding.value[d][1].bex().to_dim(),
-                    ); // FIXME
-                });
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .equals(&inputs[0].rank, &outputs[0].rank)
+            .equals(&inputs[1].rank, 2)
+            .equals(&inputs[1].shape[0], inputs[0].rank.bex().to_dim())
+            .equals(&inputs[1].shape[1], 2.to_dim())
+            .given(&inputs[1].value, move |solver, paddings: Tensor| {
+                if let Ok(pads) = pads(&paddings) {
+                    Pad::new(pads, PadMode::Constant(0.0)).shape_rules(
+                        solver,
+                        &inputs[0],
+                        &outputs[0],
+                    );
+                }
             });
     }
 }
@@ -136,6 +99,6 @@ mod tests {
             ])).into()
         );
 
-        assert_eq!(Pad::<i32>::new().eval(inputs).unwrap(), expected);
+        assert_eq!(TfPad.eval(inputs).unwrap(), expected);
     }
 }
@@ -0,0 +1,86 @@
+use tfdeploy::ops::image::*;
+use tfdeploy::ops::Op;
+use tfdeploy::{DatumType, TfdResult};
+
+use ops::OpRegister;
+use tfpb::node_def::NodeDef;
+
+pub fn register_all_ops(reg: &mut OpRegister) {
+    reg.insert("CropAndResize", crop_and_resize);
+    reg.insert("DecodeJpeg", decode_jpeg);
+    reg.insert("DecodePng", decode_png);
+    reg.insert("ResizeBilinear", |pb| resize(pb, Interpolation::Linear));
+    reg.insert("ResizeNearestNeighbor", |pb| {
+        resize(pb, Interpolation::Nearest(Rounding::RoundPreferCeil))
+    });
+}
+
+pub fn crop_and_resize(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    let interpolation = match &*pb.get_attr_opt_str("method")?.unwrap_or("bilinear".to_string()) {
+        "bilinear" => Interpolation::Linear,
+        "nearest" => Interpolation::Nearest(Rounding::RoundPreferCeil),
+        method => bail!("CropAndResize method {} is not supported", method),
+    };
+    let extrapolation_value = pb.get_attr_opt_float("extrapolation_value")?.unwrap_or(0.0);
+    Ok(Box::new(CropAndResize::new(interpolation, extrapolation_value)))
+}
+
+pub fn decode_jpeg(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    let channels = pb.get_attr_opt_int("channels")?.unwrap_or(0);
+    Ok(Box::new(DecodeImage::new(channels)))
+}
+
+pub fn decode_png(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    if let Some(dt) = pb.get_attr_opt_datum_type("dtype")? {
+        if dt != DatumType::U8 {
+            bail!("DecodePng to {:?} is not supported", dt)
+        }
+    }
+    decode_jpeg(pb)
+}
+
+/// Tensorflow resizes the (height, width) axes of NHWC images. Without
+/// `half_pixel_centers` nor `align_corners`, nearest neighbor floors.
+pub fn resize(pb: &NodeDef, interpolation: Interpolation) -> TfdResult<Box<Op>> {
+    let align_corners = pb.get_attr_opt_bool("align_corners")?.unwrap_or(false);
+    let half_pixel_centers = pb.get_attr_opt_bool("half_pixel_centers")?.unwrap_or(false);
+    let transform = match (align_corners, half_pixel_centers) {
+        (true, true) => bail!("Node {} can not both align corners and use half pixel centers", pb.get_name()),
+        (true, false) => CoordinateTransform::AlignCorners,
+        (false, true) => CoordinateTransform::HalfPixel,
+        (false, false) => CoordinateTransform::Asymmetric,
+    };
+    let interpolation = match interpolation {
+        Interpolation::Nearest(_) if transform == CoordinateTransform::Asymmetric => {
+            Interpolation::Nearest(Rounding::Floor)
+        }
+        other => other,
+    };
+    let size = ResizeSize::Sizes {
+        input: 1,
+        axes: Some(vec![1, 2]),
+    };
+    Ok(Box::new(Resize::new(interpolation, transform, size)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tfdeploy::Tensor;
+    use tfpb::node;
+
+    #[test]
+    fn resize_bilinear_align_corners() {
+        let op = resize(
+            &node().attr("align_corners", true),
+            Interpolation::Linear,
+        ).unwrap();
+        let input = Tensor::f32s(&[1, 1, 2, 1], &[0.0, 3.0]).unwrap();
+        let size = Tensor::i32s(&[2], &[1, 4]).unwrap();
+        let output = op.eval(tvec![input.into(), size.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::f32s(&[1, 1, 4, 1], &[0.0, 1.0, 2.0, 3.0]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,13 @@
+use tfdeploy::ops as tfdops;
+
+use ops::OpRegister;
+use tfpb::node_def::NodeDef;
+
+pub fn register_all_ops(reg: &mut OpRegister) {
+    reg.insert("Equal", with_T!(tfdops::logic::Equals));
+    reg.insert("Greater", with_T!(tfdops::logic::Greater));
+    reg.insert("Less", with_T!(tfdops::logic::Lesser));
+    reg.insert("LogicalAnd", |_| Ok(Box::new(tfdops::logic::And::default()) as _));
+    reg.insert("LogicalNot", |_| Ok(Box::new(tfdops::logic::Not::default()) as _));
+    reg.insert("LogicalOr", |_| Ok(Box::new(tfdops::logic::Or::default()) as _));
+}
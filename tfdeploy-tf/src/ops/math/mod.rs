@@ -0,0 +1,70 @@
+use tfdeploy::ops as tfdops;
+
+use tfpb::node_def::NodeDef;
+use ops::OpRegister;
+use tfdeploy::TfdResult;
+
+mod reduce;
+
+pub fn register_all_ops(reg: &mut OpRegister) {
+    reg.insert("Abs", with_T!(tfdops::math::Abs));
+    reg.insert("Acos", with_T!(tfdops::math::Acos));
+    reg.insert("Add", with_T!(tfdops::math::Add));
+    reg.insert("AddN", add_n);
+    reg.insert("AddV2", with_T!(tfdops::math::Add));
+    reg.insert("ArgMax", reduce::arg_max);
+    reg.insert("ArgMin", reduce::arg_min);
+    reg.insert("Asin", with_T!(tfdops::math::Asin));
+    reg.insert("Atan", with_T!(tfdops::math::Atan));
+    reg.insert("BatchMatMul", batch_mat_mul);
+    reg.insert("BatchMatMulV2", batch_mat_mul);
+    reg.insert("BiasAdd", with_T!(tfdops::math::Add));
+    reg.insert("Ceil", with_T!(tfdops::math::Ceil));
+    reg.insert("Cos", with_T!(tfdops::math::Cos));
+    reg.insert("Div", with_T!(tfdops::math::Div));
+    reg.insert("Exp", with_T!(tfdops::math::Exp));
+    reg.insert("Floor", with_T!(tfdops::math::Floor));
+    reg.insert("FloorMod", with_T!(tfdops::math::Rem));
+    reg.insert("Log", with_T!(tfdops::math::Ln));
+    reg.insert("Max", reduce::max);
+    reg.insert("MatMul", mat_mul);
+    reg.insert("Maximum", with_T!(tfdops::math::Maximum));
+    reg.insert("Mean", reduce::mean);
+    reg.insert("Min", reduce::min);
+    reg.insert("Minimum", with_T!(tfdops::math::Minimum));
+    reg.insert("Mod", with_T!(tfdops::math::Rem));
+    reg.insert("Mul", with_T!(tfdops::math::Mul));
+    reg.insert("Neg", with_T!(tfdops::math::Neg));
+    reg.insert("Pow", with_T!(tfdops::math::Pow));
+    reg.insert("Prod", reduce::prod);
+    reg.insert("RealDiv", with_T!(tfdops::math::Div));
+    reg.insert("Reciprocal", with_T!(tfdops::math::Recip));
+    reg.insert("Rsqrt", with_T!(tfdops::math::Rsqrt));
+    reg.insert("Sin", with_T!(tfdops::math::Sin));
+    reg.insert("Sqrt", with_T!(tfdops::math::Sqrt));
+    reg.insert("Square", with_T!(tfdops::math::Square));
+    reg.insert("SquaredDifference", with_T!(tfdops::math::SquaredDifference));
+    reg.insert("Sub", with_T!(tfdops::math::Sub));
+    reg.insert("Sum", reduce::sum);
+    reg.insert("Tan", with_T!(tfdops::math::Tan));
+    reg.insert("Tanh", with_T!(tfdops::math::Tanh));
+}
+
+pub fn add_n(pb: &NodeDef) -> TfdResult<Box<tfdops::Op>> {
+    let dtype = pb.get_attr_datum_type("T")?;
+    let n = pb.get_attr_int("N")?;
+    Ok(Box::new(tfdops::math::AddN::new(dtype.into(), Some(n))))
+}
+
+
+pub fn mat_mul(pb: &NodeDef) -> TfdResult<Box<tfdops::Op>> {
+    let transpose_a = pb.get_attr_opt_bool("transpose_a")?.unwrap_or(false);
+    let transpose_b = pb.get_attr_opt_bool("transpose_b")?.unwrap_or(false);
+    Ok(Box::new(tfdops::math::MatMul::new(transpose_a, transpose_b)))
+}
+
+pub fn batch_mat_mul(pb: &NodeDef) -> TfdResult<Box<tfdops::Op>> {
+    let adj_x = pb.get_attr_opt_bool("adj_x")?.unwrap_or(false);
+    let adj_y = pb.get_attr_opt_bool("adj_y")?.unwrap_or(false);
+    Ok(Box::new(tfdops::math::MatMul::new(adj_x, adj_y)))
+}
@@ -0,0 +1,154 @@
+use tfdeploy::analyser::rules::prelude::*;
+use tfdeploy::ops::math::{ArgMaxMin, Reduce, Reducer};
+use tfdeploy::ops::prelude::*;
+
+use tfpb::node_def::NodeDef;
+
+/// Reads reduction axes from an i32 or i64 tensor.
+fn axes(axes: &Tensor) -> TfdResult<Vec<i64>> {
+    match axes.datum_type() {
+        DatumType::I32 => Ok(axes.to_array_view::<i32>()?.iter().map(|&a| a as i64).collect()),
+        DatumType::I64 => Ok(axes.to_array_view::<i64>()?.iter().cloned().collect()),
+        dt => bail!("Expected reduction axes as i32 or i64, got {:?}", dt),
+    }
+}
+
+/// Tensorflow reductions, taking their axes as a second input.
+#[derive(Debug, Clone, new)]
+pub struct TfReduce {
+    reducer: Reducer,
+    keep_dims: bool,
+}
+
+pub fn build(pb: &NodeDef, reducer: Reducer) -> TfdResult<Box<Op>> {
+    let keep_dims = pb.get_attr_opt_bool("keep_dims")?.unwrap_or(false);
+    Ok(Box::new(TfReduce::new(reducer, keep_dims)))
+}
+
+pub fn sum(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    build(pb, Reducer::Sum)
+}
+
+pub fn mean(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    build(pb, Reducer::Mean)
+}
+
+pub fn max(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    build(pb, Reducer::Max)
+}
+
+pub fn min(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    build(pb, Reducer::Min)
+}
+
+pub fn prod(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    build(pb, Reducer::Prod)
+}
+
+impl Op for TfReduce {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let (input, reduction_indices) = args_2!(inputs);
+        let axes = axes(&reduction_indices)?;
+        Reduce::new(self.reducer, Some(axes), self.keep_dims).eval(tvec![input])
+    }
+}
+
+impl InferenceRulesOp for TfReduce {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 2)
+            .equals(&outputs.len, 1)
+            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
+            .given(&inputs[1].value, move |solver, indices: Tensor| {
+                if let Ok(axes) = axes(&indices) {
+                    Reduce::new(self.reducer, Some(axes), self.keep_dims)
+                        .shape_rules(solver, &inputs[0], &outputs[0]);
+                }
+            });
+    }
+}
+
+/// Tensorflow ArgMax and ArgMin, taking their axis as a second input.
+#[derive(Debug, Clone, new)]
+pub struct TfArgMaxMin {
+    max: bool,
+    output_type: DatumType,
+}
+
+pub fn arg_max(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    let output_type = pb.get_attr_opt_datum_type("output_type")?.unwrap_or(DatumType::I64);
+    Ok(Box::new(TfArgMaxMin::new(true, output_type)))
+}
+
+pub fn arg_min(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    let output_type = pb.get_attr_opt_datum_type("output_type")?.unwrap_or(DatumType::I64);
+    Ok(Box::new(TfArgMaxMin::new(false, output_type)))
+}
+
+impl Op for TfArgMaxMin {
+    /// Evaluates the operation given the input tensors.
+    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
+        let (input, dimension) = args_2!(inputs);
+        let axis = match axes(&dimension)?.as_slice() {
+            &[axis] => axis,
+            _ => bail!("Expected a single axis, got {:?}", dimension),
+        };
+        let mut result = ArgMaxMin::new(self.max, axis, false).eval(tvec![input])?;
+        if self.output_type == DatumType::I32 {
+            let indices = result.remove(0).to_array_view::<i64>()?.mapv(|i| i as i32);
+            return Ok(tvec![indices.into()]);
+        }
+        Ok(result)
+    }
+}
+
+impl InferenceRulesOp for TfArgMaxMin {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        solver: &mut Solver<'r>,
+        inputs: &'p TensorsProxy,
+        outputs: &'p TensorsProxy,
+    ) {
+        solver
+            .equals(&inputs.len, 2)
+            .equals(&outputs.len, 1)
+            .equals(&outputs[0].datum_type, self.output_type)
+            .given(&inputs[1].value, move |solver, dimension: Tensor| {
+                if let Ok(&[axis]) = axes(&dimension).as_ref().map(|a| a.as_slice()) {
+                    ArgMaxMin::new(self.max, axis, false)
+                        .shape_rules(solver, &inputs[0], &outputs[0]);
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sum_keep_dims() {
+        let op = TfReduce::new(Reducer::Sum, true);
+        let input = Tensor::f32s(&[2, 2], &[1., 2., 3., 4.]).unwrap();
+        let axes = Tensor::i32s(&[1], &[-1]).unwrap();
+        let output = op.eval(tvec![input.into(), axes.into()]).unwrap();
+        assert_eq!(
+            output[0].as_tensor(),
+            &Tensor::f32s(&[2, 1], &[3., 7.]).unwrap()
+        );
+    }
+
+    #[test]
+    fn arg_max_i32() {
+        let op = TfArgMaxMin::new(true, DatumType::I32);
+        let input = Tensor::f32s(&[2, 2], &[1., 2., 4., 3.]).unwrap();
+        let output = op.eval(tvec![input.into(), Tensor::from(1i32).into()]).unwrap();
+        assert_eq!(output[0].as_tensor(), &Tensor::i32s(&[2], &[1, 0]).unwrap());
+    }
+}
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
+use tfdeploy::analyser::types::{GenericFact, ShapeFact, TensorFact};
 use tfdeploy::ops::Op;
-use tfdeploy::TfdResult;
+use tfdeploy::{SymbolTable, TDim, TfdResult};
 
 use tfpb::node_def::NodeDef;
 
@@ -9,6 +10,9 @@ use tfpb::node_def::NodeDef;
 mod macros;
 
 pub mod array;
+#[cfg(feature = "image_ops")]
+pub mod image;
+pub mod logic;
 pub mod math;
 pub mod nn;
 
@@ -20,6 +24,9 @@ impl OpBuilder {
     pub fn new() -> OpBuilder {
         let mut reg = OpRegister::new();
         array::register_all_ops(&mut reg);
+        #[cfg(feature = "image_ops")]
+        image::register_all_ops(&mut reg);
+        logic::register_all_ops(&mut reg);
         math::register_all_ops(&mut reg);
         nn::register_all_ops(&mut reg);
         reg.insert("Const", konst);
@@ -54,8 +61,31 @@ pub fn konst(node: &NodeDef) -> TfdResult<Box<Op>> {
 }
 
 pub fn placeholder(node: &NodeDef) -> TfdResult<Box<Op>> {
-    let dt = node.get_attr_datum_type("dtype")?;
-    Ok(Box::new(::tfdeploy::ops::source::Source::new(
-        ::tfdeploy::analyser::types::TensorFact::dt(dt.into()),
-    )))
+    placeholder_with_symbols(node, &mut SymbolTable::default())
+}
+
+/// Builds a placeholder honoring its `shape` attribute, with its unknown
+/// dimensions named after the node and their axis in the symbol table, or
+/// left unknown if the table is out of symbols.
+pub fn placeholder_with_symbols(node: &NodeDef, symbols: &mut SymbolTable) -> TfdResult<Box<Op>> {
+    let mut fact = TensorFact::dt(node.get_attr_datum_type("dtype")?);
+    if let Some(shape) = node.get_attr_opt_shape("shape")? {
+        if !shape.get_unknown_rank() {
+            let dims = shape
+                .get_dim()
+                .iter()
+                .enumerate()
+                .map(|(axis, d)| {
+                    let dim = if d.get_size() >= 0 {
+                        Some(TDim::from(d.get_size()))
+                    } else {
+                        symbols.sym(&format!("{}:{}", node.get_name(), axis))
+                    };
+                    dim.map(GenericFact::Only).unwrap_or(GenericFact::Any)
+                })
+                .collect();
+            fact = fact.with_shape(ShapeFact::closed(dims));
+        }
+    }
+    Ok(Box::new(::tfdeploy::ops::source::Source::new(fact)))
 }
@@ -0,0 +1,113 @@
+use tfdeploy::ops::nn::{BatchNorm, Lrn};
+use tfdeploy::ops::Op;
+use tfdeploy::TfdResult;
+
+use tfpb::node_def::NodeDef;
+
+pub fn fused_batch_norm(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    if pb.get_attr_opt_bool("is_training")?.unwrap_or(true) {
+        bail!("{} is only supported for inference (is_training=false)", pb.get_op())
+    }
+    let epsilon = pb.get_attr_opt_float("epsilon")?.unwrap_or(0.0001);
+    let channel_axis = super::data_format(pb)?.channel_axis(4);
+    // y, then batch mean and variance, and the reserved mean and variance.
+    // V3 adds a third reserved space, left to the mean.
+    let statistics = if pb.get_op() == "FusedBatchNormV3" { 5 } else { 4 };
+    Ok(Box::new(BatchNorm::with_statistics(epsilon, channel_axis, statistics)))
+}
+
+pub fn lrn(pb: &NodeDef) -> TfdResult<Box<Op>> {
+    let depth_radius: usize = pb.get_attr_opt_int("depth_radius")?.unwrap_or(5);
+    let bias = pb.get_attr_opt_float("bias")?.unwrap_or(1.0);
+    let alpha = pb.get_attr_opt_float("alpha")?.unwrap_or(1.0);
+    let beta = pb.get_attr_opt_float("beta")?.unwrap_or(0.5);
+    Ok(Box::new(Lrn::new(alpha, beta, bias, 2 * depth_radius + 1, 3)))
+}
+
+#[cfg(test)]
+mod tests {
+    use tfdeploy::analyser::TensorFact;
+    use tfdeploy::model::Model;
+    use tfdeploy::{DatumType, SimplePlan, Tensor, ToTfd};
+    use tfpb;
+    use tfpb::types::DataType::DT_FLOAT;
+
+    fn konst(name: &str, shape: Vec<usize>, values: Vec<f32>) -> tfpb::node_def::NodeDef {
+        tfpb::node()
+            .name(name)
+            .op("Const")
+            .attr("dtype", DT_FLOAT)
+            .attr("value", tfpb::tensor_f32(shape, values))
+    }
+
+    #[test]
+    fn fold_into_conv2d() {
+        let graph = tfpb::graph()
+            .node(tfpb::node().name("data").op("Placeholder").attr("dtype", DT_FLOAT))
+            .node(konst("filter", vec![1, 2, 1, 2], vec![1., 2., 3., 4.]))
+            .node(
+                tfpb::node()
+                    .name("conv")
+                    .op("Conv2D")
+                    .input("data")
+                    .input("filter")
+                    .attr("T", DT_FLOAT)
+                    .attr("strides", vec![1, 1, 1, 1])
+                    .attr("padding", "VALID"),
+            )
+            .node(konst("scale", vec![2], vec![1., 2.]))
+            .node(konst("offset", vec![2], vec![0.5, -1.]))
+            .node(konst("mean", vec![2], vec![1., 2.]))
+            .node(konst("variance", vec![2], vec![4., 0.25]))
+            .node(
+                tfpb::node()
+                    .name("bn")
+                    .op("FusedBatchNorm")
+                    .input("conv")
+                    .input("scale")
+                    .input("offset")
+                    .input("mean")
+                    .input("variance")
+                    .attr("T", DT_FLOAT)
+                    .attr("epsilon", 0f32)
+                    .attr("is_training", false),
+            );
+        let model: Model = graph.to_tfd().unwrap();
+        let input = Tensor::f32s(&[1, 2, 3, 1], &[1., 2., 3., 4., 5., 6.]).unwrap();
+        let plan = SimplePlan::new(&model, &["data"], &["bn"]).unwrap();
+        let expected = plan.run(tvec![input.clone()]).unwrap();
+
+        let mut analyser = model.analyser("bn").unwrap();
+        analyser
+            .hint("data", &TensorFact::dt_shape(DatumType::F32, vec![1, 2, 3, 1]))
+            .unwrap();
+        let optimized = analyser.to_optimized_model().unwrap();
+        assert_eq!(optimized.node_by_name("bn").unwrap().op_name, "Add");
+        let plan = SimplePlan::new(&optimized, &["data"], &["bn"]).unwrap();
+        let folded = plan.run(tvec![input]).unwrap();
+        assert!(folded[0][0].close_enough(&expected[0][0], true));
+    }
+    #[test]
+    fn forwards_statistics() {
+        let node = tfpb::node()
+            .op("FusedBatchNorm")
+            .attr("T", DT_FLOAT)
+            .attr("epsilon", 0f32)
+            .attr("is_training", false);
+        let op = super::fused_batch_norm(&node).unwrap();
+        let mean = Tensor::f32s(&[1], &[1.]).unwrap();
+        let variance = Tensor::f32s(&[1], &[4.]).unwrap();
+        let outputs = op
+            .eval(tvec![
+                Tensor::f32s(&[1, 1, 1, 1], &[3.]).unwrap().into(),
+                Tensor::f32s(&[1], &[1.]).unwrap().into(),
+                Tensor::f32s(&[1], &[0.]).unwrap().into(),
+                mean.clone().into(),
+                variance.clone().into(),
+            ]).unwrap();
+        assert_eq!(outputs.len(), 5);
+        assert_eq!(outputs[0].as_tensor(), &Tensor::f32s(&[1, 1, 1, 1], &[1.]).unwrap());
+        assert_eq!(outputs[1].as_tensor(), &mean);
+        assert_eq!(outputs[4].as_tensor(), &variance);
+    }
+}
@@ -1,62 +1,54 @@
-use tfdeploy::analyser::rules::prelude::*;
-use tfdeploy::ops::prelude::*;
+use tfdeploy::ops::nn::{DataFormat, PaddingSpec};
+use tfdeploy::TfdResult;
 
 use ops::OpRegister;
 use tfpb::node_def::NodeDef;
 
+pub mod batch_norm;
 pub mod conv2d;
-pub mod local_patch;
 pub mod pools;
 pub mod space_to_batch;
 
 pub fn register_all_ops(reg: &mut OpRegister) {
-    reg.insert("AvgPool", pools::pool::<pools::AvgPooler>);
+    reg.insert("AvgPool", pools::avg_pool);
     reg.insert("Conv2D", conv2d::conv2d);
-    reg.insert("MaxPool", pools::pool::<pools::MaxPooler>);
+    reg.insert("Conv3D", conv2d::conv3d);
+    reg.insert("DepthwiseConv2dNative", conv2d::depthwise_conv2d);
+    reg.insert("Elu", with_T!(::tfdeploy::ops::nn::Elu));
+    reg.insert("FusedBatchNorm", batch_norm::fused_batch_norm);
+    reg.insert("FusedBatchNormV2", batch_norm::fused_batch_norm);
+    reg.insert("FusedBatchNormV3", batch_norm::fused_batch_norm);
+    reg.insert("LRN", batch_norm::lrn);
+    reg.insert("LeakyRelu", |pb| {
+        let alpha = pb.get_attr_opt_float("alpha")?.unwrap_or(0.2);
+        Ok(Box::new(::tfdeploy::ops::nn::LeakyRelu::new(alpha)))
+    });
+    reg.insert("LogSoftmax", |_| Ok(Box::new(::tfdeploy::ops::nn::LogSoftmax::new(-1, false))));
+    reg.insert("MaxPool", pools::max_pool);
     reg.insert("Relu", with_T!(::tfdeploy::ops::nn::Relu));
+    reg.insert("Relu6", with_T!(::tfdeploy::ops::nn::Relu6));
+    reg.insert("Selu", with_T!(::tfdeploy::ops::nn::Selu));
     reg.insert("Sigmoid", with_T!(::tfdeploy::ops::nn::Sigmoid));
-    reg.insert("Softmax", Softmax::build);
+    reg.insert("Softmax", |_| Ok(Box::new(::tfdeploy::ops::nn::Softmax::new(-1, false))));
+    reg.insert("Softplus", with_T!(::tfdeploy::ops::nn::Softplus));
     reg.insert("SpaceToBatchND", space_to_batch::space_to_batch_nd);
     reg.insert("BatchToSpaceND", space_to_batch::batch_to_space_nd);
 }
 
-#[derive(Debug, Clone)]
-pub struct Softmax {}
-
-impl Softmax {
-    pub fn build(_pb: &NodeDef) -> TfdResult<Box<Op>> {
-        Ok(Box::new(Softmax {}))
-    }
-}
-
-impl Op for Softmax {
-    /// Evaluates the operation given the input tensors.
-    fn eval(&self, mut inputs: TVec<Value>) -> TfdResult<TVec<Value>> {
-        let m_input = args_1!(inputs);
-        let mut input = m_input
-            .into_tensor()
-            .take_f32s()
-            .ok_or("Expect input #0 to be f32")?;
-        input.map_inplace(|a| *a = a.exp());
-        let norm: f32 = input.iter().sum();
-        input.map_inplace(|a| *a = *a / norm);
-        let result = Tensor::from(input);
-        Ok(tvec![result.into()])
+/// Reads the layout of the data from the `data_format` attribute.
+pub fn data_format(pb: &NodeDef) -> TfdResult<DataFormat> {
+    match pb.get_attr_opt_raw_str("data_format")?.unwrap_or(b"NHWC") {
+        b"NHWC" | b"NDHWC" | b"NWC" => Ok(DataFormat::NHWC),
+        b"NCHW" | b"NCDHW" | b"NCW" => Ok(DataFormat::NCHW),
+        f => bail!("Unsupported data_format {}", String::from_utf8_lossy(f)),
     }
 }
 
-impl InferenceRulesOp for Softmax {
-    /// Registers the inference rules of the operator.
-    fn rules<'r, 'p: 'r, 's: 'r>(
-        &'s self,
-        solver: &mut Solver<'r>,
-        inputs: &'p TensorsProxy,
-        outputs: &'p TensorsProxy,
-    ) {
-        solver
-            .equals(&inputs.len, 1)
-            .equals(&outputs.len, 1)
-            .equals(&inputs[0].datum_type, &outputs[0].datum_type)
-            .equals(&inputs[0].shape, &outputs[0].shape);
+/// Reads the `padding` attribute.
+pub fn padding(pb: &NodeDef) -> TfdResult<PaddingSpec> {
+    match pb.get_attr_raw_str("padding")? {
+        b"VALID" => Ok(PaddingSpec::Valid),
+        b"SAME" => Ok(PaddingSpec::SameUpper),
+        s => bail!("Unsupported padding {}", String::from_utf8_lossy(s)),
     }
 }
@@ -34,7 +34,7 @@ impl<T: Datum + Zero> Op for SpaceToBatch<T> {
             if pad[0] != 0 {
                 let mut pad_shape = data.shape().to_vec();
                 pad_shape[ix + 1] = pad[0] as usize;
-                let tmp = ::ndarray::stack(
+                let tmp = ::tfdeploy::ops::array::stack(
                     ::ndarray::Axis(ix + 1),
                     &[::ndarray::ArrayD::zeros(pad_shape).view(), data.view()],
                 )?;
@@ -43,7 +43,7 @@ impl<T: Datum + Zero> Op for SpaceToBatch<T> {
             if pad[1] != 0 {
                 let mut pad_shape = data.shape().to_vec();
                 pad_shape[ix + 1] = pad[1] as usize;
-                let tmp = ::ndarray::stack(
+                let tmp = ::tfdeploy::ops::array::stack(
                     ::ndarray::Axis(ix + 1),
                     &[data.view(), ::ndarray::ArrayD::zeros(pad_shape).view()],
                 )?;
@@ -67,7 +67,7 @@ impl<T: Datum + Zero> Op for SpaceToBatch<T> {
         permuted_axes.extend((0..block_shape.len()).map(|x| 2 * x + 1));
         permuted_axes.extend((block_shape.len() * 2 + 1)..data.ndim());
         let data = data.permuted_axes(permuted_axes);
-        let data: Vec<T> = data.into_iter().map(|x| *x).collect();
+        let data: Vec<T> = data.into_iter().cloned().collect();
         let data = ::ndarray::ArrayD::from_shape_vec(final_shape, data)?;
 
         Ok(tvec![data.into()])
@@ -132,7 +132,7 @@ impl<T: Datum + Zero> Op for SpaceToBatch<T> {
         let data = Array::from_shape_fn(shape, |mut coords| -> T {
             let buf = &buffer.buffer[coords[info.axis]];
             coords[info.axis] = 0;
-            buf[coords]
+            buf[coords].clone()
         });
         buffer.buffer.clear();
         Ok(Some(self.eval(tvec!(
@@ -237,7 +237,7 @@ impl<T: Datum> Op for BatchToSpace<T> {
         permuted_axes.extend((1 + block_shape.len() * 2)..data.ndim());
         padded_shape.extend(&input_shape[1 + block_shape.len()..]);
         let data = data.permuted_axes(permuted_axes);
-        let data: Vec<T> = data.into_iter().map(|x| *x).collect();
+        let data: Vec<T> = data.into_iter().cloned().collect();
         let data = ::ndarray::ArrayD::from_shape_vec(padded_shape, data)?;
         let mut data = data;
         for (i, crop) in crops.outer_iter().enumerate() {
@@ -246,7 +246,7 @@ impl<T: Datum> Op for BatchToSpace<T> {
                 let range = (crop[0] as usize)..(end - crop[1] as usize);
                 data = data
                     .slice_axis(Axis(i + 1), range.into())
-                    .map(|x| *x)
+                    .map(|x| x.clone())
                     .to_owned();
             }
         }
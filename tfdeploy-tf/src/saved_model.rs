@@ -0,0 +1,451 @@
+//! Loading of Tensorflow SavedModel directories.
+//!
+//! A SavedModel directory holds a `saved_model.pb` file with one or more
+//! MetaGraphDef, told apart by their tags, and the variable values in a tensor
+//! bundle checkpoint under `variables/`. The variables of the graph are
+//! replaced by constants holding their checkpointed value.
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::{fs, path};
+
+use tfdeploy::model::{Model, OutletId, TVec};
+use tfdeploy::{SimplePlan, Tensor, TfdResult, ToTfd};
+
+use tfpb::graph::GraphDef;
+use tfpb::saved_model::SavedModel;
+use tfpb::tensor::TensorProto;
+use tfpb::tensor_bundle::{BundleEntryProto, BundleHeaderProto, BundleHeaderProto_Endianness};
+use ToTensorflow;
+
+/// A model loaded from a SavedModel, with the tensors of one of its signatures.
+#[derive(Debug, Clone)]
+pub struct SignatureModel {
+    pub model: Model,
+    /// The signature inputs, as pairs of key and tensor name, sorted by key.
+    pub inputs: Vec<(String, String)>,
+    /// The signature outputs, as pairs of key and tensor name, sorted by key.
+    pub outputs: Vec<(String, String)>,
+}
+
+impl SignatureModel {
+    /// The model outlet of a "node:slot" tensor name.
+    pub fn outlet(&self, tensor: &str) -> TfdResult<OutletId> {
+        let mut splits = tensor.splitn(2, ':');
+        let node = self.model.node_by_name(splits.next().unwrap())?;
+        let slot = match splits.next() {
+            Some(slot) => slot
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid slot in tensor name {}", tensor))?,
+            None => 0,
+        };
+        Ok(OutletId::new(node.id, slot))
+    }
+
+    /// A plan computing the signature outputs from its inputs, in key order.
+    pub fn plan(&self) -> TfdResult<SignaturePlan> {
+        let mut inputs = vec![];
+        for (key, tensor) in &self.inputs {
+            let outlet = self.outlet(tensor)?;
+            if outlet.slot != 0 {
+                bail!("Signature input {} ({}) is not the first output of its node, not supported", key, tensor)
+            }
+            inputs.push(self.model.nodes()[outlet.node].name.clone());
+        }
+        // The plan computes nodes, each signature output picks its slot.
+        let mut nodes: Vec<String> = vec![];
+        let mut outputs = vec![];
+        for (_, tensor) in &self.outputs {
+            let outlet = self.outlet(tensor)?;
+            let name = &self.model.nodes()[outlet.node].name;
+            let ix = match nodes.iter().position(|n| n == name) {
+                Some(ix) => ix,
+                None => {
+                    nodes.push(name.clone());
+                    nodes.len() - 1
+                }
+            };
+            outputs.push((ix, outlet.slot));
+        }
+        Ok(SignaturePlan {
+            plan: SimplePlan::new(&self.model, &inputs, &nodes)?,
+            outputs,
+        })
+    }
+}
+
+/// A plan for a signature, whose outputs may be any output of their node.
+#[derive(Debug, Clone)]
+pub struct SignaturePlan {
+    pub plan: SimplePlan,
+    /// For each signature output, the index of its node in the plan outputs
+    /// and its slot.
+    outputs: Vec<(usize, usize)>,
+}
+
+impl SignaturePlan {
+    /// Runs the plan, returning the signature outputs in key order.
+    pub fn run(&self, inputs: TVec<Tensor>) -> TfdResult<TVec<Tensor>> {
+        let results = self.plan.run(inputs)?;
+        self.outputs
+            .iter()
+            .map(|&(ix, slot)| {
+                Ok(results[ix]
+                    .get(slot)
+                    .ok_or_else(|| {
+                        let node = &self.plan.model.nodes()[self.plan.output_ids[ix]];
+                        format!("Node {} has no output {}", node.name, slot)
+                    })?
+                    .clone())
+            }).collect()
+    }
+}
+
+/// Load a signature of the "serve" MetaGraphDef of a SavedModel directory.
+pub fn for_saved_model<P: AsRef<path::Path>>(dir: P, signature: &str) -> TfdResult<SignatureModel> {
+    for_saved_model_with_tags(dir, &["serve"], signature)
+}
+
+/// Load a signature of a SavedModel directory, from the MetaGraphDef with the
+/// given tags, in any order.
+pub fn for_saved_model_with_tags<P: AsRef<path::Path>>(
+    dir: P,
+    tags: &[&str],
+    signature: &str,
+) -> TfdResult<SignatureModel> {
+    let dir = dir.as_ref();
+    let saved_model = ::protobuf::parse_from_reader::<SavedModel>(&mut fs::File::open(
+        dir.join("saved_model.pb"),
+    )?).map_err(|e| format!("{:?}", e))?;
+    let meta_graph = saved_model
+        .get_meta_graphs()
+        .iter()
+        .find(|m| {
+            let found = m.get_meta_info_def().get_tags();
+            found.len() == tags.len() && tags.iter().all(|t| found.iter().any(|f| f == t))
+        })
+        .ok_or_else(|| format!("No MetaGraphDef tagged {:?} in {:?}", tags, dir))?;
+    let signature = meta_graph
+        .get_signature_def()
+        .get(signature)
+        .ok_or_else(|| format!("No signature {} for tags {:?} in {:?}", signature, tags, dir))?;
+    let tensors = |infos: &HashMap<String, ::tfpb::meta_graph::TensorInfo>| {
+        let mut tensors: Vec<(String, String)> = infos
+            .iter()
+            .map(|(k, info)| (k.to_string(), info.get_name().to_string()))
+            .collect();
+        tensors.sort();
+        tensors
+    };
+    let inputs = tensors(signature.get_inputs());
+    let outputs = tensors(signature.get_outputs());
+
+    let mut graph = meta_graph.get_graph_def().clone();
+    if graph.get_node().iter().any(|n| is_variable(n.get_op())) {
+        let variables = read_checkpoint(dir.join("variables").join("variables"))?;
+        freeze_variables(&mut graph, &variables)?;
+    }
+    Ok(SignatureModel {
+        model: graph.to_tfd()?,
+        inputs,
+        outputs,
+    })
+}
+
+/// The suffix of the keys of the variables in object based (Tensorflow 2)
+/// checkpoints, keyed by their path in the object graph.
+const OBJECT_KEY_SUFFIX: &str = "/.ATTRIBUTES/VARIABLE_VALUE";
+
+/// The key of the object graph of object based checkpoints.
+const OBJECT_GRAPH_KEY: &str = "_CHECKPOINTABLE_OBJECT_GRAPH";
+
+fn is_variable(op: &str) -> bool {
+    op == "VariableV2" || op == "VarHandleOp"
+}
+
+/// Replaces the variables of a graph by constants.
+///
+/// Resource variables (`VarHandleOp`) are read by `ReadVariableOp` nodes,
+/// which become `Identity` nodes.
+pub fn freeze_variables(graph: &mut GraphDef, variables: &HashMap<String, Tensor>) -> TfdResult<()> {
+    for node in graph.mut_node().iter_mut() {
+        if node.get_op() == "ReadVariableOp" {
+            node.set_op("Identity".to_string());
+        }
+        if !is_variable(node.get_op()) {
+            continue;
+        }
+        let shared_name = node.get_attr_opt_str("shared_name")?;
+        let value = shared_name
+            .iter()
+            .filter(|n| !n.is_empty())
+            .chain(Some(&node.get_name().to_string()))
+            .filter_map(|n| variables.get(n))
+            .next();
+        let value = match value {
+            Some(value) => value,
+            None if variables.keys().any(|k| k.ends_with(OBJECT_KEY_SUFFIX)) => bail!(
+                "No value for variable {}: the checkpoint keys are object based, which is not supported",
+                node.get_name()
+            ),
+            None => bail!("No value for variable {} in checkpoint", node.get_name()),
+        };
+        let value: TensorProto = value.to_tf()?;
+        let mut konst = ::tfpb::node()
+            .name(node.get_name())
+            .op("Const")
+            .attr("dtype", value.get_dtype())
+            .attr("value", value);
+        for input in node.get_input().iter().filter(|i| i.starts_with('^')) {
+            konst.mut_input().push(input.to_string());
+        }
+        *node = konst;
+    }
+    Ok(())
+}
+
+/// Reads the tensors of a tensor bundle checkpoint, given the path prefix of
+/// its files (`variables/variables` in a SavedModel).
+pub fn read_checkpoint<P: AsRef<path::Path>>(prefix: P) -> TfdResult<HashMap<String, Tensor>> {
+    let prefix = prefix.as_ref().to_string_lossy().to_string();
+    let entries = read_table(fs::File::open(format!("{}.index", prefix))?)?;
+    let header: BundleHeaderProto = match entries.get("") {
+        Some(header) => ::protobuf::parse_from_bytes(header).map_err(|e| format!("{:?}", e))?,
+        None => bail!("Checkpoint {} has no header", prefix),
+    };
+    if header.get_endianness() != BundleHeaderProto_Endianness::LITTLE {
+        bail!("Checkpoint {} is big endian, not supported", prefix)
+    }
+    let mut shards = HashMap::new();
+    let mut tensors = HashMap::new();
+    for (key, entry) in entries.iter().filter(|e| !e.0.is_empty() && e.0 != OBJECT_GRAPH_KEY) {
+        let entry: BundleEntryProto =
+            ::protobuf::parse_from_bytes(entry).map_err(|e| format!("{:?}", e))?;
+        let shard = match shards.entry(entry.get_shard_id()) {
+            Entry::Occupied(shard) => shard.into_mut(),
+            Entry::Vacant(shard) => shard.insert(fs::File::open(format!(
+                "{}.data-{:05}-of-{:05}",
+                prefix,
+                entry.get_shard_id(),
+                header.get_num_shards()
+            ))?),
+        };
+        let mut content = vec![0u8; entry.get_size() as usize];
+        shard.seek(SeekFrom::Start(entry.get_offset() as u64))?;
+        shard.read_exact(&mut content)?;
+        let mut proto = TensorProto::new();
+        proto.set_dtype(entry.get_dtype());
+        proto.set_tensor_shape(entry.get_shape().clone());
+        proto.set_tensor_content(content);
+        let tensor = proto
+            .to_tfd()
+            .map_err(|e| format!("While reading variable {}, {}", key, e))?;
+        tensors.insert(key.to_string(), tensor);
+    }
+    Ok(tensors)
+}
+
+fn varint(data: &[u8], pos: &mut usize) -> TfdResult<u64> {
+    let mut value = 0u64;
+    for shift in 0..10 {
+        let byte = *data.get(*pos).ok_or("Truncated varint in checkpoint index")?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << (7 * shift);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Invalid varint in checkpoint index")
+}
+
+fn fixed32(data: &[u8], pos: usize) -> TfdResult<usize> {
+    let bytes = data
+        .get(pos..pos + 4)
+        .ok_or("Truncated checkpoint index")?;
+    Ok(bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as usize))
+}
+
+/// Reads a block of the index file, given its handle.
+fn block(file: &[u8], handle: &[u8]) -> TfdResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut pos = 0;
+    let offset = varint(handle, &mut pos)? as usize;
+    let size = varint(handle, &mut pos)? as usize;
+    let data = file
+        .get(offset..offset + size + 1)
+        .ok_or("Truncated checkpoint index")?;
+    if data[size] != 0 {
+        bail!("Compressed checkpoint index blocks are not supported")
+    }
+    let data = &data[..size];
+    let restarts = fixed32(data, size.saturating_sub(4))?;
+    if size < 4 + 4 * restarts {
+        bail!("Invalid block in checkpoint index")
+    }
+    let end = size - 4 - 4 * restarts;
+    let mut entries: Vec<(Vec<u8>, Vec<u8>)> = vec![];
+    let mut pos = 0;
+    while pos < end {
+        let shared = varint(data, &mut pos)? as usize;
+        let unshared = varint(data, &mut pos)? as usize;
+        let value = varint(data, &mut pos)? as usize;
+        let mut key = match entries.last() {
+            Some(last) if shared <= last.0.len() => last.0[..shared].to_vec(),
+            _ if shared == 0 => vec![],
+            _ => bail!("Invalid key in checkpoint index"),
+        };
+        let rest = data
+            .get(pos..pos + unshared + value)
+            .ok_or("Truncated checkpoint index")?;
+        key.extend_from_slice(&rest[..unshared]);
+        entries.push((key, rest[unshared..].to_vec()));
+        pos += unshared + value;
+    }
+    Ok(entries)
+}
+
+/// Reads all entries of the sorted string table of a checkpoint index.
+fn read_table<R: Read>(mut r: R) -> TfdResult<HashMap<String, Vec<u8>>> {
+    const FOOTER: usize = 48;
+    const MAGIC: u64 = 0xdb47_7524_8b80_fb57;
+    let mut file = vec![];
+    r.read_to_end(&mut file)?;
+    if file.len() < FOOTER {
+        bail!("Checkpoint index is too short")
+    }
+    let footer = &file[file.len() - FOOTER..];
+    let magic = footer[40..].iter().rev().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+    if magic != MAGIC {
+        bail!("Checkpoint index has a wrong magic number")
+    }
+    // The footer starts with the handles of the meta index and index blocks.
+    let mut pos = 0;
+    varint(footer, &mut pos)?;
+    varint(footer, &mut pos)?;
+    let index = block(&file, &footer[pos..])?;
+    let mut entries = HashMap::new();
+    for (_, handle) in index {
+        for (key, value) in block(&file, &handle)? {
+            entries.insert(String::from_utf8(key).map_err(|e| format!("{:?}", e))?, value);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protobuf::Message;
+    use std::io::Write;
+    use tfpb;
+    use tfpb::meta_graph::{MetaGraphDef, SignatureDef, TensorInfo};
+    use tfpb::types::DataType::DT_FLOAT;
+
+    fn put_varint(out: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            out.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        out.push(v as u8);
+    }
+
+    // An uncompressed block without prefix compression, returning its handle.
+    fn write_block(file: &mut Vec<u8>, entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let offset = file.len() as u64;
+        for (key, value) in entries {
+            put_varint(file, 0);
+            put_varint(file, key.len() as u64);
+            put_varint(file, value.len() as u64);
+            file.extend_from_slice(key.as_bytes());
+            file.extend_from_slice(value);
+        }
+        file.extend_from_slice(&[0, 0, 0, 0, 1, 0, 0, 0]);
+        let size = file.len() as u64 - offset;
+        file.extend_from_slice(&[0, 0, 0, 0, 0]);
+        let mut handle = vec![];
+        put_varint(&mut handle, offset);
+        put_varint(&mut handle, size);
+        handle
+    }
+
+    // A sorted string table with a single data block.
+    fn table(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut file = vec![];
+        let data = write_block(&mut file, entries);
+        let meta = write_block(&mut file, &[]);
+        let last = entries.last().unwrap().0;
+        let index = write_block(&mut file, &[(last, data)]);
+        let mut footer = meta;
+        footer.extend(index);
+        footer.resize(40, 0);
+        footer.extend_from_slice(&[0x57, 0xfb, 0x80, 0x8b, 0x24, 0x75, 0x47, 0xdb]);
+        file.extend(footer);
+        file
+    }
+
+    fn tensor_info(name: &str) -> TensorInfo {
+        let mut info = TensorInfo::new();
+        info.set_name(name.to_string());
+        info
+    }
+
+    #[test]
+    fn saved_model_with_variable() {
+        let dir = ::std::env::temp_dir().join(format!("tfdeploy-tf-{}", ::std::process::id()));
+        fs::create_dir_all(dir.join("variables")).unwrap();
+
+        let graph = tfpb::graph()
+            .node(tfpb::node().name("x").op("Placeholder").attr("dtype", DT_FLOAT))
+            .node(tfpb::node().name("w").op("VariableV2").attr("dtype", DT_FLOAT))
+            .node(tfpb::node().name("w/read").op("Identity").input("w"))
+            .node(tfpb::node().name("y").op("Mul").input("x").input("w/read").attr("T", DT_FLOAT))
+            .node(tfpb::node().name("pair").op("IdentityN").input("y").input("x"));
+        let mut signature = SignatureDef::new();
+        signature.mut_inputs().insert("x".to_string(), tensor_info("x:0"));
+        signature.mut_outputs().insert("y".to_string(), tensor_info("y:0"));
+        signature.mut_outputs().insert("z".to_string(), tensor_info("pair:1"));
+        // A training graph comes first, the serving one must be picked.
+        let mut training = MetaGraphDef::new();
+        training.mut_meta_info_def().mut_tags().push("train".to_string());
+        training.mut_signature_def().insert("serving_default".to_string(), signature.clone());
+        let mut meta_graph = MetaGraphDef::new();
+        meta_graph.mut_meta_info_def().mut_tags().push("serve".to_string());
+        meta_graph.set_graph_def(graph);
+        meta_graph.mut_signature_def().insert("serving_default".to_string(), signature);
+        let mut saved_model = SavedModel::new();
+        saved_model.mut_meta_graphs().push(training);
+        saved_model.mut_meta_graphs().push(meta_graph);
+        let bytes = saved_model.write_to_bytes().unwrap();
+        fs::File::create(dir.join("saved_model.pb")).unwrap().write_all(&bytes).unwrap();
+
+        let mut header = BundleHeaderProto::new();
+        header.set_num_shards(1);
+        let mut entry = BundleEntryProto::new();
+        entry.set_dtype(DT_FLOAT);
+        entry.set_size(4);
+        let index = table(&[
+            ("", header.write_to_bytes().unwrap()),
+            ("w", entry.write_to_bytes().unwrap()),
+        ]);
+        let variables = dir.join("variables");
+        fs::write(variables.join("variables.index"), index).unwrap();
+        fs::write(variables.join("variables.data-00000-of-00001"), 3.0f32.to_bits().to_le_bytes()).unwrap();
+
+        let model = for_saved_model(&dir, "serving_default").unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(model.inputs, vec![("x".to_string(), "x:0".to_string())]);
+        assert_eq!(model.model.node_by_name("w").unwrap().op_name, "Const");
+        let result = model.plan().unwrap().run(tvec![Tensor::from(2.0f32)]).unwrap();
+        assert_eq!(result[0], Tensor::from(6.0f32));
+        assert_eq!(result[1], Tensor::from(2.0f32));
+    }
+
+    #[test]
+    fn object_based_checkpoint() {
+        let mut graph = tfpb::graph()
+            .node(tfpb::node().name("w").op("VarHandleOp").attr("dtype", DT_FLOAT));
+        let mut variables = HashMap::new();
+        variables.insert("model/w/.ATTRIBUTES/VARIABLE_VALUE".to_string(), Tensor::from(3.0f32));
+        let error = freeze_variables(&mut graph, &variables).unwrap_err();
+        assert!(error.to_string().contains("object based"));
+    }
+}
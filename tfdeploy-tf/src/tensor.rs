@@ -1,6 +1,8 @@
 use tfpb::tensor::TensorProto;
 use tfpb::tensor_shape::{TensorShapeProto, TensorShapeProto_Dim};
 use tfpb::types::DataType;
+use ndarray::ArrayD;
+use tfdeploy::tensor::{f16, Complex, Datum};
 use tfdeploy::{ DatumType, TfdResult, TfdFrom, Tensor };
 use ToTensorflow;
 
@@ -10,12 +12,17 @@ impl TfdFrom<DataType> for DatumType {
             &DataType::DT_BOOL => Ok(DatumType::Bool),
             &DataType::DT_UINT8 => Ok(DatumType::U8),
             &DataType::DT_UINT16 => Ok(DatumType::U16),
+            &DataType::DT_UINT32 => Ok(DatumType::U32),
+            &DataType::DT_UINT64 => Ok(DatumType::U64),
             &DataType::DT_INT8 => Ok(DatumType::I8),
             &DataType::DT_INT16 => Ok(DatumType::I16),
             &DataType::DT_INT32 => Ok(DatumType::I32),
             &DataType::DT_INT64 => Ok(DatumType::I64),
+            &DataType::DT_HALF => Ok(DatumType::F16),
             &DataType::DT_FLOAT => Ok(DatumType::F32),
             &DataType::DT_DOUBLE => Ok(DatumType::F64),
+            &DataType::DT_COMPLEX64 => Ok(DatumType::ComplexF32),
+            &DataType::DT_COMPLEX128 => Ok(DatumType::ComplexF64),
             &DataType::DT_STRING => Ok(DatumType::String),
             _ => Err(format!("Unknown DatumType {:?}", t))?,
         }
@@ -28,18 +35,37 @@ impl ToTensorflow<DataType> for DatumType {
             DatumType::Bool => Ok(DataType::DT_BOOL),
             DatumType::U8 => Ok(DataType::DT_UINT8),
             DatumType::U16 => Ok(DataType::DT_UINT16),
+            DatumType::U32 => Ok(DataType::DT_UINT32),
+            DatumType::U64 => Ok(DataType::DT_UINT64),
             DatumType::I8 => Ok(DataType::DT_INT8),
             DatumType::I16 => Ok(DataType::DT_INT16),
             DatumType::I32 => Ok(DataType::DT_INT32),
             DatumType::I64 => Ok(DataType::DT_INT64),
+            DatumType::F16 => Ok(DataType::DT_HALF),
             DatumType::F32 => Ok(DataType::DT_FLOAT),
             DatumType::F64 => Ok(DataType::DT_DOUBLE),
+            DatumType::ComplexF32 => Ok(DataType::DT_COMPLEX64),
+            DatumType::ComplexF64 => Ok(DataType::DT_COMPLEX128),
             DatumType::String => Ok(DataType::DT_STRING),
             DatumType::TDim => bail!("Dimension is not translatable in protobuf"),
         }
     }
 }
 
+/// Builds a tensor from the values of a `xxx_val` field.
+///
+/// TensorFlow allows the field to be shorter than the shape: the last value
+/// is then repeated to fill the tensor (this is how constants of a single
+/// value are usually serialized).
+fn from_vals<T: Datum>(dims: &[usize], mut values: Vec<T>) -> TfdResult<Tensor> {
+    let len = dims.iter().product::<usize>();
+    if values.len() < len {
+        let last = values.last().cloned().unwrap_or_default();
+        values.resize(len, last);
+    }
+    Ok(ArrayD::from_shape_vec(dims, values)?.into())
+}
+
 impl TfdFrom<TensorProto> for Tensor {
     fn tfd_from(t: &TensorProto) -> TfdResult<Tensor> {
         let dtype = t.get_dtype();
@@ -54,16 +80,80 @@ impl TfdFrom<TensorProto> for Tensor {
         let mat: Tensor = if content.len() != 0 {
             unsafe {
                 match dtype {
-                    DataType::DT_FLOAT => Self::from_raw::<f32>(&dims, content)?,
+                    DataType::DT_BOOL => Self::from_raw::<u8>(&dims, content)?
+                        .into_array::<u8>()?
+                        .mapv(|x| x != 0)
+                        .into(),
+                    DataType::DT_UINT8 => Self::from_raw::<u8>(&dims, content)?,
+                    DataType::DT_UINT16 => Self::from_raw::<u16>(&dims, content)?,
+                    DataType::DT_UINT32 => Self::from_raw::<u32>(&dims, content)?,
+                    DataType::DT_UINT64 => Self::from_raw::<u64>(&dims, content)?,
+                    DataType::DT_INT8 => Self::from_raw::<i8>(&dims, content)?,
+                    DataType::DT_INT16 => Self::from_raw::<i16>(&dims, content)?,
                     DataType::DT_INT32 => Self::from_raw::<i32>(&dims, content)?,
-                    _ => unimplemented!("missing type"),
+                    DataType::DT_INT64 => Self::from_raw::<i64>(&dims, content)?,
+                    DataType::DT_HALF => Self::from_raw::<f16>(&dims, content)?,
+                    DataType::DT_FLOAT => Self::from_raw::<f32>(&dims, content)?,
+                    DataType::DT_DOUBLE => Self::from_raw::<f64>(&dims, content)?,
+                    DataType::DT_COMPLEX64 => Self::from_raw::<Complex<f32>>(&dims, content)?,
+                    DataType::DT_COMPLEX128 => Self::from_raw::<Complex<f64>>(&dims, content)?,
+                    _ => bail!("Can not load {:?} tensor from raw content", dtype),
                 }
             }
         } else {
             match dtype {
-                DataType::DT_INT32 => Tensor::i32s(&dims, t.get_int_val())?.into(),
-                DataType::DT_FLOAT => Tensor::f32s(&dims, t.get_float_val())?.into(),
-                _ => unimplemented!("missing type"),
+                DataType::DT_BOOL => from_vals(&dims, t.get_bool_val().to_vec())?,
+                DataType::DT_UINT8 => {
+                    from_vals(&dims, t.get_int_val().iter().map(|&x| x as u8).collect())?
+                }
+                DataType::DT_UINT16 => {
+                    from_vals(&dims, t.get_int_val().iter().map(|&x| x as u16).collect())?
+                }
+                DataType::DT_UINT32 => from_vals(&dims, t.get_uint32_val().to_vec())?,
+                DataType::DT_UINT64 => from_vals(&dims, t.get_uint64_val().to_vec())?,
+                DataType::DT_INT8 => {
+                    from_vals(&dims, t.get_int_val().iter().map(|&x| x as i8).collect())?
+                }
+                DataType::DT_INT16 => {
+                    from_vals(&dims, t.get_int_val().iter().map(|&x| x as i16).collect())?
+                }
+                DataType::DT_INT32 => from_vals(&dims, t.get_int_val().to_vec())?,
+                DataType::DT_INT64 => from_vals(&dims, t.get_int64_val().to_vec())?,
+                DataType::DT_HALF => from_vals(
+                    &dims,
+                    t.get_half_val()
+                        .iter()
+                        .map(|&x| f16::from_bits(x as u16))
+                        .collect(),
+                )?,
+                DataType::DT_FLOAT => from_vals(&dims, t.get_float_val().to_vec())?,
+                DataType::DT_DOUBLE => from_vals(&dims, t.get_double_val().to_vec())?,
+                DataType::DT_COMPLEX64 => {
+                    if t.get_scomplex_val().len() % 2 != 0 {
+                        bail!("Odd number of values in scomplex_val")
+                    }
+                    from_vals(
+                        &dims,
+                        t.get_scomplex_val()
+                            .chunks(2)
+                            .map(|c| Complex::new(c[0], c[1]))
+                            .collect(),
+                    )?
+                }
+                DataType::DT_COMPLEX128 => {
+                    if t.get_dcomplex_val().len() % 2 != 0 {
+                        bail!("Odd number of values in dcomplex_val")
+                    }
+                    from_vals(
+                        &dims,
+                        t.get_dcomplex_val()
+                            .chunks(2)
+                            .map(|c| Complex::new(c[0], c[1]))
+                            .collect(),
+                    )?
+                }
+                DataType::DT_STRING => from_vals(&dims, t.get_string_val().to_vec())?,
+                _ => bail!("Can not load {:?} tensor", dtype),
             }
         };
         assert_eq!(rank, mat.shape().len());
@@ -86,21 +176,92 @@ impl ToTensorflow<TensorProto> for Tensor {
         shape.set_dim(::protobuf::RepeatedField::from_vec(dims));
         let mut tensor = TensorProto::new();
         tensor.set_tensor_shape(shape);
+        tensor.set_dtype(self.datum_type().to_tf()?);
         match self {
-            &Tensor::F32(ref it) => {
-                tensor.set_dtype(DatumType::F32.to_tf()?);
-                tensor.set_float_val(it.iter().cloned().collect());
+            &Tensor::Bool(ref it) => tensor.set_bool_val(it.iter().cloned().collect()),
+            &Tensor::U8(ref it) => tensor.set_int_val(it.iter().map(|&x| x as i32).collect()),
+            &Tensor::U16(ref it) => tensor.set_int_val(it.iter().map(|&x| x as i32).collect()),
+            &Tensor::U32(ref it) => tensor.set_uint32_val(it.iter().cloned().collect()),
+            &Tensor::U64(ref it) => tensor.set_uint64_val(it.iter().cloned().collect()),
+            &Tensor::I8(ref it) => tensor.set_int_val(it.iter().map(|&x| x as i32).collect()),
+            &Tensor::I16(ref it) => tensor.set_int_val(it.iter().map(|&x| x as i32).collect()),
+            &Tensor::I32(ref it) => tensor.set_int_val(it.iter().cloned().collect()),
+            &Tensor::I64(ref it) => tensor.set_int64_val(it.iter().cloned().collect()),
+            &Tensor::F16(ref it) => {
+                tensor.set_half_val(it.iter().map(|x| x.to_bits() as i32).collect())
             }
-            &Tensor::F64(ref it) => {
-                tensor.set_dtype(DatumType::F64.to_tf()?);
-                tensor.set_double_val(it.iter().cloned().collect());
+            &Tensor::F32(ref it) => tensor.set_float_val(it.iter().cloned().collect()),
+            &Tensor::F64(ref it) => tensor.set_double_val(it.iter().cloned().collect()),
+            &Tensor::ComplexF32(ref it) => {
+                tensor.set_scomplex_val(it.iter().flat_map(|c| vec![c.re, c.im]).collect())
             }
-            &Tensor::I32(ref it) => {
-                tensor.set_dtype(DatumType::I32.to_tf()?);
-                tensor.set_int_val(it.iter().cloned().collect());
+            &Tensor::ComplexF64(ref it) => {
+                tensor.set_dcomplex_val(it.iter().flat_map(|c| vec![c.re, c.im]).collect())
             }
-            _ => unimplemented!("missing type"),
+            &Tensor::String(ref it) => tensor.set_string_val(it.iter().cloned().collect()),
+            &Tensor::TDim(_) => unreachable!(),
         }
         Ok(tensor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(t: Tensor) {
+        let proto: TensorProto = t.to_tf().unwrap();
+        assert_eq!(Tensor::tfd_from(&proto).unwrap(), t);
+    }
+
+    #[test]
+    fn roundtrip_all_types() {
+        roundtrip(Tensor::bools(&[2], &[true, false]).unwrap());
+        roundtrip(Tensor::u8s(&[2], &[0, 255]).unwrap());
+        roundtrip(Tensor::u16s(&[2], &[0, 65535]).unwrap());
+        roundtrip(Tensor::u32s(&[2], &[0, 1 << 31]).unwrap());
+        roundtrip(Tensor::u64s(&[2], &[0, 1 << 63]).unwrap());
+        roundtrip(Tensor::i8s(&[2], &[-128, 127]).unwrap());
+        roundtrip(Tensor::i16s(&[2], &[-1, 2]).unwrap());
+        roundtrip(Tensor::i32s(&[2, 1], &[-1, 2]).unwrap());
+        roundtrip(Tensor::i64s(&[2], &[-1, 1 << 40]).unwrap());
+        roundtrip(Tensor::f16s(&[1], &[f16::from_f32(0.5)]).unwrap());
+        roundtrip(Tensor::f32s(&[], &[0.5]).unwrap());
+        roundtrip(Tensor::f64s(&[2], &[0.5, -1.0]).unwrap());
+        roundtrip(Tensor::complex_f32s(&[1], &[Complex::new(1.0, 2.0)]).unwrap());
+        roundtrip(Tensor::complex_f64s(&[1], &[Complex::new(1.0, 2.0)]).unwrap());
+        roundtrip(Tensor::strings(&[2], &[b"a".to_vec(), vec![]]).unwrap());
+    }
+
+    #[test]
+    fn single_value_fills_shape() {
+        let mut proto: TensorProto = Tensor::f32s(&[1], &[3.0]).unwrap().to_tf().unwrap();
+        proto.mut_tensor_shape().mut_dim()[0].size = 3;
+        assert_eq!(
+            Tensor::tfd_from(&proto).unwrap(),
+            Tensor::f32s(&[3], &[3.0, 3.0, 3.0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn raw_content() {
+        let mut proto: TensorProto = Tensor::i64s(&[0], &[]).unwrap().to_tf().unwrap();
+        proto.mut_tensor_shape().mut_dim()[0].size = 2;
+        let content: Vec<u8> = [7i64, -1]
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect();
+        proto.set_tensor_content(content);
+        assert_eq!(
+            Tensor::tfd_from(&proto).unwrap(),
+            Tensor::i64s(&[2], &[7, -1]).unwrap()
+        );
+    }
+    #[test]
+    fn odd_complex_values() {
+        let mut proto: TensorProto =
+            Tensor::complex_f32s(&[1], &[Complex::new(1.0, 2.0)]).unwrap().to_tf().unwrap();
+        proto.mut_scomplex_val().push(3.0);
+        assert!(Tensor::tfd_from(&proto).is_err());
+    }
+}
@@ -0,0 +1,1143 @@
+// This file is generated by rust-protobuf 2.0.4. Do not edit
+// @generated
+
+// https://github.com/Manishearth/rust-clippy/issues/702
+
+
+
+use protobuf::Message as Message_imported_for_functions;
+use protobuf::ProtobufEnum as ProtobufEnum_imported_for_functions;
+
+#[derive(PartialEq,Clone,Default)]
+pub struct MetaGraphDef {
+    // message fields
+    pub meta_info_def: ::protobuf::SingularPtrField<MetaGraphDef_MetaInfoDef>,
+    pub graph_def: ::protobuf::SingularPtrField<super::graph::GraphDef>,
+    pub signature_def: ::std::collections::HashMap<::std::string::String, SignatureDef>,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl MetaGraphDef {
+    pub fn new() -> MetaGraphDef {
+        ::std::default::Default::default()
+    }
+
+    // .tensorflow.MetaGraphDef.MetaInfoDef meta_info_def = 1;
+
+    pub fn clear_meta_info_def(&mut self) {
+        self.meta_info_def.clear();
+    }
+
+    pub fn has_meta_info_def(&self) -> bool {
+        self.meta_info_def.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_meta_info_def(&mut self, v: MetaGraphDef_MetaInfoDef) {
+        self.meta_info_def = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_meta_info_def(&mut self) -> &mut MetaGraphDef_MetaInfoDef {
+        if self.meta_info_def.is_none() {
+            self.meta_info_def.set_default();
+        }
+        self.meta_info_def.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_meta_info_def(&mut self) -> MetaGraphDef_MetaInfoDef {
+        self.meta_info_def.take().unwrap_or_else(|| MetaGraphDef_MetaInfoDef::new())
+    }
+
+    pub fn get_meta_info_def(&self) -> &MetaGraphDef_MetaInfoDef {
+        self.meta_info_def.as_ref().unwrap_or_else(|| MetaGraphDef_MetaInfoDef::default_instance())
+    }
+
+    // .tensorflow.GraphDef graph_def = 2;
+
+    pub fn clear_graph_def(&mut self) {
+        self.graph_def.clear();
+    }
+
+    pub fn has_graph_def(&self) -> bool {
+        self.graph_def.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_graph_def(&mut self, v: super::graph::GraphDef) {
+        self.graph_def = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_graph_def(&mut self) -> &mut super::graph::GraphDef {
+        if self.graph_def.is_none() {
+            self.graph_def.set_default();
+        }
+        self.graph_def.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_graph_def(&mut self) -> super::graph::GraphDef {
+        self.graph_def.take().unwrap_or_else(|| super::graph::GraphDef::new())
+    }
+
+    pub fn get_graph_def(&self) -> &super::graph::GraphDef {
+        self.graph_def.as_ref().unwrap_or_else(|| super::graph::GraphDef::default_instance())
+    }
+
+    // repeated .tensorflow.MetaGraphDef.SignatureDefEntry signature_def = 5;
+
+    pub fn clear_signature_def(&mut self) {
+        self.signature_def.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_signature_def(&mut self, v: ::std::collections::HashMap<::std::string::String, SignatureDef>) {
+        self.signature_def = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_signature_def(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, SignatureDef> {
+        &mut self.signature_def
+    }
+
+    // Take field
+    pub fn take_signature_def(&mut self) -> ::std::collections::HashMap<::std::string::String, SignatureDef> {
+        ::std::mem::replace(&mut self.signature_def, ::std::collections::HashMap::new())
+    }
+
+    pub fn get_signature_def(&self) -> &::std::collections::HashMap<::std::string::String, SignatureDef> {
+        &self.signature_def
+    }
+}
+
+impl ::protobuf::Message for MetaGraphDef {
+    fn is_initialized(&self) -> bool {
+        for v in &self.meta_info_def {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.graph_def {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.meta_info_def)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.graph_def)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<SignatureDef>>(wire_type, is, &mut self.signature_def)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.meta_info_def.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.graph_def.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<SignatureDef>>(5, &self.signature_def);
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.meta_info_def.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.graph_def.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<SignatureDef>>(5, &self.signature_def, os)?;
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> MetaGraphDef {
+        MetaGraphDef::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<MetaGraphDef_MetaInfoDef>>(
+                    "meta_info_def",
+                    |m: &MetaGraphDef| { &m.meta_info_def },
+                    |m: &mut MetaGraphDef| { &mut m.meta_info_def },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<super::graph::GraphDef>>(
+                    "graph_def",
+                    |m: &MetaGraphDef| { &m.graph_def },
+                    |m: &mut MetaGraphDef| { &mut m.graph_def },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<SignatureDef>>(
+                    "signature_def",
+                    |m: &MetaGraphDef| { &m.signature_def },
+                    |m: &mut MetaGraphDef| { &mut m.signature_def },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<MetaGraphDef>(
+                    "MetaGraphDef",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static MetaGraphDef {
+        static mut instance: ::protobuf::lazy::Lazy<MetaGraphDef> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const MetaGraphDef,
+        };
+        unsafe {
+            instance.get(MetaGraphDef::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for MetaGraphDef {
+    fn clear(&mut self) {
+        self.clear_meta_info_def();
+        self.clear_graph_def();
+        self.clear_signature_def();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for MetaGraphDef {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for MetaGraphDef {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct MetaGraphDef_MetaInfoDef {
+    // message fields
+    pub meta_graph_version: ::std::string::String,
+    pub tags: ::protobuf::RepeatedField<::std::string::String>,
+    pub tensorflow_version: ::std::string::String,
+    pub tensorflow_git_version: ::std::string::String,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl MetaGraphDef_MetaInfoDef {
+    pub fn new() -> MetaGraphDef_MetaInfoDef {
+        ::std::default::Default::default()
+    }
+
+    // string meta_graph_version = 1;
+
+    pub fn clear_meta_graph_version(&mut self) {
+        self.meta_graph_version.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_meta_graph_version(&mut self, v: ::std::string::String) {
+        self.meta_graph_version = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_meta_graph_version(&mut self) -> &mut ::std::string::String {
+        &mut self.meta_graph_version
+    }
+
+    // Take field
+    pub fn take_meta_graph_version(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.meta_graph_version, ::std::string::String::new())
+    }
+
+    pub fn get_meta_graph_version(&self) -> &str {
+        &self.meta_graph_version
+    }
+
+    // repeated string tags = 4;
+
+    pub fn clear_tags(&mut self) {
+        self.tags.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tags(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.tags = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_tags(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.tags
+    }
+
+    // Take field
+    pub fn take_tags(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.tags, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_tags(&self) -> &[::std::string::String] {
+        &self.tags
+    }
+
+    // string tensorflow_version = 5;
+
+    pub fn clear_tensorflow_version(&mut self) {
+        self.tensorflow_version.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tensorflow_version(&mut self, v: ::std::string::String) {
+        self.tensorflow_version = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_tensorflow_version(&mut self) -> &mut ::std::string::String {
+        &mut self.tensorflow_version
+    }
+
+    // Take field
+    pub fn take_tensorflow_version(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.tensorflow_version, ::std::string::String::new())
+    }
+
+    pub fn get_tensorflow_version(&self) -> &str {
+        &self.tensorflow_version
+    }
+
+    // string tensorflow_git_version = 6;
+
+    pub fn clear_tensorflow_git_version(&mut self) {
+        self.tensorflow_git_version.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tensorflow_git_version(&mut self, v: ::std::string::String) {
+        self.tensorflow_git_version = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_tensorflow_git_version(&mut self) -> &mut ::std::string::String {
+        &mut self.tensorflow_git_version
+    }
+
+    // Take field
+    pub fn take_tensorflow_git_version(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.tensorflow_git_version, ::std::string::String::new())
+    }
+
+    pub fn get_tensorflow_git_version(&self) -> &str {
+        &self.tensorflow_git_version
+    }
+}
+
+impl ::protobuf::Message for MetaGraphDef_MetaInfoDef {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.meta_graph_version)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.tags)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.tensorflow_version)?;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.tensorflow_git_version)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.meta_graph_version.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.meta_graph_version);
+        }
+        for value in &self.tags {
+            my_size += ::protobuf::rt::string_size(4, &value);
+        };
+        if !self.tensorflow_version.is_empty() {
+            my_size += ::protobuf::rt::string_size(5, &self.tensorflow_version);
+        }
+        if !self.tensorflow_git_version.is_empty() {
+            my_size += ::protobuf::rt::string_size(6, &self.tensorflow_git_version);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if !self.meta_graph_version.is_empty() {
+            os.write_string(1, &self.meta_graph_version)?;
+        }
+        for v in &self.tags {
+            os.write_string(4, &v)?;
+        };
+        if !self.tensorflow_version.is_empty() {
+            os.write_string(5, &self.tensorflow_version)?;
+        }
+        if !self.tensorflow_git_version.is_empty() {
+            os.write_string(6, &self.tensorflow_git_version)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> MetaGraphDef_MetaInfoDef {
+        MetaGraphDef_MetaInfoDef::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "meta_graph_version",
+                    |m: &MetaGraphDef_MetaInfoDef| { &m.meta_graph_version },
+                    |m: &mut MetaGraphDef_MetaInfoDef| { &mut m.meta_graph_version },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "tags",
+                    |m: &MetaGraphDef_MetaInfoDef| { &m.tags },
+                    |m: &mut MetaGraphDef_MetaInfoDef| { &mut m.tags },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "tensorflow_version",
+                    |m: &MetaGraphDef_MetaInfoDef| { &m.tensorflow_version },
+                    |m: &mut MetaGraphDef_MetaInfoDef| { &mut m.tensorflow_version },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "tensorflow_git_version",
+                    |m: &MetaGraphDef_MetaInfoDef| { &m.tensorflow_git_version },
+                    |m: &mut MetaGraphDef_MetaInfoDef| { &mut m.tensorflow_git_version },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<MetaGraphDef_MetaInfoDef>(
+                    "MetaGraphDef_MetaInfoDef",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static MetaGraphDef_MetaInfoDef {
+        static mut instance: ::protobuf::lazy::Lazy<MetaGraphDef_MetaInfoDef> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const MetaGraphDef_MetaInfoDef,
+        };
+        unsafe {
+            instance.get(MetaGraphDef_MetaInfoDef::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for MetaGraphDef_MetaInfoDef {
+    fn clear(&mut self) {
+        self.clear_meta_graph_version();
+        self.clear_tags();
+        self.clear_tensorflow_version();
+        self.clear_tensorflow_git_version();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for MetaGraphDef_MetaInfoDef {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for MetaGraphDef_MetaInfoDef {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct TensorInfo {
+    // message fields
+    pub dtype: super::types::DataType,
+    pub tensor_shape: ::protobuf::SingularPtrField<super::tensor_shape::TensorShapeProto>,
+    // message oneof groups
+    pub encoding: ::std::option::Option<TensorInfo_oneof_encoding>,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+#[derive(Clone,PartialEq)]
+pub enum TensorInfo_oneof_encoding {
+    name(::std::string::String),
+}
+
+impl TensorInfo {
+    pub fn new() -> TensorInfo {
+        ::std::default::Default::default()
+    }
+
+    // string name = 1;
+
+    pub fn clear_name(&mut self) {
+        self.encoding = ::std::option::Option::None;
+    }
+
+    pub fn has_name(&self) -> bool {
+        match self.encoding {
+            ::std::option::Option::Some(TensorInfo_oneof_encoding::name(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.encoding = ::std::option::Option::Some(TensorInfo_oneof_encoding::name(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        if let ::std::option::Option::Some(TensorInfo_oneof_encoding::name(_)) = self.encoding {
+        } else {
+            self.encoding = ::std::option::Option::Some(TensorInfo_oneof_encoding::name(::std::string::String::new()));
+        }
+        match self.encoding {
+            ::std::option::Option::Some(TensorInfo_oneof_encoding::name(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        if self.has_name() {
+            match self.encoding.take() {
+                ::std::option::Option::Some(TensorInfo_oneof_encoding::name(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ::std::string::String::new()
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self.encoding {
+            ::std::option::Option::Some(TensorInfo_oneof_encoding::name(ref v)) => v,
+            _ => "",
+        }
+    }
+
+    // .tensorflow.DataType dtype = 2;
+
+    pub fn clear_dtype(&mut self) {
+        self.dtype = super::types::DataType::DT_INVALID;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_dtype(&mut self, v: super::types::DataType) {
+        self.dtype = v;
+    }
+
+    pub fn get_dtype(&self) -> super::types::DataType {
+        self.dtype
+    }
+
+    // .tensorflow.TensorShapeProto tensor_shape = 3;
+
+    pub fn clear_tensor_shape(&mut self) {
+        self.tensor_shape.clear();
+    }
+
+    pub fn has_tensor_shape(&self) -> bool {
+        self.tensor_shape.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tensor_shape(&mut self, v: super::tensor_shape::TensorShapeProto) {
+        self.tensor_shape = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_tensor_shape(&mut self) -> &mut super::tensor_shape::TensorShapeProto {
+        if self.tensor_shape.is_none() {
+            self.tensor_shape.set_default();
+        }
+        self.tensor_shape.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_tensor_shape(&mut self) -> super::tensor_shape::TensorShapeProto {
+        self.tensor_shape.take().unwrap_or_else(|| super::tensor_shape::TensorShapeProto::new())
+    }
+
+    pub fn get_tensor_shape(&self) -> &super::tensor_shape::TensorShapeProto {
+        self.tensor_shape.as_ref().unwrap_or_else(|| super::tensor_shape::TensorShapeProto::default_instance())
+    }
+}
+
+impl ::protobuf::Message for TensorInfo {
+    fn is_initialized(&self) -> bool {
+        for v in &self.tensor_shape {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.encoding = ::std::option::Option::Some(TensorInfo_oneof_encoding::name(is.read_string()?));
+                },
+                2 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.dtype, 2, &mut self.unknown_fields)?
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.tensor_shape)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.dtype != super::types::DataType::DT_INVALID {
+            my_size += ::protobuf::rt::enum_size(2, self.dtype);
+        }
+        if let Some(ref v) = self.tensor_shape.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let ::std::option::Option::Some(ref v) = self.encoding {
+            match v {
+                &TensorInfo_oneof_encoding::name(ref v) => {
+                    my_size += ::protobuf::rt::string_size(1, &v);
+                },
+            };
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if self.dtype != super::types::DataType::DT_INVALID {
+            os.write_enum(2, self.dtype.value())?;
+        }
+        if let Some(ref v) = self.tensor_shape.as_ref() {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let ::std::option::Option::Some(ref v) = self.encoding {
+            match v {
+                &TensorInfo_oneof_encoding::name(ref v) => {
+                    os.write_string(1, v)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> TensorInfo {
+        TensorInfo::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor::<_>(
+                    "name",
+                    TensorInfo::has_name,
+                    TensorInfo::get_name,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<super::types::DataType>>(
+                    "dtype",
+                    |m: &TensorInfo| { &m.dtype },
+                    |m: &mut TensorInfo| { &mut m.dtype },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<super::tensor_shape::TensorShapeProto>>(
+                    "tensor_shape",
+                    |m: &TensorInfo| { &m.tensor_shape },
+                    |m: &mut TensorInfo| { &mut m.tensor_shape },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<TensorInfo>(
+                    "TensorInfo",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static TensorInfo {
+        static mut instance: ::protobuf::lazy::Lazy<TensorInfo> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const TensorInfo,
+        };
+        unsafe {
+            instance.get(TensorInfo::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for TensorInfo {
+    fn clear(&mut self) {
+        self.clear_name();
+        self.clear_dtype();
+        self.clear_tensor_shape();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for TensorInfo {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for TensorInfo {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SignatureDef {
+    // message fields
+    pub inputs: ::std::collections::HashMap<::std::string::String, TensorInfo>,
+    pub outputs: ::std::collections::HashMap<::std::string::String, TensorInfo>,
+    pub method_name: ::std::string::String,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::protobuf::CachedSize,
+}
+
+impl SignatureDef {
+    pub fn new() -> SignatureDef {
+        ::std::default::Default::default()
+    }
+
+    // repeated .tensorflow.SignatureDef.InputsEntry inputs = 1;
+
+    pub fn clear_inputs(&mut self) {
+        self.inputs.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_inputs(&mut self, v: ::std::collections::HashMap<::std::string::String, TensorInfo>) {
+        self.inputs = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_inputs(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, TensorInfo> {
+        &mut self.inputs
+    }
+
+    // Take field
+    pub fn take_inputs(&mut self) -> ::std::collections::HashMap<::std::string::String, TensorInfo> {
+        ::std::mem::replace(&mut self.inputs, ::std::collections::HashMap::new())
+    }
+
+    pub fn get_inputs(&self) -> &::std::collections::HashMap<::std::string::String, TensorInfo> {
+        &self.inputs
+    }
+
+    // repeated .tensorflow.SignatureDef.OutputsEntry outputs = 2;
+
+    pub fn clear_outputs(&mut self) {
+        self.outputs.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_outputs(&mut self, v: ::std::collections::HashMap<::std::string::String, TensorInfo>) {
+        self.outputs = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_outputs(&mut self) -> &mut ::std::collections::HashMap<::std::string::String, TensorInfo> {
+        &mut self.outputs
+    }
+
+    // Take field
+    pub fn take_outputs(&mut self) -> ::std::collections::HashMap<::std::string::String, TensorInfo> {
+        ::std::mem::replace(&mut self.outputs, ::std::collections::HashMap::new())
+    }
+
+    pub fn get_outputs(&self) -> &::std::collections::HashMap<::std::string::String, TensorInfo> {
+        &self.outputs
+    }
+
+    // string method_name = 3;
+
+    pub fn clear_method_name(&mut self) {
+        self.method_name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_method_name(&mut self, v: ::std::string::String) {
+        self.method_name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_method_name(&mut self) -> &mut ::std::string::String {
+        &mut self.method_name
+    }
+
+    // Take field
+    pub fn take_method_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.method_name, ::std::string::String::new())
+    }
+
+    pub fn get_method_name(&self) -> &str {
+        &self.method_name
+    }
+}
+
+impl ::protobuf::Message for SignatureDef {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TensorInfo>>(wire_type, is, &mut self.inputs)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_map_into::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TensorInfo>>(wire_type, is, &mut self.outputs)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.method_name)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TensorInfo>>(1, &self.inputs);
+        my_size += ::protobuf::rt::compute_map_size::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TensorInfo>>(2, &self.outputs);
+        if !self.method_name.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.method_name);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TensorInfo>>(1, &self.inputs, os)?;
+        ::protobuf::rt::write_map_with_cached_sizes::<::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TensorInfo>>(2, &self.outputs, os)?;
+        if !self.method_name.is_empty() {
+            os.write_string(3, &self.method_name)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
+        self as &mut ::std::any::Any
+    }
+    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SignatureDef {
+        SignatureDef::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TensorInfo>>(
+                    "inputs",
+                    |m: &SignatureDef| { &m.inputs },
+                    |m: &mut SignatureDef| { &mut m.inputs },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_map_accessor::<_, ::protobuf::types::ProtobufTypeString, ::protobuf::types::ProtobufTypeMessage<TensorInfo>>(
+                    "outputs",
+                    |m: &SignatureDef| { &m.outputs },
+                    |m: &mut SignatureDef| { &mut m.outputs },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                    "method_name",
+                    |m: &SignatureDef| { &m.method_name },
+                    |m: &mut SignatureDef| { &mut m.method_name },
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<SignatureDef>(
+                    "SignatureDef",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+
+    fn default_instance() -> &'static SignatureDef {
+        static mut instance: ::protobuf::lazy::Lazy<SignatureDef> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const SignatureDef,
+        };
+        unsafe {
+            instance.get(SignatureDef::new)
+        }
+    }
+}
+
+impl ::protobuf::Clear for SignatureDef {
+    fn clear(&mut self) {
+        self.clear_inputs();
+        self.clear_outputs();
+        self.clear_method_name();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SignatureDef {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SignatureDef {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    }
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n)tensorflow/core/protobuf/meta_graph.proto\x12\ntensorflow\x1a%tensorf\
+    low/core/framework/graph.proto\x1a,tensorflow/core/framework/tensor_shap\
+    e.proto\x1a%tensorflow/core/framework/types.proto\"\xee\x03\n\x0cMetaGra\
+    phDef\x12H\n\rmeta_info_def\x18\x01\x20\x01(\x0b2$.tensorflow.MetaGraphD\
+    ef.MetaInfoDefR\x0bmetaInfoDef\x121\n\tgraph_def\x18\x02\x20\x01(\x0b2\
+    \x14.tensorflow.GraphDefR\x08graphDef\x12O\n\rsignature_def\x18\x05\x20\
+    \x03(\x0b2*.tensorflow.MetaGraphDef.SignatureDefEntryR\x0csignatureDef\
+    \x1a\xb4\x01\n\x0bMetaInfoDef\x12,\n\x12meta_graph_version\x18\x01\x20\
+    \x01(\tR\x10metaGraphVersion\x12\x12\n\x04tags\x18\x04\x20\x03(\tR\x04ta\
+    gs\x12-\n\x12tensorflow_version\x18\x05\x20\x01(\tR\x11tensorflowVersion\
+    \x124\n\x16tensorflow_git_version\x18\x06\x20\x01(\tR\x14tensorflowGitVe\
+    rsion\x1aY\n\x11SignatureDefEntry\x12\x10\n\x03key\x18\x01\x20\x01(\tR\
+    \x03key\x12.\n\x05value\x18\x02\x20\x01(\x0b2\x18.tensorflow.SignatureDe\
+    fR\x05value:\x028\x01\"\x9b\x01\n\nTensorInfo\x12\x14\n\x04name\x18\x01\
+    \x20\x01(\tH\0R\x04name\x12*\n\x05dtype\x18\x02\x20\x01(\x0e2\x14.tensor\
+    flow.DataTypeR\x05dtype\x12?\n\x0ctensor_shape\x18\x03\x20\x01(\x0b2\x1c\
+    .tensorflow.TensorShapeProtoR\x0btensorShapeB\n\n\x08encoding\"\xd5\x02\
+    \n\x0cSignatureDef\x12<\n\x06inputs\x18\x01\x20\x03(\x0b2$.tensorflow.Si\
+    gnatureDef.InputsEntryR\x06inputs\x12?\n\x07outputs\x18\x02\x20\x03(\x0b\
+    2%.tensorflow.SignatureDef.OutputsEntryR\x07outputs\x12\x1f\n\x0bmethod_\
+    name\x18\x03\x20\x01(\tR\nmethodName\x1aQ\n\x0bInputsEntry\x12\x10\n\x03\
+    key\x18\x01\x20\x01(\tR\x03key\x12,\n\x05value\x18\x02\x20\x01(\x0b2\x16\
+    .tensorflow.TensorInfoR\x05value:\x028\x01\x1aR\n\x0cOutputsEntry\x12\
+    \x10\n\x03key\x18\x01\x20\x01(\tR\x03key\x12,\n\x05value\x18\x02\x20\x01\
+    (\x0b2\x16.tensorflow.TensorInfoR\x05value:\x028\x01B0\n\x18org.tensorfl\
+    ow.frameworkB\x0fMetaGraphProtosP\x01\xf8\x01\x01b\x06proto3\
+";
+
+static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
+    lock: ::protobuf::lazy::ONCE_INIT,
+    ptr: 0 as *const ::protobuf::descriptor::FileDescriptorProto,
+};
+
+fn parse_descriptor_proto() -> ::protobuf::descriptor::FileDescriptorProto {
+    ::protobuf::parse_from_bytes(file_descriptor_proto_data).unwrap()
+}
+
+pub fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    unsafe {
+        file_descriptor_proto_lazy.get(|| {
+            parse_descriptor_proto()
+        })
+    }
+}
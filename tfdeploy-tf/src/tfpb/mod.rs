@@ -20,10 +20,13 @@
 pub mod attr_value;
 pub mod function;
 pub mod graph;
+pub mod meta_graph;
 pub mod node_def;
 pub mod op_def;
 pub mod resource_handle;
+pub mod saved_model;
 pub mod tensor;
+pub mod tensor_bundle;
 pub mod tensor_shape;
 pub mod types;
 pub mod versions;
@@ -139,6 +142,28 @@ impl node_def::NodeDef {
         }
     }
 
+    pub fn get_attr_opt_shape(&self, name: &str) -> TfdResult<Option<&tensor_shape::TensorShapeProto>> {
+        Ok(self.get_attr().get(name).map(|v| v.get_shape()))
+    }
+
+    pub fn get_attr_bool(&self, name: &str) -> TfdResult<bool> {
+        Ok(self.get_attr_opt_bool(name)?
+            .ok_or_else(|| format!("Node {} ({}) expected bool attribute '{}'", self.get_name(), self.get_op(), name))?)
+    }
+
+    pub fn get_attr_opt_bool(&self, name: &str) -> TfdResult<Option<bool>> {
+        Ok(self.get_attr().get(name).map(|v| v.get_b()))
+    }
+
+    pub fn get_attr_float(&self, name: &str) -> TfdResult<f32> {
+        Ok(self.get_attr_opt_float(name)?
+            .ok_or_else(|| format!("Node {} ({}) expected float attribute '{}'", self.get_name(), self.get_op(), name))?)
+    }
+
+    pub fn get_attr_opt_float(&self, name: &str) -> TfdResult<Option<f32>> {
+        Ok(self.get_attr().get(name).map(|v| v.get_f()))
+    }
+
     pub fn get_attr_int<T: ::num::FromPrimitive>(&self, name: &str) -> TfdResult<T> {
         Ok(self.get_attr_opt_int(name)?
             .ok_or_else(|| format!("Node {} ({}) expected int attribute '{}'", self.get_name(), self.get_op(), name))?)
@@ -168,6 +193,14 @@ impl node_def::NodeDef {
     }
 }
 
+impl From<bool> for AttrValue {
+    fn from(b: bool) -> AttrValue {
+        let mut value = AttrValue::new();
+        value.set_b(b);
+        value
+    }
+}
+
 impl From<types::DataType> for AttrValue {
     fn from(t: types::DataType) -> AttrValue {
         let mut dt = AttrValue::new();
@@ -25,6 +25,8 @@ pub struct TensorProto {
     pub bool_val: ::std::vec::Vec<bool>,
     pub dcomplex_val: ::std::vec::Vec<f64>,
     pub resource_handle_val: ::protobuf::RepeatedField<super::resource_handle::ResourceHandle>,
+    pub uint32_val: ::std::vec::Vec<u32>,
+    pub uint64_val: ::std::vec::Vec<u64>,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::protobuf::CachedSize,
@@ -373,6 +375,56 @@ impl TensorProto {
     pub fn get_resource_handle_val(&self) -> &[super::resource_handle::ResourceHandle] {
         &self.resource_handle_val
     }
+
+    // repeated uint32 uint32_val = 16;
+
+    pub fn clear_uint32_val(&mut self) {
+        self.uint32_val.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_uint32_val(&mut self, v: ::std::vec::Vec<u32>) {
+        self.uint32_val = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_uint32_val(&mut self) -> &mut ::std::vec::Vec<u32> {
+        &mut self.uint32_val
+    }
+
+    // Take field
+    pub fn take_uint32_val(&mut self) -> ::std::vec::Vec<u32> {
+        ::std::mem::replace(&mut self.uint32_val, ::std::vec::Vec::new())
+    }
+
+    pub fn get_uint32_val(&self) -> &[u32] {
+        &self.uint32_val
+    }
+
+    // repeated uint64 uint64_val = 17;
+
+    pub fn clear_uint64_val(&mut self) {
+        self.uint64_val.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_uint64_val(&mut self, v: ::std::vec::Vec<u64>) {
+        self.uint64_val = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_uint64_val(&mut self) -> &mut ::std::vec::Vec<u64> {
+        &mut self.uint64_val
+    }
+
+    // Take field
+    pub fn take_uint64_val(&mut self) -> ::std::vec::Vec<u64> {
+        ::std::mem::replace(&mut self.uint64_val, ::std::vec::Vec::new())
+    }
+
+    pub fn get_uint64_val(&self) -> &[u64] {
+        &self.uint64_val
+    }
 }
 
 impl ::protobuf::Message for TensorProto {
@@ -440,6 +492,12 @@ impl ::protobuf::Message for TensorProto {
                 14 => {
                     ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.resource_handle_val)?;
                 },
+                16 => {
+                    ::protobuf::rt::read_repeated_uint32_into(wire_type, is, &mut self.uint32_val)?;
+                },
+                17 => {
+                    ::protobuf::rt::read_repeated_uint64_into(wire_type, is, &mut self.uint64_val)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -496,6 +554,12 @@ impl ::protobuf::Message for TensorProto {
             let len = value.compute_size();
             my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
         };
+        if !self.uint32_val.is_empty() {
+            my_size += ::protobuf::rt::vec_packed_varint_size(16, &self.uint32_val);
+        }
+        if !self.uint64_val.is_empty() {
+            my_size += ::protobuf::rt::vec_packed_varint_size(17, &self.uint64_val);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -588,6 +652,22 @@ impl ::protobuf::Message for TensorProto {
             os.write_raw_varint32(v.get_cached_size())?;
             v.write_to_with_cached_sizes(os)?;
         };
+        if !self.uint32_val.is_empty() {
+            os.write_tag(16, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            // TODO: Data size is computed again, it should be cached
+            os.write_raw_varint32(::protobuf::rt::vec_packed_varint_data_size(&self.uint32_val))?;
+            for v in &self.uint32_val {
+                os.write_uint32_no_tag(*v)?;
+            };
+        }
+        if !self.uint64_val.is_empty() {
+            os.write_tag(17, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            // TODO: Data size is computed again, it should be cached
+            os.write_raw_varint32(::protobuf::rt::vec_packed_varint_data_size(&self.uint64_val))?;
+            for v in &self.uint64_val {
+                os.write_uint64_no_tag(*v)?;
+            };
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -700,6 +780,16 @@ impl ::protobuf::Message for TensorProto {
                     |m: &TensorProto| { &m.resource_handle_val },
                     |m: &mut TensorProto| { &mut m.resource_handle_val },
                 ));
+                fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                    "uint32_val",
+                    |m: &TensorProto| { &m.uint32_val },
+                    |m: &mut TensorProto| { &mut m.uint32_val },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                    "uint64_val",
+                    |m: &TensorProto| { &m.uint64_val },
+                    |m: &mut TensorProto| { &mut m.uint64_val },
+                ));
                 ::protobuf::reflect::MessageDescriptor::new::<TensorProto>(
                     "TensorProto",
                     fields,
@@ -736,6 +826,8 @@ impl ::protobuf::Clear for TensorProto {
         self.clear_bool_val();
         self.clear_dcomplex_val();
         self.clear_resource_handle_val();
+        self.clear_uint32_val();
+        self.clear_uint64_val();
         self.unknown_fields.clear();
     }
 }
@@ -755,7 +847,7 @@ impl ::protobuf::reflect::ProtobufValue for TensorProto {
 static file_descriptor_proto_data: &'static [u8] = b"\
     \n&tensorflow/core/framework/tensor.proto\x12\ntensorflow\x1a/tensorflow\
     /core/framework/resource_handle.proto\x1a,tensorflow/core/framework/tens\
-    or_shape.proto\x1a%tensorflow/core/framework/types.proto\"\xc1\x04\n\x0b\
+    or_shape.proto\x1a%tensorflow/core/framework/types.proto\"\x87\x05\n\x0b\
     TensorProto\x12*\n\x05dtype\x18\x01\x20\x01(\x0e2\x14.tensorflow.DataTyp\
     eR\x05dtype\x12?\n\x0ctensor_shape\x18\x02\x20\x01(\x0b2\x1c.tensorflow.\
     TensorShapeProtoR\x0btensorShape\x12%\n\x0eversion_number\x18\x03\x20\
@@ -770,8 +862,10 @@ static file_descriptor_proto_data: &'static [u8] = b"\
     \x18\x0b\x20\x03(\x08R\x07boolValB\x02\x10\x01\x12%\n\x0cdcomplex_val\
     \x18\x0c\x20\x03(\x01R\x0bdcomplexValB\x02\x10\x01\x12J\n\x13resource_ha\
     ndle_val\x18\x0e\x20\x03(\x0b2\x1a.tensorflow.ResourceHandleR\x11resourc\
-    eHandleValB-\n\x18org.tensorflow.frameworkB\x0cTensorProtosP\x01\xf8\x01\
-    \x01b\x06proto3\
+    eHandleVal\x12!\n\nuint32_val\x18\x10\x20\x03(\rR\tuint32ValB\x02\x10\
+    \x01\x12!\n\nuint64_val\x18\x11\x20\x03(\x04R\tuint64ValB\x02\x10\x01B-\
+    \n\x18org.tensorflow.frameworkB\x0cTensorProtosP\x01\xf8\x01\x01b\x06pro\
+    to3\
 ";
 
 static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
@@ -31,6 +31,9 @@ pub enum DataType {
     DT_COMPLEX128 = 18,
     DT_HALF = 19,
     DT_RESOURCE = 20,
+    DT_VARIANT = 21,
+    DT_UINT32 = 22,
+    DT_UINT64 = 23,
     DT_FLOAT_REF = 101,
     DT_DOUBLE_REF = 102,
     DT_INT32_REF = 103,
@@ -51,6 +54,9 @@ pub enum DataType {
     DT_COMPLEX128_REF = 118,
     DT_HALF_REF = 119,
     DT_RESOURCE_REF = 120,
+    DT_VARIANT_REF = 121,
+    DT_UINT32_REF = 122,
+    DT_UINT64_REF = 123,
 }
 
 impl ::protobuf::ProtobufEnum for DataType {
@@ -81,6 +87,9 @@ impl ::protobuf::ProtobufEnum for DataType {
             18 => ::std::option::Option::Some(DataType::DT_COMPLEX128),
             19 => ::std::option::Option::Some(DataType::DT_HALF),
             20 => ::std::option::Option::Some(DataType::DT_RESOURCE),
+            21 => ::std::option::Option::Some(DataType::DT_VARIANT),
+            22 => ::std::option::Option::Some(DataType::DT_UINT32),
+            23 => ::std::option::Option::Some(DataType::DT_UINT64),
             101 => ::std::option::Option::Some(DataType::DT_FLOAT_REF),
             102 => ::std::option::Option::Some(DataType::DT_DOUBLE_REF),
             103 => ::std::option::Option::Some(DataType::DT_INT32_REF),
@@ -101,6 +110,9 @@ impl ::protobuf::ProtobufEnum for DataType {
             118 => ::std::option::Option::Some(DataType::DT_COMPLEX128_REF),
             119 => ::std::option::Option::Some(DataType::DT_HALF_REF),
             120 => ::std::option::Option::Some(DataType::DT_RESOURCE_REF),
+            121 => ::std::option::Option::Some(DataType::DT_VARIANT_REF),
+            122 => ::std::option::Option::Some(DataType::DT_UINT32_REF),
+            123 => ::std::option::Option::Some(DataType::DT_UINT64_REF),
             _ => ::std::option::Option::None
         }
     }
@@ -128,6 +140,9 @@ impl ::protobuf::ProtobufEnum for DataType {
             DataType::DT_COMPLEX128,
             DataType::DT_HALF,
             DataType::DT_RESOURCE,
+            DataType::DT_VARIANT,
+            DataType::DT_UINT32,
+            DataType::DT_UINT64,
             DataType::DT_FLOAT_REF,
             DataType::DT_DOUBLE_REF,
             DataType::DT_INT32_REF,
@@ -148,6 +163,9 @@ impl ::protobuf::ProtobufEnum for DataType {
             DataType::DT_COMPLEX128_REF,
             DataType::DT_HALF_REF,
             DataType::DT_RESOURCE_REF,
+            DataType::DT_VARIANT_REF,
+            DataType::DT_UINT32_REF,
+            DataType::DT_UINT64_REF,
         ];
         values
     }
@@ -181,7 +199,7 @@ impl ::protobuf::reflect::ProtobufValue for DataType {
 }
 
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n%tensorflow/core/framework/types.proto\x12\ntensorflow*\xc2\x05\n\x08D\
+    \n%tensorflow/core/framework/types.proto\x12\ntensorflow*\xaa\x06\n\x08D\
     ataType\x12\x0e\n\nDT_INVALID\x10\0\x12\x0c\n\x08DT_FLOAT\x10\x01\x12\r\
     \n\tDT_DOUBLE\x10\x02\x12\x0c\n\x08DT_INT32\x10\x03\x12\x0c\n\x08DT_UINT\
     8\x10\x04\x12\x0c\n\x08DT_INT16\x10\x05\x12\x0b\n\x07DT_INT8\x10\x06\x12\
@@ -190,17 +208,20 @@ static file_descriptor_proto_data: &'static [u8] = b"\
     \x12\r\n\tDT_QUINT8\x10\x0c\x12\r\n\tDT_QINT32\x10\r\x12\x0f\n\x0bDT_BFL\
     OAT16\x10\x0e\x12\r\n\tDT_QINT16\x10\x0f\x12\x0e\n\nDT_QUINT16\x10\x10\
     \x12\r\n\tDT_UINT16\x10\x11\x12\x11\n\rDT_COMPLEX128\x10\x12\x12\x0b\n\
-    \x07DT_HALF\x10\x13\x12\x0f\n\x0bDT_RESOURCE\x10\x14\x12\x10\n\x0cDT_FLO\
-    AT_REF\x10e\x12\x11\n\rDT_DOUBLE_REF\x10f\x12\x10\n\x0cDT_INT32_REF\x10g\
-    \x12\x10\n\x0cDT_UINT8_REF\x10h\x12\x10\n\x0cDT_INT16_REF\x10i\x12\x0f\n\
-    \x0bDT_INT8_REF\x10j\x12\x11\n\rDT_STRING_REF\x10k\x12\x14\n\x10DT_COMPL\
-    EX64_REF\x10l\x12\x10\n\x0cDT_INT64_REF\x10m\x12\x0f\n\x0bDT_BOOL_REF\
-    \x10n\x12\x10\n\x0cDT_QINT8_REF\x10o\x12\x11\n\rDT_QUINT8_REF\x10p\x12\
-    \x11\n\rDT_QINT32_REF\x10q\x12\x13\n\x0fDT_BFLOAT16_REF\x10r\x12\x11\n\r\
-    DT_QINT16_REF\x10s\x12\x12\n\x0eDT_QUINT16_REF\x10t\x12\x11\n\rDT_UINT16\
-    _REF\x10u\x12\x15\n\x11DT_COMPLEX128_REF\x10v\x12\x0f\n\x0bDT_HALF_REF\
-    \x10w\x12\x13\n\x0fDT_RESOURCE_REF\x10xB,\n\x18org.tensorflow.frameworkB\
-    \x0bTypesProtosP\x01\xf8\x01\x01b\x06proto3\
+    \x07DT_HALF\x10\x13\x12\x0f\n\x0bDT_RESOURCE\x10\x14\x12\x0e\n\nDT_VARIA\
+    NT\x10\x15\x12\r\n\tDT_UINT32\x10\x16\x12\r\n\tDT_UINT64\x10\x17\x12\x10\
+    \n\x0cDT_FLOAT_REF\x10e\x12\x11\n\rDT_DOUBLE_REF\x10f\x12\x10\n\x0cDT_IN\
+    T32_REF\x10g\x12\x10\n\x0cDT_UINT8_REF\x10h\x12\x10\n\x0cDT_INT16_REF\
+    \x10i\x12\x0f\n\x0bDT_INT8_REF\x10j\x12\x11\n\rDT_STRING_REF\x10k\x12\
+    \x14\n\x10DT_COMPLEX64_REF\x10l\x12\x10\n\x0cDT_INT64_REF\x10m\x12\x0f\n\
+    \x0bDT_BOOL_REF\x10n\x12\x10\n\x0cDT_QINT8_REF\x10o\x12\x11\n\rDT_QUINT8\
+    _REF\x10p\x12\x11\n\rDT_QINT32_REF\x10q\x12\x13\n\x0fDT_BFLOAT16_REF\x10\
+    r\x12\x11\n\rDT_QINT16_REF\x10s\x12\x12\n\x0eDT_QUINT16_REF\x10t\x12\x11\
+    \n\rDT_UINT16_REF\x10u\x12\x15\n\x11DT_COMPLEX128_REF\x10v\x12\x0f\n\x0b\
+    DT_HALF_REF\x10w\x12\x13\n\x0fDT_RESOURCE_REF\x10x\x12\x12\n\x0eDT_VARIA\
+    NT_REF\x10y\x12\x11\n\rDT_UINT32_REF\x10z\x12\x11\n\rDT_UINT64_REF\x10{B\
+    ,\n\x18org.tensorflow.frameworkB\x0bTypesProtosP\x01\xf8\x01\x01b\x06pro\
+    to3\
 ";
 
 static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {